@@ -0,0 +1,254 @@
+//! Достижения игроков.
+//!
+//! После каждого раунда обновляется статистика игрока ([`PlayerStats`]), и все правила из
+//! [`ACHIEVEMENTS`] проверяются заново. Правило — это данные: игра (или любая), счетчик и
+//! порог. Новое достижение добавляется строкой в таблицу и парой строк в каталог
+//! `i18n` (`achievement.<id>.title`, `achievement.<id>.description`), без правок `bot.rs`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::state::GameType;
+
+/// Статистика одной игры
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameStats {
+    pub rounds: u32,
+    pub wins: u32,
+    /// Текущая серия побед подряд
+    pub streak: u32,
+    /// Лучшая серия побед подряд
+    pub best_streak: u32,
+    /// Сколько раз выпадала каждая грань (индекс 0 — единица)
+    pub faces: [u32; 6],
+}
+
+/// Статистика игрока по всем играм
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStats {
+    pub games: HashMap<GameType, GameStats>,
+}
+
+impl PlayerStats {
+    /// Учет сыгранного раунда
+    pub fn record(&mut self, game: &GameType, dice: u8, win: bool) {
+        let stats = self.games.entry(game.clone()).or_default();
+        stats.rounds += 1;
+        if win {
+            stats.wins += 1;
+            stats.streak += 1;
+            stats.best_streak = stats.best_streak.max(stats.streak);
+        } else {
+            stats.streak = 0;
+        }
+        if let Some(face) = stats.faces.get_mut(usize::from(dice).wrapping_sub(1)) {
+            *face += 1;
+        }
+    }
+
+    fn total(&self, metric: Metric) -> u32 {
+        self.games.values().map(|stats| metric.of(stats)).sum()
+    }
+}
+
+/// Счетчик, который проверяет правило
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    Rounds,
+    Wins,
+    /// Лучшая серия побед подряд
+    BestStreak,
+    /// Сколько раз выпала грань
+    Face(u8),
+}
+
+impl Metric {
+    fn of(self, stats: &GameStats) -> u32 {
+        match self {
+            Metric::Rounds => stats.rounds,
+            Metric::Wins => stats.wins,
+            Metric::BestStreak => stats.best_streak,
+            Metric::Face(face) => stats
+                .faces
+                .get(usize::from(face).wrapping_sub(1))
+                .copied()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Достижение: значок и правило получения
+#[derive(Debug, PartialEq)]
+pub struct Achievement {
+    /// Постоянный идентификатор: хранится в профиле и задает ключи строк
+    pub id: &'static str,
+    pub badge: &'static str,
+    /// Игра, в которой считается счетчик; `None` — сумма по всем играм
+    /// (для серий — лучшая серия среди игр)
+    pub game: Option<GameType>,
+    pub metric: Metric,
+    pub threshold: u32,
+}
+
+impl Achievement {
+    /// Текущее значение счетчика (не больше порога)
+    pub fn progress(&self, stats: &PlayerStats) -> u32 {
+        let value = match (&self.game, self.metric) {
+            (Some(game), metric) => stats.games.get(game).map_or(0, |s| metric.of(s)),
+            (None, Metric::BestStreak) => stats
+                .games
+                .values()
+                .map(|s| s.best_streak)
+                .max()
+                .unwrap_or(0),
+            (None, metric) => stats.total(metric),
+        };
+        value.min(self.threshold)
+    }
+
+    pub fn is_reached(&self, stats: &PlayerStats) -> bool {
+        self.progress(stats) >= self.threshold
+    }
+
+    pub fn title_key(&self) -> String {
+        format!("achievement.{}.title", self.id)
+    }
+
+    pub fn description_key(&self) -> String {
+        format!("achievement.{}.description", self.id)
+    }
+}
+
+/// Все достижения в порядке показа
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_win",
+        badge: "🥇",
+        game: None,
+        metric: Metric::Wins,
+        threshold: 1,
+    },
+    Achievement {
+        id: "regular",
+        badge: "🎲",
+        game: None,
+        metric: Metric::Rounds,
+        threshold: 100,
+    },
+    Achievement {
+        id: "sniper",
+        badge: "🎯",
+        game: Some(GameType::ExactNumber),
+        metric: Metric::Wins,
+        threshold: 5,
+    },
+    Achievement {
+        id: "even_odd_streak",
+        badge: "🔥",
+        game: Some(GameType::EvenOdd),
+        metric: Metric::BestStreak,
+        threshold: 10,
+    },
+    Achievement {
+        id: "high_low_master",
+        badge: "📈",
+        game: Some(GameType::HighLow),
+        metric: Metric::Wins,
+        threshold: 25,
+    },
+    Achievement {
+        id: "snake_eyes",
+        badge: "⚀",
+        game: Some(GameType::GuessOne),
+        metric: Metric::Face(1),
+        threshold: 6,
+    },
+];
+
+/// Учет раунда и выдача новых достижений; возвращает только что полученные
+pub fn record_round(
+    stats: &mut PlayerStats,
+    unlocked: &mut Vec<String>,
+    game: &GameType,
+    dice: u8,
+    win: bool,
+) -> Vec<&'static Achievement> {
+    stats.record(game, dice, win);
+    let new: Vec<&'static Achievement> = ACHIEVEMENTS
+        .iter()
+        .filter(|a| !unlocked.iter().any(|id| id == a.id))
+        .filter(|a| a.is_reached(stats))
+        .collect();
+    unlocked.extend(new.iter().map(|a| a.id.to_string()));
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn find(id: &str) -> Option<&'static Achievement> {
+        ACHIEVEMENTS.iter().find(|a| a.id == id)
+    }
+
+    fn ids(achievements: &[&Achievement]) -> Vec<&'static str> {
+        achievements.iter().map(|a| a.id).collect()
+    }
+
+    #[test]
+    fn test_first_win_is_awarded_once() {
+        let mut stats = PlayerStats::default();
+        let mut unlocked = Vec::new();
+        let lost = record_round(&mut stats, &mut unlocked, &GameType::HighLow, 2, false);
+        assert!(lost.is_empty());
+        let won = record_round(&mut stats, &mut unlocked, &GameType::HighLow, 5, true);
+        assert_eq!(ids(&won), vec!["first_win"]);
+        let again = record_round(&mut stats, &mut unlocked, &GameType::HighLow, 6, true);
+        assert!(again.is_empty());
+        assert_eq!(unlocked, vec!["first_win".to_string()]);
+    }
+
+    #[test]
+    fn test_streak_resets_on_loss() {
+        let mut stats = PlayerStats::default();
+        let mut unlocked = vec!["first_win".to_string()];
+        for _ in 0..9 {
+            record_round(&mut stats, &mut unlocked, &GameType::EvenOdd, 2, true);
+        }
+        record_round(&mut stats, &mut unlocked, &GameType::EvenOdd, 3, false);
+        let streak = find("even_odd_streak").unwrap();
+        assert_eq!(streak.progress(&stats), 9);
+        assert!(!streak.is_reached(&stats));
+
+        for _ in 0..9 {
+            record_round(&mut stats, &mut unlocked, &GameType::EvenOdd, 4, true);
+        }
+        let tenth = record_round(&mut stats, &mut unlocked, &GameType::EvenOdd, 6, true);
+        assert_eq!(ids(&tenth), vec!["even_odd_streak"]);
+    }
+
+    #[test]
+    fn test_faces_count_only_in_their_game() {
+        let mut stats = PlayerStats::default();
+        let mut unlocked = vec!["first_win".to_string()];
+        for _ in 0..6 {
+            record_round(&mut stats, &mut unlocked, &GameType::EvenOdd, 1, false);
+        }
+        assert_eq!(find("snake_eyes").unwrap().progress(&stats), 0);
+        let mut last = Vec::new();
+        for _ in 0..6 {
+            last = record_round(&mut stats, &mut unlocked, &GameType::GuessOne, 1, true);
+        }
+        assert_eq!(ids(&last), vec!["snake_eyes"]);
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        for (index, achievement) in ACHIEVEMENTS.iter().enumerate() {
+            assert!(ACHIEVEMENTS[..index].iter().all(|a| a.id != achievement.id));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Команды операторов бота (`admin.operators`).
+//!
+//! Команды не попадают в `/help`; сообщения с ними от остальных пользователей
+//! обрабатываются как обычный текст. Пользователь указывается числовым id, `@username`
+//! (если он уже писал боту) или ответом на его сообщение. Изменение баланса оператором
+//! записывается в журнал как `admin` со ссылкой на оператора.
+
+use crate::ledger::Kind;
+use crate::storage::UserProfile;
+
+/// Пользователь, к которому применяется команда
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Id(u64),
+    /// Имя в Telegram без `@`
+    Username(String),
+}
+
+impl Target {
+    pub fn parse(word: &str) -> Option<Target> {
+        match word.strip_prefix('@') {
+            Some("") => None,
+            Some(name) => Some(Target::Username(name.to_string())),
+            None => word.parse().ok().map(Target::Id),
+        }
+    }
+}
+
+/// Сумма изменения баланса: `+50`, `-20` или `50`; ноль не допускается
+pub fn parse_amount(word: &str) -> Option<i64> {
+    word.parse::<i64>().ok().filter(|amount| *amount != 0)
+}
+
+/// `on` или `off` для включения и выключения игры
+pub fn parse_switch(word: &str) -> Option<bool> {
+    match word.trim().to_lowercase().as_str() {
+        "on" | "enable" => Some(true),
+        "off" | "disable" => Some(false),
+        _ => None,
+    }
+}
+
+/// Почему баланс не изменен; в обоих случаях с текущим балансом
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdjustError {
+    /// Списание больше баланса
+    Negative(i64),
+    /// Баланс вышел бы за пределы `i64`
+    Overflow(i64),
+}
+
+/// Изменение баланса оператором `operator`. Баланс не уходит в минус и не переполняется
+pub fn adjust(profile: &mut UserProfile, amount: i64, operator: u64) -> Result<i64, AdjustError> {
+    match profile.balance.checked_add(amount) {
+        None => Err(AdjustError::Overflow(profile.balance)),
+        Some(balance) if balance < 0 => Err(AdjustError::Negative(profile.balance)),
+        Some(_) => {
+            profile.post_with_peer(Kind::Admin, amount, operator);
+            Ok(profile.balance)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(Target::parse("42"), Some(Target::Id(42)));
+        assert_eq!(
+            Target::parse("@Alice"),
+            Some(Target::Username("Alice".to_string()))
+        );
+        assert_eq!(Target::parse("@"), None);
+        assert_eq!(Target::parse("alice"), None);
+        assert_eq!(parse_amount("+50"), Some(50));
+        assert_eq!(parse_amount("-20"), Some(-20));
+        assert_eq!(parse_amount("0"), None);
+        assert_eq!(parse_switch("OFF"), Some(false));
+        assert_eq!(parse_switch("maybe"), None);
+    }
+
+    #[test]
+    fn test_adjust_keeps_balance_in_range() {
+        let mut profile = UserProfile::new(100);
+        assert_eq!(adjust(&mut profile, -30, 1), Ok(70));
+        assert_eq!(adjust(&mut profile, -71, 1), Err(AdjustError::Negative(70)));
+        assert_eq!(adjust(&mut profile, 5, 1), Ok(75));
+        assert_eq!(
+            adjust(&mut profile, i64::MAX, 1),
+            Err(AdjustError::Overflow(75))
+        );
+        assert_eq!(profile.balance, 75);
+    }
+}
@@ -0,0 +1,496 @@
+//! JSON API для запуска игр из сторонних чатов и интеграций.
+//!
+//! Маршруты:
+//! - `POST /api/sessions` — создать игровую сессию (`{"game": "even_odd", "player": "alice"}`)
+//! - `GET /api/sessions/:id` — состояние сессии и результат последнего раунда
+//! - `POST /api/sessions/:id/choice` — сделать выбор и сыграть раунд (`{"choice": "even"}`)
+//! - `GET /api/sessions/:id/history` — история раундов сессии
+//!
+//! API включается в `[api]`: сервер слушает все интерфейсы, поэтому доступ можно закрыть
+//! токеном, а сессии хранятся в памяти с ограничением числа и срока жизни.
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::config::ApiConfig;
+use crate::game::DiceGame;
+use crate::state::{GameChoice, GameType};
+
+/// Хранилище игровых сессий API
+pub struct ApiState {
+    enabled_games: Vec<GameType>,
+    config: ApiConfig,
+    sessions: Mutex<Sessions>,
+}
+
+impl ApiState {
+    pub fn new(enabled_games: Vec<GameType>, config: ApiConfig) -> Self {
+        Self {
+            enabled_games,
+            config,
+            sessions: Mutex::new(Sessions::default()),
+        }
+    }
+
+    /// Сессии после удаления тех, к которым давно не обращались
+    fn sessions(&self) -> std::sync::MutexGuard<'_, Sessions> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.evict(Instant::now(), self.config.session_ttl());
+        sessions
+    }
+}
+
+impl Default for ApiState {
+    fn default() -> Self {
+        Self::new(GameType::ALL.to_vec(), ApiConfig::default())
+    }
+}
+
+#[derive(Default)]
+struct Sessions {
+    next_id: u64,
+    by_id: HashMap<u64, Session>,
+}
+
+impl Sessions {
+    /// Удаление сессий, к которым к моменту `now` не обращались дольше `ttl`
+    fn evict(&mut self, now: Instant, ttl: Duration) {
+        self.by_id
+            .retain(|_, session| now.saturating_duration_since(session.last_active) < ttl);
+    }
+}
+
+/// Игровая сессия: один тип игры и последовательность раундов
+#[derive(Clone, Debug, Serialize)]
+pub struct Session {
+    pub id: u64,
+    pub game: GameType,
+    pub player: Option<String>,
+    pub rounds: Vec<RoundResult>,
+    /// Время последнего запроса к сессии
+    #[serde(skip)]
+    pub last_active: Instant,
+}
+
+/// Результат одного раунда
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RoundResult {
+    pub round: u32,
+    pub choice: GameChoice,
+    pub dice: u8,
+    pub dice_emoji: &'static str,
+    pub win: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSessionRequest {
+    pub game: GameType,
+    #[serde(default)]
+    pub player: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChoiceRequest {
+    pub choice: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionView {
+    id: u64,
+    game: GameType,
+    player: Option<String>,
+    rounds_played: usize,
+    last_result: Option<RoundResult>,
+}
+
+impl From<&Session> for SessionView {
+    fn from(session: &Session) -> Self {
+        Self {
+            id: session.id,
+            game: session.game.clone(),
+            player: session.player.clone(),
+            rounds_played: session.rounds.len(),
+            last_result: session.rounds.last().cloned(),
+        }
+    }
+}
+
+/// Ошибка API, отдаётся клиенту в виде JSON
+#[derive(Debug)]
+pub enum ApiError {
+    /// Нет токена или он неверный
+    Unauthorized,
+    SessionNotFound(u64),
+    /// Достигнут предел `api.max_sessions`
+    TooManySessions,
+    PlayerTooLong {
+        max: usize,
+    },
+    GameDisabled(GameType),
+    InvalidChoice {
+        game: GameType,
+        choice: String,
+    },
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, code, message) = match self {
+            ApiError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "Нужен заголовок Authorization: Bearer <token>".to_string(),
+            ),
+            ApiError::TooManySessions => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too_many_sessions",
+                "Слишком много открытых сессий, попробуйте позже".to_string(),
+            ),
+            ApiError::PlayerTooLong { max } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "player_too_long",
+                format!("Имя игрока длиннее {} символов", max),
+            ),
+            ApiError::SessionNotFound(id) => (
+                StatusCode::NOT_FOUND,
+                "session_not_found",
+                format!("Сессия {} не найдена", id),
+            ),
+            ApiError::GameDisabled(game) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "game_disabled",
+                format!("Игра {} отключена", game.name()),
+            ),
+            ApiError::InvalidChoice { game, choice } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_choice",
+                format!("Недопустимый выбор '{}' для игры {}", choice, game.name()),
+            ),
+        };
+        let body = Json(serde_json::json!({ "error": code, "message": message }));
+        (status, body).into_response()
+    }
+}
+
+/// Роутер JSON API
+pub fn router(state: Arc<ApiState>) -> Router {
+    Router::new()
+        .route("/api/sessions", post(create_session))
+        .route("/api/sessions/:id", get(get_session))
+        .route("/api/sessions/:id/choice", post(submit_choice))
+        .route("/api/sessions/:id/history", get(get_history))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// Проверка токена из `api.token`; без токена доступ открыт
+async fn authorize(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if let Some(token) = &state.config.token {
+        let given = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if !given.is_some_and(|given| same_token(given, token)) {
+            return Err(ApiError::Unauthorized);
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+/// Сравнение за время, не зависящее от совпавшего префикса
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn create_session(
+    State(state): State<Arc<ApiState>>,
+    Json(request): Json<CreateSessionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.enabled_games.contains(&request.game) {
+        return Err(ApiError::GameDisabled(request.game));
+    }
+    let max = state.config.max_player_chars;
+    if request
+        .player
+        .as_ref()
+        .is_some_and(|player| player.chars().count() > max)
+    {
+        return Err(ApiError::PlayerTooLong { max });
+    }
+    let mut sessions = state.sessions();
+    if sessions.by_id.len() >= state.config.max_sessions {
+        return Err(ApiError::TooManySessions);
+    }
+    sessions.next_id += 1;
+    let session = Session {
+        id: sessions.next_id,
+        game: request.game,
+        player: request.player,
+        rounds: Vec::new(),
+        last_active: Instant::now(),
+    };
+    info!(session_id = %session.id, game = session.game.name(), "API: создана сессия");
+    let view = SessionView::from(&session);
+    sessions.by_id.insert(session.id, session);
+    Ok((StatusCode::CREATED, Json(view)))
+}
+
+async fn get_session(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<SessionView>, ApiError> {
+    let mut sessions = state.sessions();
+    let session = sessions
+        .by_id
+        .get_mut(&id)
+        .ok_or(ApiError::SessionNotFound(id))?;
+    session.last_active = Instant::now();
+    Ok(Json(SessionView::from(&*session)))
+}
+
+async fn submit_choice(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<u64>,
+    Json(request): Json<ChoiceRequest>,
+) -> Result<Json<RoundResult>, ApiError> {
+    let mut sessions = state.sessions();
+    let session = sessions
+        .by_id
+        .get_mut(&id)
+        .ok_or(ApiError::SessionNotFound(id))?;
+    session.last_active = Instant::now();
+
+    let choice = GameChoice::parse(&session.game, &request.choice).ok_or_else(|| {
+        ApiError::InvalidChoice {
+            game: session.game.clone(),
+            choice: request.choice.clone(),
+        }
+    })?;
+
+    let dice = DiceGame::roll_dice();
+    let result = RoundResult {
+        round: session.rounds.len() as u32 + 1,
+        win: DiceGame::check(dice, &choice),
+        choice,
+        dice,
+        dice_emoji: DiceGame::dice_emoji(dice),
+    };
+    session.rounds.push(result.clone());
+    Ok(Json(result))
+}
+
+async fn get_history(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<RoundResult>>, ApiError> {
+    let mut sessions = state.sessions();
+    let session = sessions
+        .by_id
+        .get_mut(&id)
+        .ok_or(ApiError::SessionNotFound(id))?;
+    session.last_active = Instant::now();
+    Ok(Json(session.rounds.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    async fn call(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let value = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap()
+        };
+        (status, value)
+    }
+
+    #[tokio::test]
+    async fn test_full_session_flow() {
+        let app = router(Arc::new(ApiState::default()));
+
+        let (status, session) = call(
+            &app,
+            "POST",
+            "/api/sessions",
+            Some(json!({ "game": "exact_number", "player": "alice" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = session["id"].as_u64().unwrap();
+
+        let (status, round) = call(
+            &app,
+            "POST",
+            &format!("/api/sessions/{}/choice", id),
+            Some(json!({ "choice": "3" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let dice = round["dice"].as_u64().unwrap();
+        assert!((1..=6).contains(&dice));
+        assert_eq!(round["win"].as_bool().unwrap(), dice == 3);
+        assert_eq!(
+            round["choice"],
+            json!({ "game": "exact_number", "choice": 3 })
+        );
+
+        let (_, view) = call(&app, "GET", &format!("/api/sessions/{}", id), None).await;
+        assert_eq!(view["rounds_played"], json!(1));
+        assert_eq!(view["last_result"], round);
+
+        let (_, history) = call(&app, "GET", &format!("/api/sessions/{}/history", id), None).await;
+        assert_eq!(history, json!([round]));
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let app = router(Arc::new(ApiState::default()));
+
+        let (status, body) = call(&app, "GET", "/api/sessions/42", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], json!("session_not_found"));
+
+        let (_, session) = call(
+            &app,
+            "POST",
+            "/api/sessions",
+            Some(json!({ "game": "even_odd" })),
+        )
+        .await;
+        let (status, body) = call(
+            &app,
+            "POST",
+            &format!("/api/sessions/{}/choice", session["id"]),
+            Some(json!({ "choice": "high" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], json!("invalid_choice"));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_game_is_rejected() {
+        let app = router(Arc::new(ApiState::new(
+            vec![GameType::EvenOdd],
+            ApiConfig::default(),
+        )));
+        let (status, body) = call(
+            &app,
+            "POST",
+            "/api/sessions",
+            Some(json!({ "game": "guess_one" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], json!("game_disabled"));
+        assert_eq!(body["message"], json!("Игра guess_one отключена"));
+    }
+
+    async fn status(app: &Router, request: Request<Body>) -> StatusCode {
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_token_and_session_limits() {
+        let config = ApiConfig {
+            token: Some("secret".to_string()),
+            max_sessions: 2,
+            max_player_chars: 5,
+            ..ApiConfig::default()
+        };
+        let state = Arc::new(ApiState::new(GameType::ALL.to_vec(), config));
+        let app = router(state.clone());
+        let create = |token: &str, player: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/sessions")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::from(
+                    json!({ "game": "even_odd", "player": player }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let (code, body) = call(&app, "GET", "/api/sessions/1", None).await;
+        assert_eq!(
+            (code, &body["error"]),
+            (StatusCode::UNAUTHORIZED, &json!("unauthorized"))
+        );
+        assert_eq!(
+            status(&app, create("wrong!", "bob")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&app, create("secret", "bobbybob")).await,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(&app, create("secret", "bob")).await,
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            status(&app, create("secret", "bob")).await,
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            status(&app, create("secret", "bob")).await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        // Сессии без запросов дольше срока жизни удаляются и освобождают место
+        let ttl = state.config.session_ttl();
+        state
+            .sessions
+            .lock()
+            .unwrap()
+            .evict(Instant::now() + ttl, ttl);
+        assert_eq!(
+            status(&app, create("secret", "bob")).await,
+            StatusCode::CREATED
+        );
+        assert_eq!(state.sessions.lock().unwrap().by_id.len(), 1);
+    }
+}
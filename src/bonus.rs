@@ -0,0 +1,278 @@
+//! Бесплатные фишки, чтобы игрок без баланса не уходил из игры.
+//!
+//! - `/daily` — раз в сутки (по UTC), бонус растет с каждым днем непрерывной серии.
+//! - `/faucet` — небольшая сумма, пока баланс ниже `bonus.faucet_below`, не чаще раза в
+//!   `faucet_cooldown_minutes`.
+//! - `/bankrupt` — сброс баланса к `bonus.bankruptcy_balance`, если не хватает на ставку.
+//!
+//! Состояние хранится в профиле пользователя (по Telegram user id, а не по чату), а
+//! проверка и начисление выполняются в одном `Storage::modify_user`, так что двойное
+//! нажатие или одновременные команды из разных чатов не дают второго начисления.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::BonusConfig;
+use crate::ledger::Kind;
+use crate::storage::UserProfile;
+
+const DAY: u64 = 86_400;
+
+/// Состояние бонусов пользователя
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BonusState {
+    /// День (номер суток UTC с начала эпохи) последнего ежедневного бонуса
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_day: Option<u64>,
+    /// Длина текущей серии ежедневных бонусов
+    pub daily_streak: u32,
+    /// Время (unix, секунды) последнего использования крана
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet_at: Option<u64>,
+    /// Время последнего сброса при банкротстве
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bankrupt_at: Option<u64>,
+    pub bankruptcies: u32,
+}
+
+/// Почему фишки не начислены
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BonusError {
+    /// Ежедневный бонус за сегодня уже получен
+    AlreadyClaimed { retry_in: Duration },
+    /// Баланс не ниже порога
+    BalanceTooHigh { limit: i64 },
+    /// Повтор раньше окончания паузы
+    Cooldown { retry_in: Duration },
+    /// Отключено в конфигурации
+    Disabled,
+    /// Баланс заморожен оператором
+    Frozen,
+}
+
+/// Начисленный ежедневный бонус
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DailyReward {
+    pub amount: i64,
+    pub streak: u32,
+}
+
+/// Текущее время в секундах unix
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Время до начала следующих суток UTC
+fn until_tomorrow(now: u64) -> Duration {
+    Duration::from_secs((now / DAY + 1) * DAY - now)
+}
+
+/// Оставшаяся пауза после события `since`, если она еще не истекла
+fn cooldown_left(since: Option<u64>, cooldown: Duration, now: u64) -> Option<Duration> {
+    let ready_at = since? + cooldown.as_secs();
+    (ready_at > now).then(|| Duration::from_secs(ready_at - now))
+}
+
+/// Можно ли сейчас получить ежедневный бонус; `Err` — через сколько
+pub fn daily_available(profile: &UserProfile, now: u64) -> Result<(), Duration> {
+    match profile.bonus.daily_day {
+        Some(day) if day >= now / DAY => Err(until_tomorrow(now)),
+        _ => Ok(()),
+    }
+}
+
+/// Ежедневный бонус: серия продолжается, если предыдущий получен вчера
+pub fn claim_daily(
+    profile: &mut UserProfile,
+    config: &BonusConfig,
+    now: u64,
+) -> Result<DailyReward, BonusError> {
+    if profile.frozen {
+        return Err(BonusError::Frozen);
+    }
+    daily_available(profile, now).map_err(|retry_in| BonusError::AlreadyClaimed { retry_in })?;
+    let today = now / DAY;
+    let state = &mut profile.bonus;
+    state.daily_streak = match state.daily_day {
+        Some(day) if day + 1 == today => state.daily_streak + 1,
+        _ => 1,
+    };
+    state.daily_day = Some(today);
+    let steps = state.daily_streak.min(config.daily_max_streak.max(1)) - 1;
+    let amount = config.daily_base + config.daily_step * i64::from(steps);
+    let streak = state.daily_streak;
+    profile.post(Kind::Daily, amount);
+    Ok(DailyReward { amount, streak })
+}
+
+/// Кран: немного фишек при низком балансе
+pub fn claim_faucet(
+    profile: &mut UserProfile,
+    config: &BonusConfig,
+    now: u64,
+) -> Result<i64, BonusError> {
+    if config.faucet_amount == 0 {
+        return Err(BonusError::Disabled);
+    }
+    if profile.frozen {
+        return Err(BonusError::Frozen);
+    }
+    if profile.balance >= config.faucet_below {
+        return Err(BonusError::BalanceTooHigh {
+            limit: config.faucet_below,
+        });
+    }
+    if let Some(retry_in) = cooldown_left(profile.bonus.faucet_at, config.faucet_cooldown(), now) {
+        return Err(BonusError::Cooldown { retry_in });
+    }
+    profile.bonus.faucet_at = Some(now);
+    profile.post(Kind::Faucet, config.faucet_amount);
+    Ok(config.faucet_amount)
+}
+
+/// Сброс баланса, когда фишек не хватает даже на одну ставку
+pub fn reset_bankrupt(
+    profile: &mut UserProfile,
+    config: &BonusConfig,
+    stake: i64,
+    now: u64,
+) -> Result<i64, BonusError> {
+    if config.bankruptcy_balance == 0 {
+        return Err(BonusError::Disabled);
+    }
+    if profile.frozen {
+        return Err(BonusError::Frozen);
+    }
+    if profile.balance >= stake {
+        return Err(BonusError::BalanceTooHigh { limit: stake });
+    }
+    let cooldown = config.bankruptcy_cooldown();
+    if let Some(retry_in) = cooldown_left(profile.bonus.bankrupt_at, cooldown, now) {
+        return Err(BonusError::Cooldown { retry_in });
+    }
+    profile.bonus.bankrupt_at = Some(now);
+    profile.bonus.bankruptcies += 1;
+    profile.post(
+        Kind::Bankruptcy,
+        config.bankruptcy_balance - profile.balance,
+    );
+    Ok(profile.balance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const MONDAY_NOON: u64 = 20_000 * DAY + 12 * 3600;
+
+    fn profile(balance: i64) -> UserProfile {
+        UserProfile::new(balance)
+    }
+
+    #[test]
+    fn test_daily_once_per_utc_day_with_growing_streak() {
+        let config = BonusConfig::default();
+        let mut user = profile(0);
+
+        let first = claim_daily(&mut user, &config, MONDAY_NOON).unwrap();
+        assert_eq!(
+            first,
+            DailyReward {
+                amount: 50,
+                streak: 1
+            }
+        );
+        // Повтор в те же сутки отклоняется, ждать до полуночи UTC
+        assert_eq!(
+            claim_daily(&mut user, &config, MONDAY_NOON + 3600),
+            Err(BonusError::AlreadyClaimed {
+                retry_in: Duration::from_secs(11 * 3600)
+            })
+        );
+        // Сразу после полуночи — следующий день серии
+        let second = claim_daily(&mut user, &config, 20_001 * DAY).unwrap();
+        assert_eq!(
+            second,
+            DailyReward {
+                amount: 60,
+                streak: 2
+            }
+        );
+        assert_eq!(user.balance, 110);
+
+        // Пропущенный день обрывает серию
+        let after_gap = claim_daily(&mut user, &config, 20_003 * DAY).unwrap();
+        assert_eq!(after_gap.streak, 1);
+    }
+
+    #[test]
+    fn test_daily_bonus_stops_growing() {
+        let config = BonusConfig {
+            daily_max_streak: 3,
+            ..BonusConfig::default()
+        };
+        let mut user = profile(0);
+        let amounts: Vec<i64> = (0..5)
+            .map(|day| {
+                claim_daily(&mut user, &config, (20_000 + day) * DAY)
+                    .unwrap()
+                    .amount
+            })
+            .collect();
+        assert_eq!(amounts, vec![50, 60, 70, 70, 70]);
+    }
+
+    #[test]
+    fn test_faucet_requires_low_balance_and_cooldown() {
+        let config = BonusConfig::default();
+        let mut user = profile(50);
+        assert_eq!(
+            claim_faucet(&mut user, &config, MONDAY_NOON),
+            Err(BonusError::BalanceTooHigh { limit: 10 })
+        );
+
+        user.balance = 0;
+        assert_eq!(claim_faucet(&mut user, &config, MONDAY_NOON), Ok(30));
+        user.balance = 0;
+        assert_eq!(
+            claim_faucet(&mut user, &config, MONDAY_NOON + 600),
+            Err(BonusError::Cooldown {
+                retry_in: Duration::from_secs(3000)
+            })
+        );
+        assert_eq!(claim_faucet(&mut user, &config, MONDAY_NOON + 3600), Ok(30));
+    }
+
+    #[test]
+    fn test_bankruptcy_reset() {
+        let config = BonusConfig::default();
+        let mut user = profile(10);
+        assert_eq!(
+            reset_bankrupt(&mut user, &config, 10, MONDAY_NOON),
+            Err(BonusError::BalanceTooHigh { limit: 10 })
+        );
+
+        user.balance = 3;
+        assert_eq!(reset_bankrupt(&mut user, &config, 10, MONDAY_NOON), Ok(100));
+        assert_eq!(user.bonus.bankruptcies, 1);
+        user.balance = 0;
+        assert!(matches!(
+            reset_bankrupt(&mut user, &config, 10, MONDAY_NOON + 3600),
+            Err(BonusError::Cooldown { .. })
+        ));
+
+        let disabled = BonusConfig {
+            bankruptcy_balance: 0,
+            ..BonusConfig::default()
+        };
+        assert_eq!(
+            reset_bankrupt(&mut user, &disabled, 10, MONDAY_NOON),
+            Err(BonusError::Disabled)
+        );
+    }
+}
@@ -1,14 +1,42 @@
-use log::{error, info};
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
 use teloxide::{
     dispatching::UpdateHandler,
     prelude::*,
-    types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
-    utils::command::BotCommands,
+    types::{
+        BotCommand, Chat, InlineKeyboardButton, InlineKeyboardMarkup, InlineQueryResult,
+        InlineQueryResultArticle, InputFile, InputMessageContent, InputMessageContentText,
+        MessageId, ParseMode, UpdateKind, User,
+    },
+    utils::{command::BotCommands, html},
     RequestError,
 };
+use tracing::{error, info, warn};
 
-use crate::game::DiceGame;
-use crate::state::{EvenOddChoice, HighLowChoice, GuessOneChoice};
+use crate::achievements::{self, Achievement, ACHIEVEMENTS};
+use crate::admin::{self, AdjustError, Target};
+use crate::bonus::{self, BonusError};
+use crate::callback::{CallbackData, CallbackError};
+use crate::config::UiMode;
+use crate::context::AppContext;
+use crate::game::{DiceGame, StakeError};
+use crate::i18n::{I18n, Lang};
+use crate::inline;
+use crate::jackpot;
+use crate::ledger::{self, Kind};
+use crate::levels::{self, Skin};
+use crate::limits::{Limit, LimitError, Outcome, Request};
+use crate::privacy::{Export, ExportFormat, ForgetError};
+use crate::ratelimit::{Limited, Throttled};
+use crate::rounds::RoundError;
+use crate::scheduler::Delivery;
+use crate::settings::Change;
+use crate::state::{EvenOddChoice, GameChoice, GameType, GuessOneChoice, HighLowChoice};
+use crate::storage::UserProfile;
+use crate::tournament::{Entrant, Format, Stage, Tournament, TournamentError};
+use crate::transfer::{self, TransferError};
 
 /// Команды бота
 #[derive(BotCommands, Clone)]
@@ -20,10 +48,97 @@ pub enum Command {
     Help,
     #[command(description = "Играть в кубики")]
     Play,
+    #[command(description = "Сменить язык / Change language")]
+    Lang(String),
+    #[command(description = "Режим интерфейса: messages или in_place")]
+    Ui(String),
+    #[command(description = "Ежедневный бонус")]
+    Daily,
+    #[command(description = "Фишки при низком балансе")]
+    Faucet,
+    #[command(description = "Сброс баланса при банкротстве")]
+    Bankrupt,
+    #[command(description = "Ваши достижения")]
+    Achievements,
+    #[command(description = "Уровень и статистика")]
+    Stats,
+    #[command(description = "Размер ставки")]
+    Bet(String),
+    #[command(description = "Скин броска")]
+    Skin,
+    #[command(description = "Турнир: create, join, start")]
+    Tournament(String),
+    #[command(description = "Текущий джекпот")]
+    Jackpot,
+    #[command(description = "Передать фишки: /give @username сумма")]
+    Give(String),
+    #[command(description = "История операций")]
+    History,
+    #[command(description = "Настройки чата (для администраторов)")]
+    Settings(String),
+    #[command(description = "Лимиты проигрыша, напоминания и самоисключение")]
+    Limits(String),
+    #[command(description = "Выгрузка ваших данных: json или csv")]
+    Export(String),
+    #[command(rename = "forget_me", description = "Удалить ваши данные")]
+    ForgetMe,
 }
 
+impl Command {
+    /// Меню команд для Telegram на языке `lang`: описания берутся из ключей `command.*`
+    pub fn menu(i18n: &I18n, lang: Lang) -> Vec<BotCommand> {
+        Command::bot_commands()
+            .into_iter()
+            .map(|command| {
+                let key = format!("command.{}", command.command.trim_start_matches('/'));
+                BotCommand::new(command.command, i18n.text(lang, &key))
+            })
+            .collect()
+    }
+}
+
+/// Команды операторов бота: доступны только пользователям из `admin.operators` и не
+/// показываются в `/help`
+#[derive(BotCommands, Clone)]
+#[command(rename_rule = "snake_case")]
+pub enum AdminCommand {
+    Admin,
+    AdminStats,
+    AdminBalance(String),
+    AdminFreeze(String),
+    AdminUnfreeze(String),
+    AdminBan(String),
+    AdminUnban(String),
+    AdminBroadcast(String),
+    AdminGame(String),
+    AdminErrors,
+}
+
+/// Сколько последних операций показывает /history
+const HISTORY_LIMIT: usize = 10;
+
+/// Сколько символов сообщения об ошибке показывает /admin_errors
+const ERROR_MESSAGE_CHARS: usize = 200;
+
+/// Как часто проверяются сроки турниров
+const TOURNAMENT_TICK: Duration = Duration::from_secs(5);
+
 pub struct BotHandler;
 
+/// Рассчитанный раунд
+struct Settlement {
+    win: bool,
+    /// Выплата без джекпота
+    payout: i64,
+    /// Баланс после раунда
+    balance: i64,
+    unlocked: Vec<&'static Achievement>,
+    new_levels: Range<u32>,
+    jackpot_won: Option<i64>,
+    /// Длительность сессии, если пора напомнить о перерыве
+    reminder: Option<Duration>,
+}
+
 impl BotHandler {
     pub fn new() -> Self {
         Self
@@ -36,437 +151,2992 @@ impl BotHandler {
         let command_handler = teloxide::filter_command::<Command, _>()
             .branch(case![Command::Start].endpoint(Self::start_command))
             .branch(case![Command::Help].endpoint(Self::help_command))
-            .branch(case![Command::Play].endpoint(Self::play_command));
+            .branch(case![Command::Play].endpoint(Self::play_command))
+            .branch(case![Command::Lang(code)].endpoint(Self::lang_command))
+            .branch(case![Command::Ui(mode)].endpoint(Self::ui_command))
+            .branch(case![Command::Daily].endpoint(Self::daily_command))
+            .branch(case![Command::Faucet].endpoint(Self::faucet_command))
+            .branch(case![Command::Bankrupt].endpoint(Self::bankrupt_command))
+            .branch(case![Command::Achievements].endpoint(Self::achievements_command))
+            .branch(case![Command::Stats].endpoint(Self::stats_command))
+            .branch(case![Command::Bet(amount)].endpoint(Self::bet_command))
+            .branch(case![Command::Skin].endpoint(Self::skin_command))
+            .branch(case![Command::Tournament(args)].endpoint(Self::tournament_command))
+            .branch(case![Command::Jackpot].endpoint(Self::jackpot_command))
+            .branch(case![Command::Give(args)].endpoint(Self::give_command))
+            .branch(case![Command::History].endpoint(Self::history_command))
+            .branch(case![Command::Settings(args)].endpoint(Self::settings_command))
+            .branch(case![Command::Limits(args)].endpoint(Self::limits_command))
+            .branch(case![Command::Export(format)].endpoint(Self::export_command))
+            .branch(case![Command::ForgetMe].endpoint(Self::forget_me_command));
+
+        // Команды оператора; для остальных пользователей это обычный текст
+        let admin_handler = dptree::filter(Self::is_operator)
+            .chain(teloxide::filter_command::<AdminCommand, _>())
+            .branch(case![AdminCommand::Admin].endpoint(Self::admin_command))
+            .branch(case![AdminCommand::AdminStats].endpoint(Self::admin_stats_command))
+            .branch(case![AdminCommand::AdminBalance(args)].endpoint(Self::admin_balance_command))
+            .branch(case![AdminCommand::AdminFreeze(args)].endpoint(Self::admin_freeze_command))
+            .branch(case![AdminCommand::AdminUnfreeze(args)].endpoint(Self::admin_unfreeze_command))
+            .branch(case![AdminCommand::AdminBan(args)].endpoint(Self::admin_ban_command))
+            .branch(case![AdminCommand::AdminUnban(args)].endpoint(Self::admin_unban_command))
+            .branch(
+                case![AdminCommand::AdminBroadcast(text)].endpoint(Self::admin_broadcast_command),
+            )
+            .branch(case![AdminCommand::AdminGame(args)].endpoint(Self::admin_game_command))
+            .branch(case![AdminCommand::AdminErrors].endpoint(Self::admin_errors_command));
 
+        // Лимит частоты проверяется до всех остальных обработчиков
         let callback_handler = Update::filter_callback_query()
-            .endpoint(Self::handle_callback);
+            .branch(dptree::filter_map(Self::callback_rate_limit).endpoint(Self::callback_cooldown))
+            .branch(dptree::endpoint(Self::handle_callback));
 
         let message_handler = Update::filter_message()
+            .branch(dptree::filter_map(Self::message_rate_limit).endpoint(Self::message_cooldown))
+            .branch(admin_handler)
             .branch(command_handler)
             .branch(dptree::endpoint(Self::handle_message));
 
+        let inline_handler = Update::filter_inline_query().endpoint(Self::handle_inline_query);
+
+        // Обновления от заблокированных пользователей дальше не обрабатываются
         dptree::entry()
+            .branch(dptree::filter(Self::is_banned).endpoint(Self::banned_update))
             .branch(message_handler)
             .branch(callback_handler)
+            .branch(inline_handler)
+    }
+
+    /// Пользователь заблокирован оператором (на самих операторов блокировка не действует)
+    fn is_banned(update: Update, ctx: Arc<AppContext>) -> bool {
+        update.user().is_some_and(|user| {
+            ctx.storage.is_banned(user.id.0) && !ctx.config.admin.is_operator(user.id.0)
+        })
+    }
+
+    /// Ответ заблокированному пользователю: нажатие кнопки получает подсказку, остальное
+    /// остается без ответа
+    async fn banned_update(bot: Bot, update: Update, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        if let UpdateKind::CallbackQuery(callback) = update.kind {
+            let lang = ctx.lang_for(Some(&callback.from));
+            bot.answer_callback_query(callback.id)
+                .text(ctx.i18n.text(lang, "account.banned"))
+                .show_alert(true)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Сообщение от оператора бота
+    fn is_operator(msg: Message, ctx: Arc<AppContext>) -> bool {
+        msg.from()
+            .is_some_and(|user| ctx.config.admin.is_operator(user.id.0))
+    }
+
+    /// Превышение лимита сообщений (действие при этом не засчитывается)
+    fn message_rate_limit(msg: Message, ctx: Arc<AppContext>) -> Option<Limited> {
+        let user = msg.from()?;
+        ctx.limiter.check(user.id.0, msg.chat.id).err()
+    }
+
+    /// Превышение лимита нажатий кнопок
+    fn callback_rate_limit(callback: CallbackQuery, ctx: Arc<AppContext>) -> Option<Limited> {
+        let chat_id = callback
+            .message
+            .as_ref()
+            .map_or(ChatId(callback.from.id.0 as i64), |message| message.chat.id);
+        ctx.limiter.check(callback.from.id.0, chat_id).err()
+    }
+
+    /// Ответ на слишком частые сообщения: предупреждение один раз за период ожидания
+    async fn message_cooldown(
+        bot: Bot,
+        msg: Message,
+        limited: Limited,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.rate_limited("message");
+        if limited.notify {
+            let lang = ctx.lang_in(msg.chat.id, msg.from());
+            let text = Self::cooldown_text(&ctx, lang, &limited);
+            bot.send_message(msg.chat.id, text)
+                .throttled(&ctx.throttle)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Ответ на слишком частые нажатия: всплывающая подсказка вместо нового раунда
+    async fn callback_cooldown(
+        bot: Bot,
+        callback: CallbackQuery,
+        limited: Limited,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.rate_limited("callback");
+        let lang = ctx.lang_for(Some(&callback.from));
+        let text = Self::cooldown_text(&ctx, lang, &limited);
+        bot.answer_callback_query(callback.id).text(text).await?;
+        Ok(())
+    }
+
+    fn cooldown_text(ctx: &AppContext, lang: Lang, limited: &Limited) -> String {
+        let seconds = limited.retry_in.as_secs_f64().ceil().max(1.0) as u64;
+        ctx.i18n
+            .format(lang, "rate.cooldown", &[("seconds", &seconds)])
     }
 
     /// Обработчик команды /start
-    async fn start_command(bot: Bot, msg: Message) -> ResponseResult<()> {
-        info!("Пользователь {} начал работу с ботом", msg.chat.id);
-        
-        let text = "🎲 Привет! Добро пожаловать в игру с кубиками!\n\n\
-                   Я предлагаю вам угадать результат броска кубика.\n\
-                   Доступны четыре варианта игры:\n\n\
-                   🔵 Четное/Нечетное - угадайте четность числа\n\
-                   🔴 Больше/Меньше 3.5 - угадайте диапазон\n\
-                   🎯 Точное число - угадайте конкретное число\n\
-                   🎱 Угадать единицу - выпадет ли единица\n\n\
-                   Используйте /play чтобы начать игру!";
+    async fn start_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("start");
+        let _timer = ctx.metrics.handler_timer("command");
+        info!("Пользователь начал работу с ботом");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        if let Some(user) = msg.from() {
+            Self::remember_user(&ctx, user);
+        }
+        let economy = &ctx.config.economy;
+        let profile = match msg.from() {
+            Some(user) => ctx.storage.user(user.id.0),
+            None => UserProfile::new(economy.starting_balance),
+        };
+        let welcome = ctx.i18n.format(
+            lang,
+            "start.welcome",
+            &[("balance", &profile.balance), ("stake", &economy.stake)],
+        );
+        let bonus = match bonus::daily_available(&profile, bonus::now()) {
+            Ok(()) => ctx.i18n.text(lang, "bonus.welcome_ready").to_string(),
+            Err(retry_in) => Self::wait_text(&ctx, lang, "bonus.welcome_wait", retry_in),
+        };
+        let mut text = format!("{}\n\n{}", welcome, bonus);
+        if profile.balance < economy.stake {
+            text = format!("{}\n{}", text, ctx.i18n.text(lang, "bonus.welcome_broke"));
+        }
 
         bot.send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
             .await?;
-        
+
         Ok(())
     }
 
-    /// Обработчик команды /help
-    async fn help_command(bot: Bot, msg: Message) -> ResponseResult<()> {
-        let text = "🎮 <b>Помощь по игре в кубики</b>\n\n\
-                   <b>Доступные команды:</b>\n\
-                   /start - начать работу с ботом\n\
-                   /play - начать новую игру\n\
-                   /help - показать эту справку\n\n\
-                   <b>Варианты игры:</b>\n\n\
-                   🔵 <b>Четное/Нечетное</b>\n\
-                   Угадайте, будет ли результат четным или нечетным числом\n\n\
-                   🔴 <b>Больше/Меньше 3.5</b>\n\
-                   Угадайте, будет ли результат больше 3.5 (4-6) или меньше 3.5 (1-3)\n\n\
-                   🎯 <b>Точное число</b>\n\
-                   Угадайте конкретное число от 1 до 6\n\n\
-                   ⚀ <b>Угадать единицу</b>\n\
-                   Угадайте, выпадет ли на кубике единица";
+    /// Обработчик команды /daily
+    async fn daily_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("daily");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let config = &ctx.config.bonus;
+        let claimed = ctx.storage.modify_user(user.id.0, |profile| {
+            bonus::claim_daily(profile, config, bonus::now())
+                .map(|reward| (reward, profile.balance))
+        });
+        let text = match claimed {
+            Ok(Ok((reward, balance))) => {
+                info!(
+                    amount = reward.amount,
+                    streak = reward.streak,
+                    "Выдан ежедневный бонус"
+                );
+                let streak = ctx
+                    .i18n
+                    .plural(lang, "bonus.streak", reward.streak.into(), &[]);
+                ctx.i18n.format(
+                    lang,
+                    "bonus.daily",
+                    &[
+                        ("amount", &reward.amount),
+                        ("streak", &streak),
+                        ("balance", &balance),
+                    ],
+                )
+            }
+            Ok(Err(error)) => Self::bonus_error_text(&ctx, lang, "bonus.daily", error),
+            Err(e) => {
+                error!(error = %e, "Не удалось сохранить ежедневный бонус");
+                return Ok(());
+            }
+        };
+        bot.send_message(msg.chat.id, text)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
 
+    /// Обработчик команды /faucet
+    async fn faucet_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("faucet");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let config = &ctx.config.bonus;
+        let claimed = ctx.storage.modify_user(user.id.0, |profile| {
+            bonus::claim_faucet(profile, config, bonus::now())
+                .map(|amount| (amount, profile.balance))
+        });
+        let text = match claimed {
+            Ok(Ok((amount, balance))) => {
+                info!(amount, "Выданы фишки из крана");
+                ctx.i18n.format(
+                    lang,
+                    "bonus.faucet",
+                    &[("amount", &amount), ("balance", &balance)],
+                )
+            }
+            Ok(Err(error)) => Self::bonus_error_text(&ctx, lang, "bonus.faucet", error),
+            Err(e) => {
+                error!(error = %e, "Не удалось сохранить фишки из крана");
+                return Ok(());
+            }
+        };
         bot.send_message(msg.chat.id, text)
-            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
             .await?;
-        
         Ok(())
     }
 
-    /// Обработчик команды /play
-    async fn play_command(bot: Bot, msg: Message) -> ResponseResult<()> {
-        info!("Пользователь {} начал новую игру", msg.chat.id);
-        Self::show_game_selection(&bot, msg.chat.id).await
+    /// Обработчик команды /bankrupt
+    async fn bankrupt_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("bankrupt");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let config = &ctx.config.bonus;
+        let stake = ctx.config.economy.stake;
+        let reset = ctx.storage.modify_user(user.id.0, |profile| {
+            bonus::reset_bankrupt(profile, config, stake, bonus::now())
+        });
+        let text = match reset {
+            Ok(Ok(balance)) => {
+                info!(balance, "Баланс сброшен после банкротства");
+                ctx.i18n
+                    .format(lang, "bonus.bankrupt", &[("balance", &balance)])
+            }
+            Ok(Err(error)) => Self::bonus_error_text(&ctx, lang, "bonus.bankrupt", error),
+            Err(e) => {
+                error!(error = %e, "Не удалось сбросить баланс");
+                return Ok(());
+            }
+        };
+        bot.send_message(msg.chat.id, text)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
     }
 
-    /// Отображение выбора типа игры
-    async fn show_game_selection(bot: &Bot, chat_id: ChatId) -> ResponseResult<()> {
-        let keyboard = InlineKeyboardMarkup::new(vec![
-            vec![InlineKeyboardButton::callback("🔵 Четное/Нечетное", "game_even_odd")],
-            vec![InlineKeyboardButton::callback("🔴 Больше/Меньше 3.5", "game_high_low")],
-            vec![InlineKeyboardButton::callback("🎯 Точное число", "game_exact")],
-            vec![InlineKeyboardButton::callback("⚀ Угадать единицу", "game_guess_one")],
-        ]);
+    /// Обработчик команды /achievements: полученные достижения и прогресс остальных
+    async fn achievements_command(
+        bot: Bot,
+        msg: Message,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("achievements");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let profile = ctx.storage.user(user.id.0);
+        let i18n = &ctx.i18n;
 
-        let text = "🎲 Выберите вариант игры:";
+        let mut lines = vec![i18n.format(
+            lang,
+            "achievements.header",
+            &[
+                ("count", &profile.achievements.len()),
+                ("total", &ACHIEVEMENTS.len()),
+            ],
+        )];
+        for achievement in ACHIEVEMENTS {
+            let title = i18n.text(lang, &achievement.title_key()).to_string();
+            let description = i18n.text(lang, &achievement.description_key()).to_string();
+            let line = if profile.achievements.iter().any(|id| id == achievement.id) {
+                i18n.format(
+                    lang,
+                    "achievement.line",
+                    &[
+                        ("badge", &achievement.badge),
+                        ("title", &title),
+                        ("description", &description),
+                    ],
+                )
+            } else {
+                i18n.format(
+                    lang,
+                    "achievement.locked",
+                    &[
+                        ("title", &title),
+                        ("description", &description),
+                        ("progress", &achievement.progress(&profile.stats)),
+                        ("threshold", &achievement.threshold),
+                    ],
+                )
+            };
+            lines.push(line);
+        }
 
-        bot.send_message(chat_id, text)
-            .reply_markup(keyboard)
+        bot.send_message(msg.chat.id, lines.join("\n"))
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
             .await?;
-        
         Ok(())
     }
 
-    /// Обработчик callback запросов
-    async fn handle_callback(bot: Bot, callback: CallbackQuery) -> ResponseResult<()> {
-        if let Some(data) = &callback.data {
-            if let Some(message) = &callback.message {
-                let chat_id = message.chat.id;
-                
-                match data.as_str() {
-                    "game_even_odd" => {
-                        Self::start_even_odd_game(&bot, chat_id).await?;
-                    }
-                    "game_high_low" => {
-                        Self::start_high_low_game(&bot, chat_id).await?;
-                    }
-                    "game_exact" => {
-                        Self::start_exact_number_game(&bot, chat_id).await?;
-                    }
-                    "game_guess_one" => {
-                        Self::start_guess_one_game(&bot, chat_id).await?;
-                    }
-                    "choice_even" => {
-                        Self::play_even_odd_game(&bot, chat_id, EvenOddChoice::Even).await?;
+    /// Обработчик команды /stats: уровень, опыт, ставка и статистика по играм
+    async fn stats_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("stats");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let profile = ctx.storage.user(user.id.0);
+        let i18n = &ctx.i18n;
+        let level = levels::level_for(profile.xp);
+        let next = match levels::next_level_xp(profile.xp) {
+            Some(xp) => i18n.format(lang, "stats.next", &[("level", &(level + 1)), ("xp", &xp)]),
+            None => String::new(),
+        };
+        let games = &profile.stats.games;
+        let rounds: u32 = games.values().map(|g| g.rounds).sum();
+        let wins: u32 = games.values().map(|g| g.wins).sum();
+        let win_rate = (wins * 100).checked_div(rounds).unwrap_or(0);
+        let mut lines = vec![i18n.format(
+            lang,
+            "stats.body",
+            &[
+                ("level", &level),
+                ("xp", &profile.xp),
+                ("next", &next),
+                ("balance", &profile.balance),
+                ("stake", &Self::stake_for(&ctx, &profile)),
+                (
+                    "max_stake",
+                    &levels::max_stake(level, ctx.config.economy.stake),
+                ),
+                ("rounds", &rounds),
+                ("wins", &wins),
+                ("win_rate", &win_rate),
+            ],
+        )];
+        for game in GameType::ALL.iter() {
+            if let Some(stats) = games.get(game) {
+                let name = i18n.text(lang, Self::game_label(game)).to_string();
+                lines.push(i18n.format(
+                    lang,
+                    "stats.game",
+                    &[
+                        ("game", &name),
+                        ("rounds", &stats.rounds),
+                        ("wins", &stats.wins),
+                    ],
+                ));
+            }
+        }
+
+        bot.send_message(msg.chat.id, lines.join("\n"))
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Обработчик команды /bet: без аргумента показывает текущую ставку и допустимый диапазон
+    async fn bet_command(
+        bot: Bot,
+        msg: Message,
+        amount: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("bet");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let profile = ctx.storage.user(user.id.0);
+        let min = ctx.config.economy.stake;
+        let max = levels::max_stake(levels::level_for(profile.xp), min);
+
+        let text = match amount.trim() {
+            "" => ctx.i18n.format(
+                lang,
+                "bet.current",
+                &[
+                    ("stake", &Self::stake_for(&ctx, &profile)),
+                    ("min", &min),
+                    ("max", &max),
+                ],
+            ),
+            amount => match amount.parse::<i64>() {
+                Ok(stake) if (min..=max).contains(&stake) => {
+                    if let Err(e) = ctx
+                        .storage
+                        .update_user(user.id.0, |p| p.stake = Some(stake))
+                    {
+                        error!(error = %e, "Не удалось сохранить ставку");
                     }
-                    "choice_odd" => {
-                        Self::play_even_odd_game(&bot, chat_id, EvenOddChoice::Odd).await?;
+                    info!(stake, "Игрок изменил ставку");
+                    ctx.i18n.format(lang, "bet.changed", &[("stake", &stake)])
+                }
+                _ => ctx
+                    .i18n
+                    .format(lang, "bet.invalid", &[("min", &min), ("max", &max)]),
+            },
+        };
+        bot.send_message(msg.chat.id, text)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Обработчик команды /skin: выбор скина броска из открытых
+    async fn skin_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("skin");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let level = msg
+            .from()
+            .map_or(1, |user| levels::level_for(ctx.storage.user(user.id.0).xp));
+        let rows = Skin::ALL
+            .iter()
+            .map(|skin| {
+                let mut label = ctx
+                    .i18n
+                    .text(lang, &format!("skin.{}", skin.name()))
+                    .to_string();
+                if level < skin.required_level() {
+                    label = format!("🔒 {}", label);
+                }
+                vec![InlineKeyboardButton::callback(
+                    label,
+                    CallbackData::Skin(*skin).encode(),
+                )]
+            })
+            .collect::<Vec<_>>();
+        bot.send_message(msg.chat.id, ctx.i18n.text(lang, "skin.prompt"))
+            .reply_markup(InlineKeyboardMarkup::new(rows))
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Обработчик команды /tournament: `create [knockout|round_robin] [минуты]`, `join`,
+    /// `start`; без аргумента показывает текущий турнир
+    async fn tournament_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("tournament");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let chat_id = msg.chat.id;
+        let config = &ctx.config.tournament;
+        let now = bonus::now();
+        let mut args = args.split_whitespace();
+
+        let result = match args.next().unwrap_or_default() {
+            "" => {
+                let text = match ctx.storage.tournament(chat_id.0) {
+                    Some(tournament) => Self::tournament_text(&ctx, &tournament, now),
+                    None => ctx.i18n.text(lang, "tournament.none").to_string(),
+                };
+                Ok(text)
+            }
+            "create" => {
+                let mut format = Format::default();
+                let mut minutes = config.registration_minutes;
+                for arg in args {
+                    match (Format::parse(arg), arg.parse::<u64>()) {
+                        (Some(parsed), _) => format = parsed,
+                        (None, Ok(parsed)) => minutes = parsed,
+                        (None, Err(_)) => {
+                            let text = ctx.i18n.text(lang, "tournament.usage");
+                            bot.send_message(chat_id, text)
+                                .throttled(&ctx.throttle)
+                                .await?;
+                            return Ok(());
+                        }
                     }
-                    "choice_high" => {
-                        Self::play_high_low_game(&bot, chat_id, HighLowChoice::High).await?;
+                }
+                if !Self::is_chat_admin(&bot, &msg.chat, user.id).await? {
+                    Err(TournamentError::NotAllowed)
+                } else {
+                    let created = ctx.storage.modify_tournament(chat_id.0, |current, _| {
+                        if current.as_ref().is_some_and(|t| !t.is_over()) {
+                            return Err(TournamentError::AlreadyExists);
+                        }
+                        let starts_at = now + minutes * 60;
+                        let tournament =
+                            Tournament::new(user.id.0, lang, format, config, starts_at);
+                        *current = Some(tournament);
+                        Ok(())
+                    });
+                    match created {
+                        Ok(Ok(())) => {
+                            info!(format = format.name(), minutes, "Создан турнир");
+                            let format = Self::format_label(&ctx, lang, format);
+                            Ok(ctx.i18n.format(
+                                lang,
+                                "tournament.created",
+                                &[
+                                    ("format", &format),
+                                    ("fee", &config.entry_fee),
+                                    ("max", &config.max_players),
+                                    ("minutes", &minutes),
+                                ],
+                            ))
+                        }
+                        Ok(Err(error)) => Err(error),
+                        Err(e) => {
+                            error!(error = %e, "Не удалось сохранить турнир");
+                            return Ok(());
+                        }
                     }
-                    "choice_low" => {
-                        Self::play_high_low_game(&bot, chat_id, HighLowChoice::Low).await?;
+                }
+            }
+            "join" => {
+                let name = html::escape(&user.full_name());
+                let joined = ctx
+                    .storage
+                    .modify_tournament(chat_id.0, |current, accounts| {
+                        let tournament = current
+                            .as_mut()
+                            .filter(|t| !t.is_over())
+                            .ok_or(TournamentError::NotFound)?;
+                        let entrant = Entrant {
+                            user_id: user.id.0,
+                            name: name.clone(),
+                        };
+                        let profile = accounts.user(user.id.0);
+                        let fee = tournament.entry_fee;
+                        profile
+                            .limits
+                            .check(fee, now)
+                            .map_err(TournamentError::Limit)?;
+                        tournament.join(entrant, profile, config.max_players)?;
+                        profile.limits.record_round(fee, &ctx.config.limits, now);
+                        Ok((tournament.entrants.len(), tournament.pool()))
+                    });
+                match joined {
+                    Ok(Ok((count, pool))) => {
+                        info!(count, "Игрок зарегистрировался в турнире");
+                        Ok(ctx.i18n.format(
+                            lang,
+                            "tournament.joined",
+                            &[("name", &name), ("count", &count), ("pool", &pool)],
+                        ))
                     }
-                    "guess_one_yes" => {
-                        Self::play_guess_one_game(&bot, chat_id, GuessOneChoice::Yes).await?;
+                    Ok(Err(error)) => Err(error),
+                    Err(e) => {
+                        error!(error = %e, "Не удалось сохранить регистрацию в турнире");
+                        return Ok(());
                     }
-                    "guess_one_no" => {
-                        Self::play_guess_one_game(&bot, chat_id, GuessOneChoice::No).await?;
+                }
+            }
+            "start" => {
+                let is_admin = Self::is_chat_admin(&bot, &msg.chat, user.id).await?;
+                let started = ctx.storage.modify_tournament(chat_id.0, |current, _| {
+                    let tournament = current
+                        .as_mut()
+                        .filter(|t| !t.is_over())
+                        .ok_or(TournamentError::NotFound)?;
+                    if tournament.creator != user.id.0 && !is_admin {
+                        return Err(TournamentError::NotAllowed);
                     }
-                    data if data.starts_with("number_") => {
-                        if let Ok(number) = data[7..].parse::<u8>() {
-                            if (1..=6).contains(&number) {
-                                Self::play_exact_number_game(&bot, chat_id, number).await?;
-                            }
-                        }
+                    let mut rng = rand::thread_rng();
+                    tournament.start(now, config.round_delay(), &mut rng)?;
+                    Ok(tournament.clone())
+                });
+                match started {
+                    Ok(Ok(tournament)) => {
+                        info!(players = tournament.entrants.len(), "Турнир начат досрочно");
+                        Ok(Self::tournament_text(&ctx, &tournament, now))
                     }
-                    _ => {
-                        error!("Неизвестный callback: {}", data);
+                    Ok(Err(error)) => Err(error),
+                    Err(e) => {
+                        error!(error = %e, "Не удалось сохранить турнир");
+                        return Ok(());
                     }
                 }
             }
-        }
+            _ => Ok(ctx.i18n.text(lang, "tournament.usage").to_string()),
+        };
 
-        // Подтверждение callback запроса
-        bot.answer_callback_query(callback.id).await?;
+        let text = result.unwrap_or_else(|error| {
+            let key = format!("tournament.error.{}", error.kind());
+            match error {
+                TournamentError::NotEnoughChips { fee } => {
+                    ctx.i18n.format(lang, &key, &[("fee", &fee)])
+                }
+                TournamentError::Limit(error) => Self::limit_error_text(&ctx, lang, error),
+                _ => ctx.i18n.text(lang, &key).to_string(),
+            }
+        });
+        bot.send_message(chat_id, text)
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
+            .await?;
         Ok(())
     }
 
-    /// Начало игры "Четное/Нечетное"
-    async fn start_even_odd_game(bot: &Bot, chat_id: ChatId) -> ResponseResult<()> {
-        let keyboard = InlineKeyboardMarkup::new(vec![
-            vec![
-                InlineKeyboardButton::callback("🔵 Четное", "choice_even"),
-                InlineKeyboardButton::callback("🔴 Нечетное", "choice_odd"),
-            ],
-        ]);
-
-        let text = "🔵 <b>Игра: Четное/Нечетное</b>\n\n\
-                   Выберите, будет ли результат броска четным или нечетным числом:";
-
-        bot.send_message(chat_id, text)
+    /// Обработчик команды /jackpot: фонд и условие выигрыша
+    async fn jackpot_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("jackpot");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let config = &ctx.config.jackpot;
+        let streak = ctx
+            .i18n
+            .plural(lang, "jackpot.streak", config.exact_streak.into(), &[]);
+        let text = match config.enabled {
+            true => ctx.i18n.format(
+                lang,
+                "jackpot.status",
+                &[
+                    ("amount", &Self::jackpot_amount(&ctx, msg.chat.id)),
+                    ("streak", &streak),
+                    ("share", &config.share_percent),
+                ],
+            ),
+            false => ctx.i18n.text(lang, "jackpot.disabled").to_string(),
+        };
+        bot.send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
+            .throttled(&ctx.throttle)
             .await?;
-        
         Ok(())
     }
 
-    /// Начало игры "Больше/Меньше 3.5"
-    async fn start_high_low_game(bot: &Bot, chat_id: ChatId) -> ResponseResult<()> {
-        let keyboard = InlineKeyboardMarkup::new(vec![
-            vec![
-                InlineKeyboardButton::callback("⬆️ Больше 3.5 (4-6)", "choice_high"),
-                InlineKeyboardButton::callback("⬇️ Меньше 3.5 (1-3)", "choice_low"),
-            ],
-        ]);
+    /// Текущий фонд джекпота для чата
+    fn jackpot_amount(ctx: &AppContext, chat_id: ChatId) -> i64 {
+        let config = &ctx.config.jackpot;
+        ctx.storage
+            .jackpot(jackpot::pool_key(config.scope, chat_id.0))
+            .unwrap_or(config.seed)
+    }
 
-        let text = "🔴 <b>Игра: Больше/Меньше 3.5</b>\n\n\
-                   Выберите, будет ли результат больше или меньше 3.5:";
+    /// Обработчик команды /give: `/give @username сумма` или `/give сумма` в ответ на
+    /// сообщение получателя. Перевод выполняется после подтверждения кнопкой
+    async fn give_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("give");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        Self::remember_user(&ctx, user);
+        let chat_id = msg.chat.id;
+        let reply_to = msg
+            .reply_to_message()
+            .and_then(|reply| reply.from())
+            .filter(|recipient| !recipient.is_bot);
+        let args: Vec<&str> = args.split_whitespace().collect();
 
-        bot.send_message(chat_id, text)
-            .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
-            .await?;
-        
+        let (recipient, name, amount) = match (reply_to, args.as_slice()) {
+            (Some(recipient), [amount]) => (
+                Some(recipient.id.0),
+                html::escape(&recipient.full_name()),
+                *amount,
+            ),
+            (_, [username, amount]) if username.starts_with('@') => {
+                let recipient = ctx.storage.find_username(&username[1..]);
+                (recipient, html::escape(username), *amount)
+            }
+            _ => {
+                let text = ctx.i18n.text(lang, "give.usage");
+                bot.send_message(chat_id, text)
+                    .throttled(&ctx.throttle)
+                    .await?;
+                return Ok(());
+            }
+        };
+        let config = &ctx.config.transfer;
+        let checked = amount
+            .parse::<i64>()
+            .map_err(|_| TransferError::Amount {
+                min: config.min_amount,
+                max: config.max_amount,
+            })
+            .and_then(|amount| transfer::check_amount(amount, config).map(|()| amount));
+
+        let (text, keyboard) = match (recipient, checked) {
+            (None, _) => {
+                let text = ctx.i18n.format(lang, "give.unknown", &[("name", &name)]);
+                (text, None)
+            }
+            (Some(to), _) if to == user.id.0 => (
+                Self::transfer_error_text(&ctx, lang, TransferError::SelfTransfer),
+                None,
+            ),
+            (Some(_), Err(error)) => (Self::transfer_error_text(&ctx, lang, error), None),
+            (Some(to), Ok(amount)) => {
+                let t = |key| ctx.i18n.text(lang, key).to_string();
+                let round = ctx.rounds.open(chat_id);
+                let from = user.id.0;
+                let keyboard = InlineKeyboardMarkup::new(vec![vec![
+                    InlineKeyboardButton::callback(
+                        t("give.yes"),
+                        CallbackData::Give {
+                            from,
+                            to,
+                            amount,
+                            round,
+                        }
+                        .encode(),
+                    ),
+                    InlineKeyboardButton::callback(
+                        t("give.no"),
+                        CallbackData::GiveCancel { from, round }.encode(),
+                    ),
+                ]]);
+                let text = ctx.i18n.format(
+                    lang,
+                    "give.confirm",
+                    &[("amount", &amount), ("name", &name)],
+                );
+                (text, Some(keyboard))
+            }
+        };
+        let mut request = bot.send_message(chat_id, text);
+        request.parse_mode = Some(ParseMode::Html);
+        request.reply_markup = keyboard.map(Into::into);
+        request.throttled(&ctx.throttle).await?;
         Ok(())
     }
 
-    /// Начало игры "Точное число"
-    async fn start_exact_number_game(bot: &Bot, chat_id: ChatId) -> ResponseResult<()> {
-        let keyboard = InlineKeyboardMarkup::new(vec![
-            vec![
-                InlineKeyboardButton::callback("1️⃣", "number_1"),
-                InlineKeyboardButton::callback("2️⃣", "number_2"),
-                InlineKeyboardButton::callback("3️⃣", "number_3"),
-            ],
-            vec![
-                InlineKeyboardButton::callback("4️⃣", "number_4"),
-                InlineKeyboardButton::callback("5️⃣", "number_5"),
-                InlineKeyboardButton::callback("6️⃣", "number_6"),
-            ],
-        ]);
+    /// Перевод после подтверждения; возвращает текст результата
+    fn confirm_transfer(ctx: &AppContext, lang: Lang, from: u64, to: u64, amount: i64) -> String {
+        let config = &ctx.config.transfer;
+        let done = ctx.storage.modify_accounts(|accounts| {
+            let (sender, recipient) = accounts.pair(from, to).ok_or(TransferError::SelfTransfer)?;
+            transfer::transfer(
+                (from, sender),
+                (to, recipient),
+                amount,
+                config,
+                bonus::now(),
+            )?;
+            Ok(sender.balance)
+        });
+        match done {
+            Ok(Ok(balance)) => {
+                info!(amount, "Выполнен перевод фишек");
+                ctx.i18n.format(
+                    lang,
+                    "give.done",
+                    &[("amount", &amount), ("balance", &balance)],
+                )
+            }
+            Ok(Err(error)) => Self::transfer_error_text(ctx, lang, error),
+            Err(e) => {
+                error!(error = %e, "Не удалось сохранить перевод");
+                ctx.i18n.text(lang, "give.failed").to_string()
+            }
+        }
+    }
 
-        let text = "🎯 <b>Игра: Точное число</b>\n\n\
-                   Выберите число от 1 до 6, которое выпадет на кубике:";
+    /// Текст отказа в переводе
+    fn transfer_error_text(ctx: &AppContext, lang: Lang, error: TransferError) -> String {
+        let key = format!("give.error.{}", error.kind());
+        match error {
+            TransferError::Amount { min, max } => {
+                ctx.i18n.format(lang, &key, &[("min", &min), ("max", &max)])
+            }
+            TransferError::NotEnoughChips { balance } => {
+                ctx.i18n.format(lang, &key, &[("balance", &balance)])
+            }
+            TransferError::DailyLimit { left } => ctx.i18n.format(lang, &key, &[("left", &left)]),
+            TransferError::SelfTransfer | TransferError::Frozen => {
+                ctx.i18n.text(lang, &key).to_string()
+            }
+        }
+    }
 
-        bot.send_message(chat_id, text)
+    /// Обработчик команды /history: последние операции из журнала
+    async fn history_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("history");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let entries = match ctx.storage.history(user.id.0, HISTORY_LIMIT) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!(error = %e, "Не удалось прочитать журнал операций");
+                Vec::new()
+            }
+        };
+        let text = match entries.is_empty() {
+            true => ctx.i18n.text(lang, "history.empty").to_string(),
+            false => {
+                let mut lines = vec![ctx.i18n.text(lang, "history.header").to_string()];
+                for entry in entries {
+                    let key = format!("ledger.{}", entry.kind.name());
+                    let kind = ctx.i18n.text(lang, &key);
+                    lines.push(ctx.i18n.format(
+                        lang,
+                        "history.line",
+                        &[
+                            ("time", &ledger::format_time(entry.at)),
+                            ("kind", &kind),
+                            ("amount", &format!("{:+}", entry.amount)),
+                            ("balance", &entry.balance),
+                        ],
+                    ));
+                }
+                lines.join("\n")
+            }
+        };
+        bot.send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
+            .throttled(&ctx.throttle)
             .await?;
-        
         Ok(())
     }
 
-    /// Начало игры "Угадать единицу"
-    async fn start_guess_one_game(bot: &Bot, chat_id: ChatId) -> ResponseResult<()> {
-        let keyboard = InlineKeyboardMarkup::new(vec![
-            vec![
-                InlineKeyboardButton::callback("✅ Да, выпадет единица", "guess_one_yes"),
-                InlineKeyboardButton::callback("❌ Нет, не выпадет", "guess_one_no"),
-            ],
-        ]);
-
-        let text = "⚀ <b>Игра: Угадать единицу</b>\n\n\
-                   Выпадет ли на кубике единица?";
+    /// Обработчик команды /export: профиль, раунды и журнал операций документом.
+    /// Только в личном чате, чтобы данные не попали в группу
+    async fn export_command(
+        bot: Bot,
+        msg: Message,
+        format: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("export");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let key = match ExportFormat::parse(&format) {
+            _ if !msg.chat.is_private() => "privacy.private",
+            None => "export.usage",
+            Some(format) => match ctx.storage.ledger_entries(user.id.0) {
+                Ok(entries) => {
+                    let profile = ctx.storage.user(user.id.0);
+                    let export = Export::new(user.id.0, profile, entries, bonus::now());
+                    let name = format!("dice-bot-{}.{}", user.id.0, format.extension());
+                    let document = InputFile::memory(export.render(format)).file_name(name);
+                    info!(format = format.extension(), "Игрок выгрузил свои данные");
+                    bot.send_document(msg.chat.id, document)
+                        .caption(ctx.i18n.text(lang, "export.caption"))
+                        .throttled(&ctx.throttle)
+                        .await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!(error = %e, "Не удалось прочитать журнал для выгрузки");
+                    "export.failed"
+                }
+            },
+        };
+        bot.send_message(msg.chat.id, ctx.i18n.text(lang, key))
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
 
-        bot.send_message(chat_id, text)
+    /// Обработчик команды /forget_me: удаление данных после подтверждения кнопкой
+    async fn forget_me_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("forget_me");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        if !msg.chat.is_private() {
+            bot.send_message(msg.chat.id, ctx.i18n.text(lang, "privacy.private"))
+                .throttled(&ctx.throttle)
+                .await?;
+            return Ok(());
+        }
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let round = ctx.rounds.open(msg.chat.id);
+        let user = user.id.0;
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback(
+                t("forget.yes"),
+                CallbackData::Forget { user, round }.encode(),
+            ),
+            InlineKeyboardButton::callback(
+                t("forget.no"),
+                CallbackData::ForgetCancel { user, round }.encode(),
+            ),
+        ]]);
+        bot.send_message(msg.chat.id, t("forget.confirm"))
             .parse_mode(ParseMode::Html)
             .reply_markup(keyboard)
+            .throttled(&ctx.throttle)
             .await?;
-        
         Ok(())
     }
 
-    /// Игра "Четное/Нечетное"
-    async fn play_even_odd_game(bot: &Bot, chat_id: ChatId, choice: EvenOddChoice) -> ResponseResult<()> {
-        let choice_text = match choice {
-            EvenOddChoice::Even => "четное",
-            EvenOddChoice::Odd => "нечетное",
-        };
+    /// Удаление данных после подтверждения; возвращает текст результата
+    async fn confirm_forget(ctx: &AppContext, lang: Lang, user_id: u64) -> String {
+        // Журнал переписывается целиком, поэтому не в потоке обработчиков
+        let storage = ctx.storage.clone();
+        let result = tokio::task::spawn_blocking(move || storage.forget_user(user_id))
+            .await
+            .unwrap_or_else(|e| Err(io::Error::other(e)));
+        match result {
+            Ok(Ok(())) => {
+                info!("Данные игрока удалены по его запросу");
+                ctx.i18n.text(lang, "forget.done").to_string()
+            }
+            Ok(Err(ForgetError::Tournament)) => {
+                ctx.i18n.text(lang, "forget.error.tournament").to_string()
+            }
+            Err(e) => {
+                error!(error = %e, "Не удалось удалить данные игрока");
+                ctx.i18n.text(lang, "forget.failed").to_string()
+            }
+        }
+    }
 
-        // Отправляем сообщение о выборе пользователя
-        bot.send_message(chat_id, format!("🎯 Вы выбрали: {}\n🎲 Бросаю кубик...", choice_text))
-            .await?;
+    /// Обработчик команды /admin: список команд оператора
+    async fn admin_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("admin");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        Self::admin_reply(
+            &bot,
+            &ctx,
+            &msg,
+            ctx.i18n.text(lang, "admin.help").to_string(),
+        )
+        .await
+    }
 
-        // Отправляем анимированный кубик
-        let dice_message = bot.send_dice(chat_id).await?;
-        
-        // Получаем результат кубика
-        if let Some(dice) = dice_message.dice() {
-            let dice_result = dice.value as u8;
-            let is_win = DiceGame::check_even_odd(dice_result, choice.clone());
-            let result_text = if dice_result % 2 == 0 { "четное" } else { "нечетное" };
-            
-            // Даем время для анимации кубика
-            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            
-            let message = if is_win {
-                format!(
-                    "🎉 Число {}: {}\n\n{}",
-                    dice_result,
-                    result_text,
-                    DiceGame::win_message()
-                )
-            } else {
-                format!(
-                    "😔 Число {}: {}\n\n{}",
-                    dice_result,
-                    result_text,
-                    DiceGame::lose_message()
+    /// Обработчик команды /admin_stats: сводка по всем пользователям и чатам
+    async fn admin_stats_command(
+        bot: Bot,
+        msg: Message,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_stats");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let overview = ctx.storage.overview();
+        let text = ctx.i18n.format(
+            lang,
+            "admin.stats",
+            &[
+                ("users", &overview.users),
+                ("players", &overview.players),
+                ("rounds", &overview.rounds),
+                ("balance", &overview.balance),
+                ("jackpots", &overview.jackpots),
+                ("chats", &overview.chats),
+                ("tournaments", &overview.tournaments),
+                ("banned", &overview.banned),
+                ("frozen", &overview.frozen),
+            ],
+        );
+        Self::admin_reply(&bot, &ctx, &msg, text).await
+    }
+
+    /// Обработчик команды /admin_balance: `/admin_balance <кто> <±сумма>`
+    async fn admin_balance_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_balance");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(operator) = msg.from().map(|user| user.id.0) else {
+            return Ok(());
+        };
+        let (user_id, rest) = match Self::admin_target(&ctx, lang, &msg, &args) {
+            Ok(target) => target,
+            Err(text) => return Self::admin_reply(&bot, &ctx, &msg, text).await,
+        };
+        let Some(amount) = rest.first().and_then(|word| admin::parse_amount(word)) else {
+            let text = ctx.i18n.text(lang, "admin.help").to_string();
+            return Self::admin_reply(&bot, &ctx, &msg, text).await;
+        };
+        let user = Self::user_label(&ctx, user_id);
+        let text = match ctx
+            .storage
+            .modify_user(user_id, |p| admin::adjust(p, amount, operator))
+        {
+            Ok(Ok(balance)) => {
+                info!(target_user = user_id, amount, "Оператор изменил баланс");
+                ctx.i18n.format(
+                    lang,
+                    "admin.balance",
+                    &[
+                        ("user", &user),
+                        ("amount", &format!("{:+}", amount)),
+                        ("balance", &balance),
+                    ],
                 )
-            };
+            }
+            Ok(Err(error)) => {
+                let (key, balance) = match error {
+                    AdjustError::Negative(balance) => ("admin.balance_negative", balance),
+                    AdjustError::Overflow(balance) => ("admin.balance_overflow", balance),
+                };
+                ctx.i18n
+                    .format(lang, key, &[("user", &user), ("balance", &balance)])
+            }
+            Err(e) => {
+                error!(error = %e, "Не удалось сохранить изменение баланса оператором");
+                ctx.i18n.text(lang, "admin.failed").to_string()
+            }
+        };
+        Self::admin_reply(&bot, &ctx, &msg, text).await
+    }
 
-            bot.send_message(chat_id, message).await?;
-        }
-        
-        // Предложение новой игры
-        Self::offer_new_game(bot, chat_id).await
+    /// Обработчик команды /admin_freeze
+    async fn admin_freeze_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_freeze");
+        Self::admin_flag(&bot, &msg, &args, &ctx, "admin.frozen", |p| p.frozen = true).await
     }
 
-    /// Игра "Больше/Меньше 3.5"
-    async fn play_high_low_game(bot: &Bot, chat_id: ChatId, choice: HighLowChoice) -> ResponseResult<()> {
-        let choice_text = match choice {
-            HighLowChoice::High => "больше 3.5 (4-6)",
-            HighLowChoice::Low => "меньше 3.5 (1-3)",
-        };
+    /// Обработчик команды /admin_unfreeze
+    async fn admin_unfreeze_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_unfreeze");
+        Self::admin_flag(&bot, &msg, &args, &ctx, "admin.unfrozen", |p| {
+            p.frozen = false
+        })
+        .await
+    }
 
-        // Отправляем сообщение о выборе пользователя
-        bot.send_message(chat_id, format!("📊 Вы выбрали: {}\n🎲 Бросаю кубик...", choice_text))
-            .await?;
+    /// Обработчик команды /admin_ban
+    async fn admin_ban_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_ban");
+        Self::admin_flag(&bot, &msg, &args, &ctx, "admin.banned", |p| p.banned = true).await
+    }
 
-        // Отправляем анимированный кубик
-        let dice_message = bot.send_dice(chat_id).await?;
-        
-        // Получаем результат кубика
-        if let Some(dice) = dice_message.dice() {
-            let dice_result = dice.value as u8;
-            let is_win = DiceGame::check_high_low(dice_result, choice.clone());
-            let result_text = if dice_result >= 4 { "больше 3.5" } else { "меньше 3.5" };
-            
-            // Даем время для анимации кубика
-            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            
-            let message = if is_win {
-                format!(
-                    "🎉 Число {} - {}\n\n{}",
-                    dice_result,
-                    result_text,
-                    DiceGame::win_message()
-                )
-            } else {
-                format!(
-                    "😔 Число {} - {}\n\n{}",
-                    dice_result,
-                    result_text,
-                    DiceGame::lose_message()
-                )
-            };
+    /// Обработчик команды /admin_unban
+    async fn admin_unban_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_unban");
+        Self::admin_flag(&bot, &msg, &args, &ctx, "admin.unbanned", |p| {
+            p.banned = false
+        })
+        .await
+    }
 
-            bot.send_message(chat_id, message).await?;
-        }
-        
-        // Предложение новой игры
-        Self::offer_new_game(bot, chat_id).await
+    /// Заморозка или блокировка пользователя; `key` — строка с результатом
+    async fn admin_flag(
+        bot: &Bot,
+        msg: &Message,
+        args: &str,
+        ctx: &AppContext,
+        key: &str,
+        update: impl FnOnce(&mut UserProfile),
+    ) -> ResponseResult<()> {
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let text = match Self::admin_target(ctx, lang, msg, args) {
+            Ok((user_id, _)) => match ctx.storage.update_user(user_id, update) {
+                Ok(profile) => {
+                    info!(
+                        target_user = user_id,
+                        banned = profile.banned,
+                        frozen = profile.frozen,
+                        "Оператор изменил статус пользователя"
+                    );
+                    let user = Self::user_label(ctx, user_id);
+                    ctx.i18n.format(lang, key, &[("user", &user)])
+                }
+                Err(e) => {
+                    error!(error = %e, "Не удалось сохранить статус пользователя");
+                    ctx.i18n.text(lang, "admin.failed").to_string()
+                }
+            },
+            Err(text) => text,
+        };
+        Self::admin_reply(bot, ctx, msg, text).await
     }
 
-    /// Игра "Точное число"
-    async fn play_exact_number_game(bot: &Bot, chat_id: ChatId, guess: u8) -> ResponseResult<()> {
-        // Отправляем сообщение о выборе пользователя
-        bot.send_message(chat_id, format!("🎯 Вы выбрали число: {}\n🎲 Бросаю кубик...", guess))
-            .await?;
+    /// Обработчик команды /admin_broadcast: объявление рассылается в фоне через общую
+    /// очередь исходящих сообщений, по окончании оператор получает отчет
+    async fn admin_broadcast_command(
+        bot: Bot,
+        msg: Message,
+        text: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_broadcast");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            let help = ctx.i18n.text(lang, "admin.help").to_string();
+            return Self::admin_reply(&bot, &ctx, &msg, help).await;
+        }
+        // Рассылка, как и раунд, учитывается при завершении работы: бот дожидается,
+        // пока она остановится и оператор получит отчет
+        let Some(guard) = ctx.shutdown.start_round() else {
+            let text = ctx.i18n.text(lang, "admin.broadcast_refused").to_string();
+            return Self::admin_reply(&bot, &ctx, &msg, text).await;
+        };
+        let recipients = ctx.storage.broadcast_chats();
+        info!(
+            recipients = recipients.len(),
+            "Оператор отправляет объявление"
+        );
+        let started = ctx.i18n.format(
+            lang,
+            "admin.broadcast_started",
+            &[("count", &recipients.len())],
+        );
+        Self::admin_reply(&bot, &ctx, &msg, started).await?;
 
-        // Отправляем анимированный кубик
-        let dice_message = bot.send_dice(chat_id).await?;
-        
-        // Получаем результат кубика
-        if let Some(dice) = dice_message.dice() {
-            let dice_result = dice.value as u8;
-            let is_win = DiceGame::check_exact_number(dice_result, guess);
-            
-            // Даем время для анимации кубика
-            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            
-            let message = if is_win {
-                format!(
-                    "🎉 Выпало число: {}\nВы угадали!\n\n{}",
-                    dice_result,
-                    DiceGame::win_message()
+        let operator_chat = msg.chat.id;
+        tokio::spawn(async move {
+            let _guard = guard;
+            let total = recipients.len();
+            let (mut sent, mut failed) = (0, 0);
+            for chat_id in recipients {
+                if ctx.shutdown.is_draining() {
+                    break;
+                }
+                match bot
+                    .send_message(ChatId(chat_id), text.clone())
+                    .throttled(&ctx.throttle)
+                    .await
+                {
+                    Ok(_) => sent += 1,
+                    Err(e) => {
+                        // Чаще всего пользователь не начинал личный чат с ботом
+                        failed += 1;
+                        info!(chat_id, error = %e, "Объявление не доставлено");
+                    }
+                }
+            }
+            let left = total - sent - failed;
+            let report = if left > 0 {
+                warn!(
+                    sent,
+                    failed, left, "Рассылка объявления прервана завершением работы"
+                );
+                ctx.i18n.format(
+                    lang,
+                    "admin.broadcast_aborted",
+                    &[("sent", &sent), ("failed", &failed), ("left", &left)],
                 )
             } else {
-                format!(
-                    "😔 Выпало число: {}\nВы выбрали: {}\n\n{}",
-                    dice_result,
-                    guess,
-                    DiceGame::lose_message()
+                info!(sent, failed, "Объявление разослано");
+                ctx.i18n.format(
+                    lang,
+                    "admin.broadcast_done",
+                    &[("sent", &sent), ("failed", &failed)],
                 )
             };
+            if let Err(e) = bot
+                .send_message(operator_chat, report)
+                .throttled(&ctx.throttle)
+                .await
+            {
+                warn!(error = %e, "Не удалось отправить отчет об объявлении");
+            }
+        });
+        Ok(())
+    }
+
+    /// Обработчик команды /admin_game: без аргументов — состояние игр,
+    /// `/admin_game <игра> [on|off]` — включение, выключение или переключение
+    async fn admin_game_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_game");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let state = |game: &GameType| {
+            let key = match (ctx.config.games.is_enabled(game), ctx.game_enabled(game)) {
+                (false, _) => "admin.game_config_off",
+                (true, true) => "admin.game_on",
+                (true, false) => "admin.game_off",
+            };
+            ctx.i18n.text(lang, key).to_string()
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        let text = match args.as_slice() {
+            [] => {
+                let mut lines = vec![ctx.i18n.text(lang, "admin.games").to_string()];
+                for game in GameType::ALL {
+                    lines.push(ctx.i18n.format(
+                        lang,
+                        "admin.game_line",
+                        &[("name", &game.name()), ("state", &state(&game))],
+                    ));
+                }
+                lines.join("\n")
+            }
+            [name, switch @ ..] => match (GameType::parse(name), switch) {
+                (Some(game), _) if !ctx.config.games.is_enabled(&game) => ctx.i18n.format(
+                    lang,
+                    "admin.game_line",
+                    &[("name", &game.name()), ("state", &state(&game))],
+                ),
+                (Some(game), []) => {
+                    Self::admin_set_game(&ctx, lang, &game, !ctx.game_enabled(&game))
+                }
+                (Some(game), [word]) => match admin::parse_switch(word) {
+                    Some(enabled) => Self::admin_set_game(&ctx, lang, &game, enabled),
+                    None => ctx.i18n.text(lang, "admin.help").to_string(),
+                },
+                (Some(_), _) => ctx.i18n.text(lang, "admin.help").to_string(),
+                (None, _) => ctx.i18n.text(lang, "admin.game_unknown").to_string(),
+            },
+        };
+        Self::admin_reply(&bot, &ctx, &msg, text).await
+    }
 
-            bot.send_message(chat_id, message).await?;
+    /// Включение или выключение игры; возвращает текст результата
+    fn admin_set_game(ctx: &AppContext, lang: Lang, game: &GameType, enabled: bool) -> String {
+        if let Err(e) = ctx.storage.set_game_enabled(game, enabled) {
+            error!(error = %e, "Не удалось сохранить состояние игры");
+            return ctx.i18n.text(lang, "admin.failed").to_string();
         }
-        
-        // Предложение новой игры
-        Self::offer_new_game(bot, chat_id).await
+        info!(game = game.name(), enabled, "Оператор переключил игру");
+        let state = match enabled {
+            true => ctx.i18n.text(lang, "admin.game_on"),
+            false => ctx.i18n.text(lang, "admin.game_off"),
+        };
+        ctx.i18n.format(
+            lang,
+            "admin.game_changed",
+            &[("name", &game.name()), ("state", &state)],
+        )
     }
 
-    /// Игра "Угадать единицу"
-    async fn play_guess_one_game(bot: &Bot, chat_id: ChatId, choice: GuessOneChoice) -> ResponseResult<()> {
-        let choice_text = match choice {
-            GuessOneChoice::Yes => "да, выпадет единица",
-            GuessOneChoice::No => "нет, не выпадет единица",
+    /// Обработчик команды /admin_errors: последние ошибки и предупреждения из лога
+    async fn admin_errors_command(
+        bot: Bot,
+        msg: Message,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("admin_errors");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let records = ctx.errors.recent();
+        let text = match records.is_empty() {
+            true => ctx.i18n.text(lang, "admin.errors_empty").to_string(),
+            false => {
+                let mut lines = vec![ctx.i18n.text(lang, "admin.errors").to_string()];
+                for record in records {
+                    let message: String =
+                        record.message.chars().take(ERROR_MESSAGE_CHARS).collect();
+                    lines.push(ctx.i18n.format(
+                        lang,
+                        "admin.error_line",
+                        &[
+                            ("time", &ledger::format_time(record.at)),
+                            ("level", &record.level),
+                            ("message", &html::escape(&message)),
+                        ],
+                    ));
+                }
+                lines.join("\n")
+            }
         };
+        Self::admin_reply(&bot, &ctx, &msg, text).await
+    }
 
-        // Отправляем сообщение о выборе пользователя
-        bot.send_message(chat_id, format!("⚀ Вы выбрали: {}\n🎲 Бросаю кубик...", choice_text))
+    /// Пользователь, к которому относится команда оператора: автор сообщения, на которое
+    /// ответил оператор, или первый аргумент. Возвращает его id и остальные аргументы;
+    /// `Err` — текст ответа оператору
+    fn admin_target<'a>(
+        ctx: &AppContext,
+        lang: Lang,
+        msg: &Message,
+        args: &'a str,
+    ) -> Result<(u64, Vec<&'a str>), String> {
+        let mut args: Vec<&str> = args.split_whitespace().collect();
+        let reply_to = msg
+            .reply_to_message()
+            .and_then(|reply| reply.from())
+            .filter(|user| !user.is_bot);
+        if let Some(user) = reply_to {
+            return Ok((user.id.0, args));
+        }
+        let target = match args.is_empty() {
+            true => None,
+            false => Target::parse(args.remove(0)),
+        };
+        match target {
+            Some(Target::Id(user_id)) => Ok((user_id, args)),
+            Some(Target::Username(name)) => match ctx.storage.find_username(&name) {
+                Some(user_id) => Ok((user_id, args)),
+                None => {
+                    let name = html::escape(&format!("@{}", name));
+                    Err(ctx
+                        .i18n
+                        .format(lang, "admin.unknown_user", &[("name", &name)]))
+                }
+            },
+            None => Err(ctx.i18n.text(lang, "admin.help").to_string()),
+        }
+    }
+
+    /// Пользователь в ответах оператору: `@username (id)` или только id
+    fn user_label(ctx: &AppContext, user_id: u64) -> String {
+        match ctx.storage.user(user_id).username {
+            Some(username) => format!("@{} (<code>{}</code>)", html::escape(&username), user_id),
+            None => format!("<code>{}</code>", user_id),
+        }
+    }
+
+    async fn admin_reply(
+        bot: &Bot,
+        ctx: &AppContext,
+        msg: &Message,
+        text: String,
+    ) -> ResponseResult<()> {
+        bot.send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
             .await?;
+        Ok(())
+    }
 
-        // Отправляем анимированный кубик
-        let dice_message = bot.send_dice(chat_id).await?;
-        
-        // Получаем результат кубика
-        if let Some(dice) = dice_message.dice() {
-            let dice_result = dice.value as u8;
-            let is_win = DiceGame::check_guess_one(dice_result, choice.clone());
-            let result_text = if dice_result == 1 { "выпала единица" } else { "единица не выпала" };
-            
-            // Даем время для анимации кубика
-            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            
-            let message = if is_win {
-                format!(
-                    "🎉 Число {}: {}\n\n{}",
-                    dice_result,
-                    result_text,
-                    DiceGame::win_message()
-                )
-            } else {
-                format!(
-                    "😔 Число {}: {}\n\n{}",
-                    dice_result,
-                    result_text,
-                    DiceGame::lose_message()
-                )
+    /// Запоминание имени пользователя в Telegram для `/give @username`; после
+    /// `/forget_me` пользователь, снова написавший боту, опять получает объявления
+    fn remember_user(ctx: &AppContext, user: &User) {
+        let profile = ctx.storage.user(user.id.0);
+        if profile.username == user.username && !profile.forgotten {
+            return;
+        }
+        let username = user.username.clone();
+        if let Err(e) = ctx.storage.update_user(user.id.0, |p| {
+            p.username = username;
+            p.forgotten = false;
+        }) {
+            error!(error = %e, "Не удалось сохранить имя пользователя");
+        }
+    }
+
+    /// Администратор ли пользователь в чате; в личном чате — всегда да
+    async fn is_chat_admin(bot: &Bot, chat: &Chat, user_id: UserId) -> ResponseResult<bool> {
+        if chat.is_private() {
+            return Ok(true);
+        }
+        let member = bot.get_chat_member(chat.id, user_id).await?;
+        Ok(member.is_privileged())
+    }
+
+    /// Фоновая проверка турниров: старт после регистрации и очередные раунды.
+    /// Сроки берутся из хранилища, поэтому после перезапуска турниры продолжаются
+    pub async fn run_tournaments(bot: Bot, ctx: Arc<AppContext>) {
+        let mut interval = tokio::time::interval(TOURNAMENT_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        while !ctx.shutdown.is_draining() {
+            interval.tick().await;
+            let now = bonus::now();
+            for chat_id in ctx.storage.due_tournaments(now) {
+                if let Err(e) = Self::advance_tournament(&bot, &ctx, ChatId(chat_id), now).await {
+                    warn!(chat_id, error = %e, "Не удалось отправить объявление турнира");
+                }
+            }
+        }
+    }
+
+    /// Шаг турнира: старт (или отмена, если участников мало) либо игра текущего раунда
+    async fn advance_tournament(
+        bot: &Bot,
+        ctx: &AppContext,
+        chat_id: ChatId,
+        now: u64,
+    ) -> ResponseResult<()> {
+        let round_delay = ctx.config.tournament.round_delay();
+        let advanced = ctx
+            .storage
+            .modify_tournament(chat_id.0, |current, accounts| {
+                let tournament = current.as_mut().filter(|t| t.is_due(now))?;
+                let (kind, payouts) = match tournament.stage {
+                    Stage::Registration => {
+                        let mut rng = rand::thread_rng();
+                        match tournament.start(now, round_delay, &mut rng) {
+                            Ok(()) => (Kind::TournamentRefund, Vec::new()),
+                            Err(_) => (Kind::TournamentRefund, tournament.cancel()),
+                        }
+                    }
+                    _ => (
+                        Kind::TournamentPrize,
+                        tournament.play_round(now, round_delay, DiceGame::roll_dice),
+                    ),
+                };
+                for (user_id, amount) in payouts {
+                    let profile = accounts.user(user_id);
+                    profile.post(kind, amount);
+                    profile.limits.credit(amount, now);
+                }
+                Some(tournament.clone())
+            });
+        let tournament = match advanced {
+            Ok(Some(tournament)) => tournament,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                error!(error = %e, "Не удалось сохранить турнир");
+                return Ok(());
+            }
+        };
+        info!(
+            stage = ?tournament.stage,
+            round = tournament.round,
+            "Турнир продвинулся"
+        );
+        bot.send_message(chat_id, Self::tournament_text(ctx, &tournament, now))
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Состояние турнира: участники при регистрации, сетка и таблица во время игры, призы
+    /// по итогам. В круговой системе показываются только последние два раунда, чтобы
+    /// сообщение не превысило лимит Telegram
+    fn tournament_text(ctx: &AppContext, tournament: &Tournament, now: u64) -> String {
+        let lang = tournament.lang;
+        let i18n = &ctx.i18n;
+        let name = |user_id| tournament.name(user_id).to_string();
+        let format = Self::format_label(ctx, lang, tournament.format);
+
+        if tournament.stage == Stage::Cancelled {
+            return i18n.text(lang, "tournament.cancelled").to_string();
+        }
+        if tournament.stage == Stage::Registration {
+            let minutes = tournament.starts_at.saturating_sub(now).div_ceil(60);
+            let mut lines = vec![i18n.format(
+                lang,
+                "tournament.registration",
+                &[
+                    ("format", &format),
+                    ("fee", &tournament.entry_fee),
+                    ("pool", &tournament.pool()),
+                    ("minutes", &minutes),
+                    ("count", &tournament.entrants.len()),
+                ],
+            )];
+            lines.extend(tournament.entrants.iter().map(|e| format!("• {}", e.name)));
+            return lines.join("\n");
+        }
+
+        let mut lines = vec![i18n.format(
+            lang,
+            "tournament.header",
+            &[
+                ("format", &format),
+                ("round", &tournament.round),
+                ("rounds", &tournament.total_rounds()),
+            ],
+        )];
+        let first = match tournament.format {
+            Format::Knockout => 1,
+            Format::RoundRobin => tournament.round.saturating_sub(1).max(1),
+        };
+        for round in first..=tournament.round {
+            lines.push(String::new());
+            lines.push(i18n.format(lang, "tournament.round", &[("round", &round)]));
+            for game in tournament.round_matches(round) {
+                let line = match (game.away, game.score, game.winner) {
+                    (None, _, _) => {
+                        i18n.format(lang, "tournament.bye", &[("name", &name(game.home))])
+                    }
+                    (Some(away), Some((home_roll, away_roll)), Some(winner)) => i18n.format(
+                        lang,
+                        "tournament.match_played",
+                        &[
+                            ("home", &name(game.home)),
+                            ("away", &name(away)),
+                            ("home_roll", &DiceGame::dice_emoji(home_roll)),
+                            ("away_roll", &DiceGame::dice_emoji(away_roll)),
+                            ("winner", &name(winner)),
+                        ],
+                    ),
+                    (Some(away), _, _) => i18n.format(
+                        lang,
+                        "tournament.match_pending",
+                        &[("home", &name(game.home)), ("away", &name(away))],
+                    ),
+                };
+                lines.push(line);
+            }
+        }
+        if tournament.format == Format::RoundRobin {
+            lines.push(String::new());
+            lines.push(i18n.text(lang, "tournament.standings").to_string());
+            for (index, user_id) in tournament.standings().into_iter().enumerate() {
+                lines.push(i18n.format(
+                    lang,
+                    "tournament.standing",
+                    &[
+                        ("place", &(index + 1)),
+                        ("name", &name(user_id)),
+                        ("wins", &tournament.wins(user_id)),
+                    ],
+                ));
+            }
+        }
+
+        lines.push(String::new());
+        match tournament.stage {
+            Stage::Finished => {
+                let champion = tournament.placings().first().map(|group| name(group[0]));
+                lines.push(i18n.format(
+                    lang,
+                    "tournament.finished",
+                    &[("name", &champion.unwrap_or_default())],
+                ));
+                for (user_id, amount) in &tournament.prizes {
+                    lines.push(i18n.format(
+                        lang,
+                        "tournament.prize",
+                        &[("name", &name(*user_id)), ("amount", amount)],
+                    ));
+                }
+            }
+            _ => {
+                let next_at = tournament.next_round_at.unwrap_or(now);
+                let minutes = next_at.saturating_sub(now).div_ceil(60);
+                lines.push(i18n.format(lang, "tournament.next", &[("minutes", &minutes)]));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Название системы проведения турнира
+    fn format_label(ctx: &AppContext, lang: Lang, format: Format) -> String {
+        ctx.i18n
+            .text(lang, &format!("tournament.format.{}", format.name()))
+            .to_string()
+    }
+
+    /// Ставка игрока: выбранная командой /bet в пределах его уровня или базовая
+    fn stake_for(ctx: &AppContext, profile: &UserProfile) -> i64 {
+        let base = ctx.config.economy.stake;
+        let max = levels::max_stake(levels::level_for(profile.xp), base);
+        profile.stake.unwrap_or(base).clamp(base, max)
+    }
+
+    /// Текст отказа, если игра еще не открыта на уровне игрока
+    fn locked_game_text(
+        ctx: &AppContext,
+        lang: Lang,
+        user_id: UserId,
+        game: &GameType,
+    ) -> Option<String> {
+        if !ctx.config.levels.lock_games {
+            return None;
+        }
+        let required = levels::game_level(game);
+        let level = levels::level_for(ctx.storage.user(user_id.0).xp);
+        (level < required).then(|| {
+            ctx.i18n
+                .format(lang, "level.locked_game", &[("level", &required)])
+        })
+    }
+
+    /// Баннер нового уровня со списком открытого
+    fn level_up_text(ctx: &AppContext, lang: Lang, level: u32) -> String {
+        let i18n = &ctx.i18n;
+        let unlocks = levels::level(level);
+        let mut lines = vec![i18n.format(lang, "level.up", &[("level", &level)])];
+        for game in unlocks.games {
+            if ctx.config.levels.lock_games && ctx.game_enabled(game) {
+                let name = i18n.text(lang, Self::game_label(game)).to_string();
+                lines.push(i18n.format(lang, "level.unlock_game", &[("game", &name)]));
+            }
+        }
+        if let Some(skin) = unlocks.skin {
+            let name = i18n
+                .text(lang, &format!("skin.{}", skin.name()))
+                .to_string();
+            lines.push(i18n.format(lang, "level.unlock_skin", &[("skin", &name)]));
+        }
+        if level > 1 && unlocks.bet_multiplier > levels::level(level - 1).bet_multiplier {
+            let stake = levels::max_stake(level, ctx.config.economy.stake);
+            lines.push(i18n.format(lang, "level.unlock_bet", &[("stake", &stake)]));
+        }
+        lines.join("\n")
+    }
+
+    /// Ключ названия игры в каталоге строк
+    fn game_label(game: &GameType) -> &'static str {
+        match game {
+            GameType::EvenOdd => "menu.even_odd",
+            GameType::HighLow => "menu.high_low",
+            GameType::ExactNumber => "menu.exact",
+            GameType::GuessOne => "menu.guess_one",
+        }
+    }
+
+    /// Текст отказа в бесплатных фишках; `prefix` — ключ команды (`bonus.faucet`)
+    fn bonus_error_text(ctx: &AppContext, lang: Lang, prefix: &str, error: BonusError) -> String {
+        match error {
+            BonusError::AlreadyClaimed { retry_in } | BonusError::Cooldown { retry_in } => {
+                Self::wait_text(ctx, lang, &format!("{}_wait", prefix), retry_in)
+            }
+            BonusError::BalanceTooHigh { limit } => {
+                ctx.i18n
+                    .format(lang, &format!("{}_rich", prefix), &[("limit", &limit)])
+            }
+            BonusError::Disabled => ctx.i18n.text(lang, "bonus.disabled").to_string(),
+            BonusError::Frozen => ctx.i18n.text(lang, "account.frozen").to_string(),
+        }
+    }
+
+    /// Текст с оставшимся временем в часах и минутах (минуты округляются вверх)
+    fn wait_text(ctx: &AppContext, lang: Lang, key: &str, retry_in: Duration) -> String {
+        let minutes = retry_in.as_secs().div_ceil(60);
+        ctx.i18n.format(
+            lang,
+            key,
+            &[("hours", &(minutes / 60)), ("minutes", &(minutes % 60))],
+        )
+    }
+
+    /// Обработчик команды /help
+    async fn help_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("help");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let text = ctx
+            .i18n
+            .format(lang, "help.body", &[("stake", &ctx.config.economy.stake)]);
+
+        bot.send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Обработчик команды /play
+    async fn play_command(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        ctx.metrics.command("play");
+        let _timer = ctx.metrics.handler_timer("command");
+        info!("Пользователь начал новую игру");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        Self::show_game_selection(&bot, &ctx, lang, msg.chat.id, None).await
+    }
+
+    /// Обработчик команды /lang: без аргумента показывает выбор языка
+    async fn lang_command(
+        bot: Bot,
+        msg: Message,
+        code: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("lang");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+
+        if code.trim().is_empty() {
+            let keyboard = InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("🇷🇺 Русский", CallbackData::Lang(Lang::Ru).encode()),
+                InlineKeyboardButton::callback("🇬🇧 English", CallbackData::Lang(Lang::En).encode()),
+            ]]);
+            bot.send_message(msg.chat.id, ctx.i18n.text(lang, "lang.prompt"))
+                .reply_markup(keyboard)
+                .throttled(&ctx.throttle)
+                .await?;
+            return Ok(());
+        }
+
+        match (Lang::parse(&code), msg.from()) {
+            (Some(new_lang), Some(user)) => {
+                Self::set_language(&bot, &ctx, user.id, msg.chat.id, new_lang).await
+            }
+            _ => {
+                bot.send_message(msg.chat.id, ctx.i18n.text(lang, "lang.unknown"))
+                    .throttled(&ctx.throttle)
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Сохранение выбранного пользователем языка
+    async fn set_language(
+        bot: &Bot,
+        ctx: &AppContext,
+        user_id: UserId,
+        chat_id: ChatId,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        if let Err(e) = ctx.storage.update_user(user_id.0, |p| p.lang = Some(lang)) {
+            error!(error = %e, "Не удалось сохранить язык пользователя");
+        }
+        info!(lang = lang.code(), "Пользователь выбрал язык");
+        bot.send_message(chat_id, ctx.i18n.text(lang, "lang.changed"))
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Обработчик команды /ui: без аргумента показывает выбор режима для чата
+    async fn ui_command(
+        bot: Bot,
+        msg: Message,
+        mode: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("ui");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        if !Self::is_chat_admin(&bot, &msg.chat, user.id).await? {
+            bot.send_message(msg.chat.id, t("settings.not_admin"))
+                .throttled(&ctx.throttle)
+                .await?;
+            return Ok(());
+        }
+
+        if mode.trim().is_empty() {
+            let button = |mode: UiMode| {
+                let label = ctx
+                    .i18n
+                    .text(lang, &format!("ui.{}", mode.name()))
+                    .to_string();
+                vec![InlineKeyboardButton::callback(
+                    label,
+                    CallbackData::Ui(mode).encode(),
+                )]
+            };
+            let keyboard =
+                InlineKeyboardMarkup::new(vec![button(UiMode::Messages), button(UiMode::InPlace)]);
+            bot.send_message(msg.chat.id, t("ui.prompt"))
+                .reply_markup(keyboard)
+                .throttled(&ctx.throttle)
+                .await?;
+            return Ok(());
+        }
+
+        match UiMode::parse(&mode) {
+            Some(mode) => Self::set_ui_mode(&bot, &ctx, lang, msg.chat.id, mode).await,
+            None => {
+                bot.send_message(msg.chat.id, t("ui.unknown"))
+                    .throttled(&ctx.throttle)
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Сохранение режима интерфейса чата
+    async fn set_ui_mode(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        mode: UiMode,
+    ) -> ResponseResult<()> {
+        if let Err(e) = ctx.storage.update_chat(chat_id.0, |c| c.ui = Some(mode)) {
+            error!(error = %e, "Не удалось сохранить режим интерфейса чата");
+        }
+        info!(mode = mode.name(), "Чат выбрал режим интерфейса");
+        let label = ctx
+            .i18n
+            .text(lang, &format!("ui.{}", mode.name()))
+            .to_string();
+        let text = ctx.i18n.format(lang, "ui.changed", &[("mode", &label)]);
+        bot.send_message(chat_id, text)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Обработчик команды /settings: без аргументов показывает настройки чата, с
+    /// аргументами меняет одну из них. Доступна только администраторам чата
+    async fn settings_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("settings");
+        let _timer = ctx.metrics.handler_timer("command");
+        let chat_id = msg.chat.id;
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        if !Self::is_chat_admin(&bot, &msg.chat, user.id).await? {
+            let lang = ctx.lang_in(chat_id, Some(user));
+            bot.send_message(chat_id, ctx.i18n.text(lang, "settings.not_admin"))
+                .throttled(&ctx.throttle)
+                .await?;
+            return Ok(());
+        }
+
+        let text = match (args.trim().is_empty(), Change::parse(&args)) {
+            (true, _) => Self::settings_text(&ctx, ctx.lang_in(chat_id, Some(user)), chat_id),
+            (false, None) => {
+                let lang = ctx.lang_in(chat_id, Some(user));
+                let t = |key| ctx.i18n.text(lang, key).to_string();
+                format!("{}\n\n{}", t("settings.invalid"), t("settings.usage"))
+            }
+            (false, Some(change)) => {
+                match ctx.storage.update_chat(chat_id.0, |c| change.apply(c)) {
+                    Ok(_) => {
+                        info!(setting = %args.trim(), "Администратор изменил настройки чата");
+                        // Ответ уже на новом языке, если он изменился
+                        let lang = ctx.lang_in(chat_id, Some(user));
+                        let changed = ctx.i18n.text(lang, "settings.changed");
+                        format!(
+                            "{}\n\n{}",
+                            changed,
+                            Self::settings_text(&ctx, lang, chat_id)
+                        )
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Не удалось сохранить настройки чата");
+                        let lang = ctx.lang_in(chat_id, Some(user));
+                        ctx.i18n.text(lang, "settings.failed").to_string()
+                    }
+                }
+            }
+        };
+        bot.send_message(chat_id, text)
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Текущие настройки чата и подсказка, как их изменить
+    fn settings_text(ctx: &AppContext, lang: Lang, chat_id: ChatId) -> String {
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let settings = ctx.storage.chat(chat_id.0);
+        // Игры, выключенные в конфигурации или оператором, администратор включить не может
+        let games = GameType::ALL
+            .iter()
+            .filter(|game| ctx.game_enabled(game))
+            .map(|game| {
+                let mark = if settings.game_enabled(game) {
+                    "✅"
+                } else {
+                    "🚫"
+                };
+                format!("{} <code>{}</code>", mark, game.name())
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let bet = match settings.stake_limits {
+            Some((min, max)) => {
+                ctx.i18n
+                    .format(lang, "settings.bet_range", &[("min", &min), ("max", &max)])
+            }
+            None => t("settings.bet_default"),
+        };
+        let chat_lang = match settings.lang {
+            Some(lang) => lang.code().to_string(),
+            None => t("settings.lang_default"),
+        };
+        let ui = format!("ui.{}", ctx.ui_mode(chat_id).name());
+        let cooldown = match settings.cooldown_seconds {
+            0 => t("settings.cooldown_off"),
+            seconds => ctx
+                .i18n
+                .format(lang, "settings.cooldown_value", &[("seconds", &seconds)]),
+        };
+        let text = ctx.i18n.format(
+            lang,
+            "settings.show",
+            &[
+                ("games", &games),
+                ("bet", &bet),
+                ("lang", &chat_lang),
+                ("ui", &t(&ui)),
+                ("cooldown", &cooldown),
+            ],
+        );
+        format!("{}\n\n{}", text, t("settings.usage"))
+    }
+
+    /// Обработчик команды /limits: текущие ограничения или их изменение
+    async fn limits_command(
+        bot: Bot,
+        msg: Message,
+        args: String,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        ctx.metrics.command("limits");
+        let _timer = ctx.metrics.handler_timer("command");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+        let Some(user) = msg.from() else {
+            return Ok(());
+        };
+        let config = &ctx.config.limits;
+        let now = bonus::now();
+
+        let text = match (args.trim().is_empty(), Request::parse(&args, config)) {
+            (true, _) => Self::limits_text(&ctx, lang, user.id.0),
+            (false, None) => {
+                let usage = Self::limits_usage(&ctx, lang);
+                format!("{}\n\n{}", ctx.i18n.text(lang, "limits.invalid"), usage)
+            }
+            (false, Some(request)) => {
+                let changed = ctx
+                    .storage
+                    .modify_user(user.id.0, |p| p.limits.request(request, config, now));
+                match changed {
+                    Ok(outcome) => {
+                        info!(request = %args.trim(), "Игрок изменил ограничения");
+                        let at = |key, name, at| {
+                            ctx.i18n
+                                .format(lang, key, &[(name, &ledger::format_time(at))])
+                        };
+                        let done = match outcome {
+                            Outcome::Applied => ctx.i18n.text(lang, "limits.applied").to_string(),
+                            Outcome::Scheduled { at: when } => at("limits.scheduled", "at", when),
+                            Outcome::Excluded { until } => at("limits.excluded", "until", until),
+                        };
+                        format!("{}\n\n{}", done, Self::limits_text(&ctx, lang, user.id.0))
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Не удалось сохранить ограничения игрока");
+                        ctx.i18n.text(lang, "limits.failed").to_string()
+                    }
+                }
+            }
+        };
+        bot.send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::Html)
+            .throttled(&ctx.throttle)
+            .await?;
+        Ok(())
+    }
+
+    /// Текущие ограничения игрока, отложенные ослабления и подсказка, как их изменить
+    fn limits_text(ctx: &AppContext, lang: Lang, user_id: u64) -> String {
+        let now = bonus::now();
+        let mut limits = ctx.storage.user(user_id).limits;
+        limits.refresh(now);
+        let none = ctx.i18n.text(lang, "limits.none").to_string();
+        let loss_text = |amount: Option<i64>| match amount {
+            Some(amount) => ctx
+                .i18n
+                .format(lang, "limits.loss_value", &[("amount", &amount)]),
+            None => none.clone(),
+        };
+        let reminder_text = |minutes: Option<u64>| match minutes {
+            Some(minutes) => {
+                ctx.i18n
+                    .format(lang, "limits.reminder_value", &[("minutes", &minutes)])
+            }
+            None => none.clone(),
+        };
+        let exclusion = match limits.excluded_until {
+            Some(until) => ctx.i18n.format(
+                lang,
+                "limits.excluded_value",
+                &[("until", &ledger::format_time(until))],
+            ),
+            None => none.clone(),
+        };
+        let mut lines = vec![ctx.i18n.format(
+            lang,
+            "limits.show",
+            &[
+                ("loss", &loss_text(limits.loss_limit)),
+                ("lost", &limits.lost_today(now).max(0)),
+                ("reminder", &reminder_text(limits.reminder_minutes)),
+                ("exclusion", &exclusion),
+            ],
+        )];
+        for pending in &limits.pending {
+            let (key, value) = match pending.limit {
+                Limit::Loss(amount) => ("limits.pending_loss", loss_text(amount)),
+                Limit::Reminder(minutes) => ("limits.pending_reminder", reminder_text(minutes)),
             };
+            let at = ledger::format_time(pending.at);
+            lines.push(
+                ctx.i18n
+                    .format(lang, key, &[("value", &value), ("at", &at)]),
+            );
+        }
+        format!("{}\n\n{}", lines.join("\n"), Self::limits_usage(ctx, lang))
+    }
+
+    fn limits_usage(ctx: &AppContext, lang: Lang) -> String {
+        let config = &ctx.config.limits;
+        ctx.i18n.format(
+            lang,
+            "limits.usage",
+            &[
+                ("days", &config.max_exclusion_days),
+                ("hours", &config.cool_off_hours),
+            ],
+        )
+    }
+
+    /// Текст отказа в ставке
+    fn stake_error_text(ctx: &AppContext, lang: Lang, stake: i64, error: StakeError) -> String {
+        match error {
+            StakeError::Frozen => ctx.i18n.text(lang, "account.frozen").to_string(),
+            StakeError::NotEnoughChips { balance } => ctx.i18n.format(
+                lang,
+                "round.no_chips",
+                &[("stake", &stake), ("balance", &balance)],
+            ),
+            StakeError::Limit(error) => Self::limit_error_text(ctx, lang, error),
+        }
+    }
+
+    /// Текст отказа в раунде из-за ограничений игрока
+    fn limit_error_text(ctx: &AppContext, lang: Lang, error: LimitError) -> String {
+        match error {
+            LimitError::Excluded { until } => ctx.i18n.format(
+                lang,
+                "limits.error.excluded",
+                &[("until", &ledger::format_time(until))],
+            ),
+            LimitError::LossLimit { limit, left } => ctx.i18n.format(
+                lang,
+                "limits.error.loss",
+                &[("limit", &limit), ("left", &left)],
+            ),
+        }
+    }
+
+    /// Показ шага раунда: правка сообщения `target` в режиме in_place, иначе новое сообщение
+    async fn show(
+        bot: &Bot,
+        ctx: &AppContext,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+        text: String,
+        parse_mode: Option<ParseMode>,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> ResponseResult<()> {
+        match target {
+            Some(message_id) => {
+                // Правка без клавиатуры убирает прежние кнопки
+                let mut request = bot.edit_message_text(chat_id, message_id, text);
+                request.parse_mode = parse_mode;
+                request.reply_markup = keyboard;
+                request.throttled(&ctx.throttle).await?;
+            }
+            None => {
+                let mut request = bot.send_message(chat_id, text);
+                request.parse_mode = parse_mode;
+                request.reply_markup = keyboard.map(Into::into);
+                request.throttled(&ctx.throttle).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Отображение выбора типа игры
+    async fn show_game_selection(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+    ) -> ResponseResult<()> {
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let rows = GameType::ALL
+            .iter()
+            .filter(|game| ctx.chat_game_enabled(chat_id, game))
+            .map(|game| {
+                let label = Self::game_label(game);
+                let data = CallbackData::Game(game.clone()).encode();
+                vec![InlineKeyboardButton::callback(t(label), data)]
+            })
+            .collect::<Vec<_>>();
+        let keyboard = InlineKeyboardMarkup::new(rows);
+        let mut text = t("menu.prompt");
+        let jackpot = &ctx.config.jackpot;
+        if jackpot.enabled {
+            let streak = ctx
+                .i18n
+                .plural(lang, "jackpot.streak", jackpot.exact_streak.into(), &[]);
+            let line = ctx.i18n.format(
+                lang,
+                "jackpot.menu",
+                &[
+                    ("amount", &Self::jackpot_amount(ctx, chat_id)),
+                    ("streak", &streak),
+                ],
+            );
+            text = format!("{}\n\n{}", text, line);
+        }
+
+        Self::show(bot, ctx, chat_id, target, text, None, Some(keyboard)).await
+    }
+
+    /// Кнопки после раунда: повтор с тем же выбором (новый раунд открывается сразу, чтобы
+    /// серия игр занимала одно нажатие), другой выбор в той же игре и меню игр
+    fn again_keyboard(
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        choice: &GameChoice,
+    ) -> InlineKeyboardMarkup {
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let round = ctx.rounds.open(chat_id);
+        let choice_text = ctx.i18n.choice_text(lang, choice);
+        let repeat = ctx
+            .i18n
+            .format(lang, "again.repeat", &[("choice", &choice_text)]);
+        InlineKeyboardMarkup::new(vec![
+            vec![InlineKeyboardButton::callback(
+                repeat,
+                CallbackData::Play {
+                    choice: choice.clone(),
+                    round,
+                }
+                .encode(),
+            )],
+            vec![
+                InlineKeyboardButton::callback(
+                    t("again.other"),
+                    CallbackData::Game(choice.game_type()).encode(),
+                ),
+                InlineKeyboardButton::callback(t("again.menu"), CallbackData::Menu.encode()),
+            ],
+        ])
+    }
+
+    /// Обработчик callback запросов
+    async fn handle_callback(
+        bot: Bot,
+        callback: CallbackQuery,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        let _timer = ctx.metrics.handler_timer("callback");
+        let lang = match &callback.message {
+            Some(message) => ctx.lang_in(message.chat.id, Some(&callback.from)),
+            None => ctx.lang_for(Some(&callback.from)),
+        };
+        let ctx = ctx.as_ref();
+        Self::remember_user(ctx, &callback.from);
+
+        let (Some(data), Some(message)) = (&callback.data, &callback.message) else {
+            bot.answer_callback_query(callback.id).await?;
+            return Ok(());
+        };
+        let chat_id = message.chat.id;
+
+        let action = match CallbackData::decode(data) {
+            Ok(action) => action,
+            Err(error) => {
+                ctx.metrics.callback(error.kind());
+                info!(?error, data = %data, "Кнопка не разобрана");
+                let key = match error {
+                    CallbackError::Outdated => "callback.outdated",
+                    CallbackError::Invalid => "callback.invalid",
+                };
+                bot.answer_callback_query(callback.id)
+                    .text(ctx.i18n.text(lang, key))
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
+            }
+        };
+        ctx.metrics.callback(action.kind());
+        // В режиме in_place шаги раунда редактируют сообщение с нажатой кнопкой
+        let target = (ctx.ui_mode(chat_id) == UiMode::InPlace).then_some(message.id);
 
-            bot.send_message(chat_id, message).await?;
+        match action {
+            CallbackData::Menu => {
+                Self::show_game_selection(&bot, ctx, lang, chat_id, target).await?;
+            }
+            CallbackData::Game(game) => {
+                if let Some(text) = Self::locked_game_text(ctx, lang, callback.from.id, &game) {
+                    bot.answer_callback_query(callback.id)
+                        .text(text)
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                Self::start_game(&bot, ctx, lang, chat_id, target, &game).await?;
+            }
+            CallbackData::Play { choice, round } => {
+                let game = choice.game_type();
+                if let Some(text) = Self::locked_game_text(ctx, lang, callback.from.id, &game) {
+                    bot.answer_callback_query(callback.id)
+                        .text(text)
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                // Пауза из настроек чата не тратит кнопку: после нее можно нажать снова
+                let pause = Duration::from_secs(ctx.storage.chat(chat_id.0).cooldown_seconds);
+                if let Err(retry_in) = ctx.cooldowns.check(chat_id, callback.from.id, pause) {
+                    let seconds = retry_in.as_secs_f64().ceil().max(1.0) as u64;
+                    let text = ctx
+                        .i18n
+                        .format(lang, "settings.wait", &[("seconds", &seconds)]);
+                    bot.answer_callback_query(callback.id)
+                        .text(text)
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                // Отказ до броска тоже не тратит кнопку: пополнив баланс, можно нажать снова
+                let profile = ctx.storage.user(callback.from.id.0);
+                if let Err(text) = Self::accept_round(ctx, lang, chat_id, &profile, &choice) {
+                    bot.answer_callback_query(callback.id)
+                        .text(text)
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                // Кнопки выбора действуют один раз: повторное нажатие не начинает новый раунд
+                if let Err(error) = ctx.rounds.settle(round, chat_id) {
+                    info!(?error, "Нажатие на кнопку закрытого раунда");
+                    let key = match error {
+                        RoundError::AlreadyPlayed => "round.already_played",
+                        RoundError::Expired => "round.expired",
+                    };
+                    bot.answer_callback_query(callback.id)
+                        .text(ctx.i18n.text(lang, key))
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                // Убираем клавиатуру, чтобы по ней нельзя было сыграть еще раз; в режиме
+                // in_place ее заменит правка сообщения
+                if target.is_none() {
+                    if let Err(e) = bot
+                        .edit_message_reply_markup(chat_id, message.id)
+                        .throttled(&ctx.throttle)
+                        .await
+                    {
+                        error!(error = %e, "Не удалось убрать клавиатуру раунда");
+                    }
+                }
+                let user_id = callback.from.id;
+                Self::play_round(&bot, ctx, lang, chat_id, target, user_id, choice).await?;
+            }
+            CallbackData::Lang(new_lang) => {
+                Self::set_language(&bot, ctx, callback.from.id, chat_id, new_lang).await?;
+            }
+            CallbackData::Ui(mode) => {
+                if !Self::is_chat_admin(&bot, &message.chat, callback.from.id).await? {
+                    bot.answer_callback_query(callback.id)
+                        .text(ctx.i18n.text(lang, "settings.not_admin"))
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                Self::set_ui_mode(&bot, ctx, lang, chat_id, mode).await?;
+            }
+            CallbackData::Skin(skin) => {
+                let level = levels::level_for(ctx.storage.user(callback.from.id.0).xp);
+                if level < skin.required_level() {
+                    let text =
+                        ctx.i18n
+                            .format(lang, "skin.locked", &[("level", &skin.required_level())]);
+                    bot.answer_callback_query(callback.id)
+                        .text(text)
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                if let Err(e) = ctx
+                    .storage
+                    .update_user(callback.from.id.0, |p| p.skin = skin)
+                {
+                    error!(error = %e, "Не удалось сохранить скин броска");
+                }
+                let label = ctx
+                    .i18n
+                    .text(lang, &format!("skin.{}", skin.name()))
+                    .to_string();
+                let text = ctx.i18n.format(lang, "skin.changed", &[("skin", &label)]);
+                bot.send_message(chat_id, text)
+                    .throttled(&ctx.throttle)
+                    .await?;
+            }
+            CallbackData::Give { from, .. } | CallbackData::GiveCancel { from, .. }
+                if callback.from.id.0 != from =>
+            {
+                bot.answer_callback_query(callback.id)
+                    .text(ctx.i18n.text(lang, "give.not_yours"))
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
+            }
+            CallbackData::Give {
+                from,
+                to,
+                amount,
+                round,
+            } => {
+                // Подтверждение действует один раз: двойное нажатие не переводит дважды
+                if ctx.rounds.settle(round, chat_id).is_err() {
+                    bot.answer_callback_query(callback.id)
+                        .text(ctx.i18n.text(lang, "give.expired"))
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                let text = Self::confirm_transfer(ctx, lang, from, to, amount);
+                Self::show(&bot, ctx, chat_id, Some(message.id), text, None, None).await?;
+            }
+            CallbackData::GiveCancel { round, .. } => {
+                let _ = ctx.rounds.settle(round, chat_id);
+                let text = ctx.i18n.text(lang, "give.cancelled").to_string();
+                Self::show(&bot, ctx, chat_id, Some(message.id), text, None, None).await?;
+            }
+            CallbackData::Forget { user, .. } | CallbackData::ForgetCancel { user, .. }
+                if callback.from.id.0 != user =>
+            {
+                bot.answer_callback_query(callback.id)
+                    .text(ctx.i18n.text(lang, "forget.not_yours"))
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
+            }
+            CallbackData::Forget { user, round } => {
+                if ctx.rounds.settle(round, chat_id).is_err() {
+                    bot.answer_callback_query(callback.id)
+                        .text(ctx.i18n.text(lang, "forget.expired"))
+                        .show_alert(true)
+                        .await?;
+                    return Ok(());
+                }
+                let text = Self::confirm_forget(ctx, lang, user).await;
+                Self::show(&bot, ctx, chat_id, Some(message.id), text, None, None).await?;
+            }
+            CallbackData::ForgetCancel { round, .. } => {
+                let _ = ctx.rounds.settle(round, chat_id);
+                let text = ctx.i18n.text(lang, "forget.cancelled").to_string();
+                Self::show(&bot, ctx, chat_id, Some(message.id), text, None, None).await?;
+            }
         }
-        
-        // Предложение новой игры
-        Self::offer_new_game(bot, chat_id).await
+
+        // Подтверждение callback запроса
+        bot.answer_callback_query(callback.id).await?;
+        Ok(())
     }
 
-    /// Предложение новой игры
-    async fn offer_new_game(bot: &Bot, chat_id: ChatId) -> ResponseResult<()> {
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        
-        let text = "🎮 Хотите сыграть еще раз? Используйте /play для новой игры!";
-        bot.send_message(chat_id, text).await?;
-        
+    /// Обработчик inline запросов (`@botname 2d6`, `@botname even`)
+    async fn handle_inline_query(
+        bot: Bot,
+        query: InlineQuery,
+        ctx: Arc<AppContext>,
+    ) -> ResponseResult<()> {
+        let _timer = ctx.metrics.handler_timer("inline_query");
+        let lang = ctx.lang_for(Some(&query.from));
+        let results: Vec<InlineQueryResult> = inline::parse_query(&query.query)
+            .iter()
+            .enumerate()
+            .map(|(index, request)| {
+                let (count, sides) = inline::dice_for(request);
+                let rolls: Vec<u8> = (0..count).map(|_| DiceGame::roll_die(sides)).collect();
+                let balance = ctx.storage.user(query.from.id.0).balance;
+                let card = inline::render(&ctx.i18n, lang, request, &rolls, balance);
+                InlineQueryResult::Article(
+                    InlineQueryResultArticle::new(
+                        format!("{}-{}", query.id, index),
+                        card.title,
+                        InputMessageContent::Text(InputMessageContentText::new(card.text)),
+                    )
+                    .description(card.description),
+                )
+            })
+            .collect();
+
+        // Результаты бросков уникальны, поэтому кэширование отключено
+        bot.answer_inline_query(query.id, results)
+            .cache_time(0)
+            .is_personal(true)
+            .await?;
         Ok(())
     }
 
+    /// Начало выбранной в меню игры
+    async fn start_game(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+        game: &GameType,
+    ) -> ResponseResult<()> {
+        if !ctx.chat_game_enabled(chat_id, game) {
+            let text = ctx.i18n.text(lang, "game.disabled").to_string();
+            return Self::show(bot, ctx, chat_id, target, text, None, None).await;
+        }
+        match game {
+            GameType::EvenOdd => Self::start_even_odd_game(bot, ctx, lang, chat_id, target).await,
+            GameType::HighLow => Self::start_high_low_game(bot, ctx, lang, chat_id, target).await,
+            GameType::ExactNumber => {
+                Self::start_exact_number_game(bot, ctx, lang, chat_id, target).await
+            }
+            GameType::GuessOne => Self::start_guess_one_game(bot, ctx, lang, chat_id, target).await,
+        }
+    }
+
+    /// Начало игры "Четное/Нечетное"
+    async fn start_even_odd_game(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+    ) -> ResponseResult<()> {
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let round = ctx.rounds.open(chat_id);
+        let button = |key, choice| {
+            InlineKeyboardButton::callback(t(key), CallbackData::Play { choice, round }.encode())
+        };
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            button("even_odd.even", GameChoice::EvenOdd(EvenOddChoice::Even)),
+            button("even_odd.odd", GameChoice::EvenOdd(EvenOddChoice::Odd)),
+        ]]);
+
+        let text = t("even_odd.intro");
+        Self::show(
+            bot,
+            ctx,
+            chat_id,
+            target,
+            text,
+            Some(ParseMode::Html),
+            Some(keyboard),
+        )
+        .await
+    }
+
+    /// Начало игры "Больше/Меньше 3.5"
+    async fn start_high_low_game(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+    ) -> ResponseResult<()> {
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let round = ctx.rounds.open(chat_id);
+        let button = |key, choice| {
+            InlineKeyboardButton::callback(t(key), CallbackData::Play { choice, round }.encode())
+        };
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            button("high_low.high", GameChoice::HighLow(HighLowChoice::High)),
+            button("high_low.low", GameChoice::HighLow(HighLowChoice::Low)),
+        ]]);
+
+        let text = t("high_low.intro");
+        Self::show(
+            bot,
+            ctx,
+            chat_id,
+            target,
+            text,
+            Some(ParseMode::Html),
+            Some(keyboard),
+        )
+        .await
+    }
+
+    /// Начало игры "Точное число"
+    async fn start_exact_number_game(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+    ) -> ResponseResult<()> {
+        let round = ctx.rounds.open(chat_id);
+        let button = |label: &str, number: u8| {
+            let choice = GameChoice::ExactNumber(number);
+            InlineKeyboardButton::callback(label, CallbackData::Play { choice, round }.encode())
+        };
+        let keyboard = InlineKeyboardMarkup::new(vec![
+            vec![button("1️⃣", 1), button("2️⃣", 2), button("3️⃣", 3)],
+            vec![button("4️⃣", 4), button("5️⃣", 5), button("6️⃣", 6)],
+        ]);
+
+        let text = ctx.i18n.text(lang, "exact.intro").to_string();
+        Self::show(
+            bot,
+            ctx,
+            chat_id,
+            target,
+            text,
+            Some(ParseMode::Html),
+            Some(keyboard),
+        )
+        .await
+    }
+
+    /// Начало игры "Угадать единицу"
+    async fn start_guess_one_game(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+    ) -> ResponseResult<()> {
+        let t = |key| ctx.i18n.text(lang, key).to_string();
+        let round = ctx.rounds.open(chat_id);
+        let button = |key, choice| {
+            InlineKeyboardButton::callback(t(key), CallbackData::Play { choice, round }.encode())
+        };
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            button("guess_one.yes", GameChoice::GuessOne(GuessOneChoice::Yes)),
+            button("guess_one.no", GameChoice::GuessOne(GuessOneChoice::No)),
+        ]]);
+
+        let text = t("guess_one.intro");
+        Self::show(
+            bot,
+            ctx,
+            chat_id,
+            target,
+            text,
+            Some(ParseMode::Html),
+            Some(keyboard),
+        )
+        .await
+    }
+
+    /// Проверки до броска: игра доступна в чате, бот не останавливается, ставка принимается.
+    /// Возвращает ставку с учетом пределов чата или текст отказа
+    fn accept_round(
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        profile: &UserProfile,
+        choice: &GameChoice,
+    ) -> Result<i64, String> {
+        if !ctx.chat_game_enabled(chat_id, &choice.game_type()) {
+            return Err(ctx.i18n.text(lang, "game.disabled").to_string());
+        }
+        if ctx.shutdown.is_draining() {
+            return Err(ctx.i18n.text(lang, "round.shutting_down").to_string());
+        }
+        // Пределы ставки из настроек чата важнее выбранной игроком ставки
+        let stake = ctx
+            .storage
+            .chat(chat_id.0)
+            .clamp_stake(Self::stake_for(ctx, profile));
+        DiceGame::check_stake(profile, stake, bonus::now())
+            .map_err(|error| Self::stake_error_text(ctx, lang, stake, error))?;
+        Ok(stake)
+    }
+
+    /// Раунд игры: ставка, бросок кубика, расчет выигрыша
+    async fn play_round(
+        bot: &Bot,
+        ctx: &AppContext,
+        lang: Lang,
+        chat_id: ChatId,
+        target: Option<MessageId>,
+        user_id: UserId,
+        choice: GameChoice,
+    ) -> ResponseResult<()> {
+        let i18n = &ctx.i18n;
+        let profile = ctx.storage.user(user_id.0);
+        let game = choice.game_type().key();
+        let choice_text = i18n.choice_text(lang, &choice);
+
+        let stake = match Self::accept_round(ctx, lang, chat_id, &profile, &choice) {
+            Ok(stake) => stake,
+            Err(text) => {
+                return Self::show(bot, ctx, chat_id, target, text, Some(ParseMode::Html), None)
+                    .await
+            }
+        };
+        // Раунд считается идущим, пока не доставлено предложение новой игры, чтобы при
+        // завершении работы игрок успел получить результат
+        let Some(round) = ctx.shutdown.start_round() else {
+            let text = i18n.text(lang, "round.shutting_down").to_string();
+            return Self::show(bot, ctx, chat_id, target, text, Some(ParseMode::Html), None).await;
+        };
+        ctx.cooldowns.record(chat_id, user_id);
+
+        // Отправляем сообщение о выборе пользователя
+        let text = i18n.format(
+            lang,
+            &format!("{}.chosen", game),
+            &[("choice", &choice_text), ("stake", &stake)],
+        );
+        Self::show(
+            bot,
+            ctx,
+            chat_id,
+            target,
+            text.clone(),
+            Some(ParseMode::Html),
+            None,
+        )
+        .await?;
+
+        // Отправляем анимированный кубик; в режиме in_place это единственное новое сообщение
+        let dice_message = bot
+            .send_dice(chat_id)
+            .emoji(profile.skin.emoji())
+            .throttled(&ctx.throttle)
+            .await?;
+
+        // Предложение новой игры завершает раунд
+        let delays = &ctx.config.delays;
+        let again = i18n.text(lang, "round.again");
+        let keyboard = Self::again_keyboard(ctx, lang, chat_id, &choice);
+        // В режиме in_place предложение дописывается к последнему тексту сообщения
+        let offer = |previous: &str| {
+            let text = match target {
+                Some(_) => format!("{}\n\n{}", previous, again),
+                None => again.to_string(),
+            };
+            Delivery::new(chat_id, text)
+                .editing(target)
+                .with_keyboard(keyboard)
+        };
+
+        // Получаем результат кубика
+        let Some(dice) = dice_message.dice() else {
+            ctx.scheduler
+                .schedule(delays.offer_new_game(), offer(&text).with_round(round));
+            return Ok(());
+        };
+        let dice_result = dice.value as u8;
+        let settled = Self::settle_round(ctx, user_id, chat_id, &choice, dice_result, stake);
+        // Результат объявляется после анимации кубика; обработчик не ждет доставки
+        let announce = |message: String| {
+            let offer = offer(&message).with_round(round);
+            let result = Delivery::new(chat_id, message)
+                .editing(target)
+                .then(delays.offer_new_game(), offer);
+            ctx.scheduler.schedule(delays.dice_animation(), result);
+        };
+        let settlement = match settled {
+            Ok(Ok(settlement)) => settlement,
+            Ok(Err(error)) => {
+                info!(?error, "Ставка не принята при расчете раунда");
+                let reason = Self::stake_error_text(ctx, lang, stake, error);
+                announce(format!("{}\n{}", i18n.text(lang, "round.void"), reason));
+                return Ok(());
+            }
+            Err(e) => {
+                error!(error = %e, "Не удалось сохранить баланс пользователя");
+                announce(i18n.format(lang, "round.failed", &[("dice", &dice_result)]));
+                return Ok(());
+            }
+        };
+        let Settlement {
+            win,
+            payout,
+            balance,
+            unlocked,
+            new_levels,
+            jackpot_won,
+            reminder,
+        } = settlement;
+        let game_type = choice.game_type();
+        ctx.metrics.round(&game_type, dice_result, win);
+        info!(
+            game = game_type.name(),
+            dice = dice_result,
+            win,
+            payout,
+            "Раунд сыгран"
+        );
+        let outcome_text = i18n.outcome_text(lang, &game_type, dice_result);
+
+        let (key, pool) = if win {
+            (format!("{}.win", game), "pool.win")
+        } else {
+            (format!("{}.lose", game), "pool.lose")
+        };
+        let verdict = i18n.random(
+            lang,
+            pool,
+            &[
+                ("dice", &dice_result),
+                ("choice", &choice_text),
+                ("outcome", &outcome_text),
+                ("balance", &balance),
+            ],
+        );
+        let mut message = i18n.format(
+            lang,
+            &key,
+            &[
+                ("dice", &dice_result),
+                ("choice", &choice_text),
+                ("outcome", &outcome_text),
+                ("verdict", &verdict),
+                ("balance", &balance),
+                ("stake", &stake),
+            ],
+        );
+        if let Some(amount) = jackpot_won {
+            info!(amount, "Выигран джекпот");
+            let line = i18n.format(lang, "jackpot.won", &[("amount", &amount)]);
+            message = format!("{}\n\n{}", message, line);
+        }
+        // Новые достижения объявляются вместе с результатом раунда
+        for achievement in unlocked {
+            info!(achievement = achievement.id, "Получено достижение");
+            let title = i18n.text(lang, &achievement.title_key()).to_string();
+            let line = i18n.format(
+                lang,
+                "achievement.unlocked",
+                &[("badge", &achievement.badge), ("title", &title)],
+            );
+            message = format!("{}\n{}", message, line);
+        }
+        for level in new_levels {
+            info!(level, "Новый уровень игрока");
+            message = format!("{}\n\n{}", message, Self::level_up_text(ctx, lang, level));
+        }
+        if let Some(played) = reminder {
+            let minutes = played.as_secs() / 60;
+            let line = i18n.format(lang, "limits.reminder", &[("minutes", &minutes)]);
+            message = format!("{}\n\n{}", message, line);
+        }
+
+        announce(message);
+        Ok(())
+    }
+
+    /// Расчет раунда под одной блокировкой хранилища: повторная проверка ставки, списание,
+    /// выплата, достижения, опыт, джекпот и учет ограничений игрока
+    fn settle_round(
+        ctx: &AppContext,
+        user_id: UserId,
+        chat_id: ChatId,
+        choice: &GameChoice,
+        dice_result: u8,
+        stake: i64,
+    ) -> io::Result<Result<Settlement, StakeError>> {
+        let win = DiceGame::check(dice_result, choice);
+        let payout = if win {
+            DiceGame::payout(choice, stake, &ctx.config.economy.payouts)
+        } else {
+            0
+        };
+        let game_type = choice.game_type();
+        let jackpot = &ctx.config.jackpot;
+        let pool_key = jackpot::pool_key(jackpot.scope, chat_id.0);
+        ctx.storage
+            .modify_round(user_id.0, pool_key, jackpot.seed, |p, pool| {
+                DiceGame::check_stake(p, stake, bonus::now())?;
+                p.post(Kind::Bet, -stake);
+                if payout > 0 {
+                    p.post(Kind::Payout, payout);
+                }
+                let unlocked = achievements::record_round(
+                    &mut p.stats,
+                    &mut p.achievements,
+                    &game_type,
+                    dice_result,
+                    win,
+                );
+                let new_levels = levels::gain(&mut p.xp, levels::xp_for(&game_type, win));
+                let mut jackpot_won = None;
+                if jackpot.enabled {
+                    let hit = win && jackpot::is_hit(&p.stats, &game_type, jackpot);
+                    jackpot_won = jackpot::settle(pool, jackpot, stake, win, hit);
+                    if let Some(won) = jackpot_won {
+                        p.post(Kind::Jackpot, won);
+                    }
+                }
+                let lost = stake - payout - jackpot_won.unwrap_or(0);
+                let reminder = p
+                    .limits
+                    .record_round(lost, &ctx.config.limits, bonus::now());
+                Ok(Settlement {
+                    win,
+                    payout,
+                    balance: p.balance,
+                    unlocked,
+                    new_levels,
+                    jackpot_won,
+                    reminder,
+                })
+            })
+    }
+
     /// Обработчик обычных сообщений
-    async fn handle_message(bot: Bot, msg: Message) -> ResponseResult<()> {
+    async fn handle_message(bot: Bot, msg: Message, ctx: Arc<AppContext>) -> ResponseResult<()> {
+        let _timer = ctx.metrics.handler_timer("message");
+        let lang = ctx.lang_in(msg.chat.id, msg.from());
+
         if let Some(text) = msg.text() {
             match text.to_lowercase().as_str() {
-                "играть" | "игра" | "кубик" | "кубики" => {
-                    Self::show_game_selection(&bot, msg.chat.id).await?;
+                "играть" | "игра" | "кубик" | "кубики" | "play" | "dice" => {
+                    Self::show_game_selection(&bot, &ctx, lang, msg.chat.id, None).await?;
                 }
                 "привет" | "hello" | "hi" => {
-                    bot.send_message(msg.chat.id, "🎲 Привет! Хотите сыграть в кубики? Используйте /play")
+                    bot.send_message(msg.chat.id, ctx.i18n.text(lang, "message.greeting"))
+                        .throttled(&ctx.throttle)
                         .await?;
                 }
                 _ => {
-                    bot.send_message(
-                        msg.chat.id,
-                        "🤔 Не понимаю команду. Используйте /help для получения справки или /play для игры."
-                    ).await?;
+                    bot.send_message(msg.chat.id, ctx.i18n.text(lang, "message.unknown"))
+                        .throttled(&ctx.throttle)
+                        .await?;
                 }
             }
         }
-        
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::health::Health;
+    use crate::metrics::Metrics;
+    use crate::ratelimit::Throttle;
+    use crate::scheduler::{Scheduler, ThrottledBot};
+    use crate::storage::Storage;
+    use pretty_assertions::assert_eq;
+    use teloxide::error_handlers::LoggingErrorHandler;
+
+    const ROUNDS: usize = 8;
+    const STAKE: i64 = 10;
+
+    fn context(dir: &std::path::Path) -> AppContext {
+        let config = Config::default();
+        let throttle = Arc::new(Throttle::new(&config.rate_limit));
+        let sender = ThrottledBot {
+            bot: Bot::new("test"),
+            throttle: throttle.clone(),
+        };
+        AppContext::new(
+            config,
+            I18n::new(),
+            Storage::open(dir.join("storage.json")).unwrap(),
+            Arc::new(Metrics::new()),
+            Arc::new(Health::new()),
+            Scheduler::start(sender, LoggingErrorHandler::new()),
+            throttle,
+        )
+    }
+
+    /// Проигрышные раунды игрока из нескольких потоков сразу; `Ok` — баланс после раунда
+    fn lose_concurrently(ctx: &AppContext, user_id: u64) -> Vec<Result<i64, StakeError>> {
+        let choice = GameChoice::EvenOdd(EvenOddChoice::Even);
+        std::thread::scope(|scope| {
+            let rounds: Vec<_> = (0..ROUNDS)
+                .map(|_| {
+                    scope.spawn(|| {
+                        BotHandler::settle_round(
+                            ctx,
+                            UserId(user_id),
+                            ChatId(-1),
+                            &choice,
+                            1,
+                            STAKE,
+                        )
+                        .unwrap()
+                        .map(|settlement| settlement.balance)
+                    })
+                })
+                .collect();
+            rounds
+                .into_iter()
+                .map(|round| round.join().unwrap())
+                .collect()
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_rounds_keep_balance_and_loss_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = context(dir.path());
+
+        // Баланс на три ставки, и одновременно с раундами списание вне раунда
+        ctx.storage
+            .update_user(1, |p| p.balance = 3 * STAKE + 5)
+            .unwrap();
+        let (results, debited) = std::thread::scope(|scope| {
+            let debit = scope.spawn(|| {
+                ctx.storage.modify_user(1, |p| {
+                    let debited = p.balance >= STAKE;
+                    if debited {
+                        p.post(Kind::Admin, -STAKE);
+                    }
+                    debited
+                })
+            });
+            let results = lose_concurrently(&ctx, 1);
+            (results, debit.join().unwrap().unwrap())
+        });
+        let accepted = results.iter().filter(|result| result.is_ok()).count() as i64;
+        assert_eq!(accepted + i64::from(debited), 3);
+        assert!(results.iter().all(|result| match result {
+            Ok(balance) => *balance >= 0,
+            Err(error) => matches!(error, StakeError::NotEnoughChips { .. }),
+        }));
+        assert_eq!(ctx.storage.user(1).balance, 5);
+
+        // Лимит проигрыша на три ставки при большом балансе
+        ctx.storage
+            .update_user(2, |p| p.limits.loss_limit = Some(3 * STAKE))
+            .unwrap();
+        let results = lose_concurrently(&ctx, 2);
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 3);
+        assert!(results.iter().all(|result| match result {
+            Ok(_) => true,
+            Err(error) => matches!(error, StakeError::Limit(LimitError::LossLimit { .. })),
+        }));
+        let profile = ctx.storage.user(2);
+        assert_eq!(profile.limits.lost_today(bonus::now()), 3 * STAKE);
+        assert_eq!(
+            profile.balance,
+            ctx.config.economy.starting_balance - 3 * STAKE
+        );
+    }
+
+    #[test]
+    fn test_command_menu_is_translated() {
+        let i18n = I18n::new();
+        let ru = Command::menu(&i18n, Lang::Ru);
+        let en = Command::menu(&i18n, Lang::En);
+        assert_eq!(ru.len(), Command::bot_commands().len());
+        for (ru, en) in ru.iter().zip(&en) {
+            assert_eq!(ru.command, en.command);
+            assert!(!ru.description.starts_with("command."), "{}", ru.command);
+            assert_ne!(ru.description, en.description, "{}", ru.command);
+        }
+        assert_eq!(en[0].description, "Start the game");
+    }
+}
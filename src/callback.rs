@@ -0,0 +1,265 @@
+//! Данные inline-кнопок.
+//!
+//! Каждая кнопка бота — вариант [`CallbackData`], закодированный как
+//! `<версия>:<тег>[:параметры]`, например `1:p:even_odd:even:17`. Telegram ограничивает
+//! данные кнопки 64 байтами; самая длинная кнопка (выбор с номером раунда) укладывается
+//! с запасом. При несовместимом изменении формата увеличивается [`VERSION`]: кнопки старых
+//! сообщений получают ответ «кнопка устарела», а не молча игнорируются.
+
+use crate::config::UiMode;
+use crate::i18n::Lang;
+use crate::levels::Skin;
+use crate::state::{GameChoice, GameType};
+
+/// Версия формата данных кнопок
+pub const VERSION: u32 = 1;
+
+/// Ограничение Telegram на длину `callback_data`
+pub const MAX_LEN: usize = 64;
+
+/// Действие, закодированное в кнопке
+#[derive(Clone, Debug, PartialEq)]
+pub enum CallbackData {
+    /// Меню выбора игры
+    Menu,
+    /// Начало игры: варианты выбора
+    Game(GameType),
+    /// Выбор в раунде `round`
+    Play { choice: GameChoice, round: u64 },
+    /// Смена языка пользователя
+    Lang(Lang),
+    /// Смена режима интерфейса чата
+    Ui(UiMode),
+    /// Выбор скина броска
+    Skin(Skin),
+    /// Подтверждение перевода `/give`; `round` делает кнопку одноразовой
+    Give {
+        from: u64,
+        to: u64,
+        amount: i64,
+        round: u64,
+    },
+    /// Отмена перевода
+    GiveCancel { from: u64, round: u64 },
+    /// Подтверждение удаления данных `/forget_me`
+    Forget { user: u64, round: u64 },
+    /// Отмена удаления данных
+    ForgetCancel { user: u64, round: u64 },
+}
+
+/// Почему данные кнопки не разобраны
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CallbackError {
+    /// Кнопка другой версии бота (в том числе старые строковые `choice_even:17`)
+    Outdated,
+    /// Данные текущей версии, но не соответствуют ни одному действию
+    Invalid,
+}
+
+impl CallbackError {
+    /// Имя для метрик
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CallbackError::Outdated => "outdated",
+            CallbackError::Invalid => "invalid",
+        }
+    }
+}
+
+impl CallbackData {
+    pub fn encode(&self) -> String {
+        let data = match self {
+            CallbackData::Menu => format!("{}:m", VERSION),
+            CallbackData::Game(game) => format!("{}:g:{}", VERSION, game.name()),
+            CallbackData::Play { choice, round } => format!(
+                "{}:p:{}:{}:{}",
+                VERSION,
+                choice.game_type().name(),
+                choice.code(),
+                round
+            ),
+            CallbackData::Lang(lang) => format!("{}:l:{}", VERSION, lang.code()),
+            CallbackData::Ui(mode) => format!("{}:u:{}", VERSION, mode.name()),
+            CallbackData::Skin(skin) => format!("{}:s:{}", VERSION, skin.name()),
+            CallbackData::Give {
+                from,
+                to,
+                amount,
+                round,
+            } => format!("{}:t:{}:{}:{}:{}", VERSION, from, to, amount, round),
+            CallbackData::GiveCancel { from, round } => {
+                format!("{}:tc:{}:{}", VERSION, from, round)
+            }
+            CallbackData::Forget { user, round } => format!("{}:f:{}:{}", VERSION, user, round),
+            CallbackData::ForgetCancel { user, round } => {
+                format!("{}:fc:{}:{}", VERSION, user, round)
+            }
+        };
+        debug_assert!(
+            data.len() <= MAX_LEN,
+            "данные кнопки длиннее 64 байт: {}",
+            data
+        );
+        data
+    }
+
+    pub fn decode(data: &str) -> Result<Self, CallbackError> {
+        let Some((version, rest)) = data.split_once(':') else {
+            return Err(CallbackError::Outdated);
+        };
+        if version.parse::<u32>() != Ok(VERSION) {
+            return Err(CallbackError::Outdated);
+        }
+        let parts: Vec<&str> = rest.split(':').collect();
+        let decoded = match parts.as_slice() {
+            ["m"] => Some(CallbackData::Menu),
+            ["g", game] => GameType::parse(game).map(CallbackData::Game),
+            ["p", game, choice, round] => GameType::parse(game)
+                .and_then(|game| GameChoice::parse(&game, choice))
+                .zip(round.parse().ok())
+                .map(|(choice, round)| CallbackData::Play { choice, round }),
+            ["l", lang] => Lang::parse(lang).map(CallbackData::Lang),
+            ["u", mode] => UiMode::parse(mode).map(CallbackData::Ui),
+            ["s", skin] => Skin::parse(skin).map(CallbackData::Skin),
+            ["t", from, to, amount, round] => {
+                match (from.parse(), to.parse(), amount.parse(), round.parse()) {
+                    (Ok(from), Ok(to), Ok(amount), Ok(round)) => Some(CallbackData::Give {
+                        from,
+                        to,
+                        amount,
+                        round,
+                    }),
+                    _ => None,
+                }
+            }
+            ["tc", from, round] => from
+                .parse()
+                .ok()
+                .zip(round.parse().ok())
+                .map(|(from, round)| CallbackData::GiveCancel { from, round }),
+            ["f", user, round] => user
+                .parse()
+                .ok()
+                .zip(round.parse().ok())
+                .map(|(user, round)| CallbackData::Forget { user, round }),
+            ["fc", user, round] => user
+                .parse()
+                .ok()
+                .zip(round.parse().ok())
+                .map(|(user, round)| CallbackData::ForgetCancel { user, round }),
+            _ => None,
+        };
+        decoded.ok_or(CallbackError::Invalid)
+    }
+
+    /// Вид кнопки для метрик (без параметров, чтобы не плодить метки)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CallbackData::Menu => "menu",
+            CallbackData::Game(_) => "game",
+            CallbackData::Play { .. } => "play",
+            CallbackData::Lang(_) => "lang",
+            CallbackData::Ui(_) => "ui",
+            CallbackData::Skin(_) => "skin",
+            CallbackData::Give { .. } => "give",
+            CallbackData::GiveCancel { .. } => "give_cancel",
+            CallbackData::Forget { .. } => "forget",
+            CallbackData::ForgetCancel { .. } => "forget_cancel",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{EvenOddChoice, GuessOneChoice};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_roundtrip_fits_telegram_limit() {
+        let all = vec![
+            CallbackData::Menu,
+            CallbackData::Game(GameType::ExactNumber),
+            CallbackData::Play {
+                choice: GameChoice::EvenOdd(EvenOddChoice::Even),
+                round: 17,
+            },
+            CallbackData::Play {
+                choice: GameChoice::ExactNumber(6),
+                round: u64::MAX,
+            },
+            CallbackData::Play {
+                choice: GameChoice::GuessOne(GuessOneChoice::No),
+                round: 1,
+            },
+            CallbackData::Lang(Lang::En),
+            CallbackData::Ui(UiMode::InPlace),
+            CallbackData::Skin(Skin::Bowling),
+            // Id пользователей Telegram укладываются в 52 бита
+            CallbackData::Give {
+                from: 1 << 52,
+                to: (1 << 52) - 1,
+                amount: 1_000_000,
+                round: u32::MAX.into(),
+            },
+            CallbackData::GiveCancel {
+                from: 1 << 52,
+                round: 5,
+            },
+            CallbackData::Forget {
+                user: 1 << 52,
+                round: u64::MAX,
+            },
+            CallbackData::ForgetCancel {
+                user: 1 << 52,
+                round: 5,
+            },
+        ];
+        for data in all {
+            let encoded = data.encode();
+            assert!(encoded.len() <= MAX_LEN, "{}", encoded);
+            assert_eq!(CallbackData::decode(&encoded), Ok(data));
+        }
+        assert_eq!(CallbackData::Menu.encode(), "1:m");
+        assert_eq!(
+            CallbackData::Play {
+                choice: GameChoice::EvenOdd(EvenOddChoice::Even),
+                round: 17,
+            }
+            .encode(),
+            "1:p:even_odd:even:17"
+        );
+    }
+
+    #[test]
+    fn test_old_and_broken_buttons() {
+        // Кнопки до появления версий и кнопки будущих версий
+        assert_eq!(
+            CallbackData::decode("choice_even:17"),
+            Err(CallbackError::Outdated)
+        );
+        assert_eq!(
+            CallbackData::decode("game_exact"),
+            Err(CallbackError::Outdated)
+        );
+        assert_eq!(CallbackData::decode("2:m"), Err(CallbackError::Outdated));
+
+        assert_eq!(CallbackData::decode("1:x"), Err(CallbackError::Invalid));
+        assert_eq!(
+            CallbackData::decode("1:p:exact_number:7:3"),
+            Err(CallbackError::Invalid)
+        );
+        assert_eq!(
+            CallbackData::decode("1:p:even_odd:high:3"),
+            Err(CallbackError::Invalid)
+        );
+        assert_eq!(
+            CallbackData::decode("1:p:even_odd:even"),
+            Err(CallbackError::Invalid)
+        );
+        assert_eq!(
+            CallbackData::decode("1:g:poker"),
+            Err(CallbackError::Invalid)
+        );
+    }
+}
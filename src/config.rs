@@ -0,0 +1,1091 @@
+//! Конфигурация бота: TOML-файл, поверх которого накладываются переменные окружения.
+//!
+//! Файл берется из `CONFIG_PATH` (или `config.toml` в рабочем каталоге, если он есть).
+//! Все ошибки — синтаксис файла, некорректные поля в каждом разделе, переменные
+//! окружения, недопустимые значения — собираются в один список вместо паники на первой
+//! же. Разделы и поля разбираются по отдельности, так что проверяется все, что
+//! разобралось.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::game::DEFAULT_STARTING_BALANCE;
+use crate::jackpot::Scope;
+use crate::state::GameType;
+use crate::telemetry::LogFormat;
+
+/// Файл конфигурации по умолчанию
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Разделы файла разбираются по одному в [`Config::load_from`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub bot: BotConfig,
+    pub server: ServerConfig,
+    pub api: ApiConfig,
+    pub delays: DelaysConfig,
+    pub games: GamesConfig,
+    pub economy: EconomyConfig,
+    pub bonus: BonusConfig,
+    pub levels: LevelsConfig,
+    pub tournament: TournamentConfig,
+    pub jackpot: JackpotConfig,
+    pub transfer: TransferConfig,
+    pub limits: LimitsConfig,
+    pub admin: AdminConfig,
+    pub storage: StorageConfig,
+    pub logging: LoggingConfig,
+    pub rate_limit: RateLimitConfig,
+    pub ui: UiConfig,
+}
+
+/// Режим получения обновлений от Telegram
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Polling,
+}
+
+/// Как бот показывает раунд в чате
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UiMode {
+    /// Каждый шаг раунда — отдельное сообщение
+    #[default]
+    Messages,
+    /// Меню, выбор и результат редактируются в одном сообщении, отдельно идет только кубик
+    InPlace,
+}
+
+impl UiMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "messages" => Some(UiMode::Messages),
+            "in_place" | "inplace" => Some(UiMode::InPlace),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UiMode::Messages => "messages",
+            UiMode::InPlace => "in_place",
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotConfig {
+    pub token: Option<String>,
+    pub mode: Mode,
+}
+
+// Токен не должен попадать в логи
+impl fmt::Debug for BotConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BotConfig")
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self { port: 5000 }
+    }
+}
+
+/// JSON API для интеграций (`/api/sessions`)
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    /// API выключено по умолчанию: HTTP сервер слушает все интерфейсы
+    pub enabled: bool,
+    /// Если задан, запросы без заголовка `Authorization: Bearer <token>` отклоняются
+    pub token: Option<String>,
+    /// Сколько сессий API может храниться в памяти одновременно
+    pub max_sessions: usize,
+    /// Сессия без запросов дольше этого срока удаляется
+    pub session_ttl_minutes: u64,
+    /// Наибольшая длина имени игрока в символах
+    pub max_player_chars: usize,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            token: None,
+            max_sessions: 1000,
+            session_ttl_minutes: 60,
+            max_player_chars: 64,
+        }
+    }
+}
+
+impl ApiConfig {
+    pub fn session_ttl(&self) -> Duration {
+        Duration::from_secs(self.session_ttl_minutes * 60)
+    }
+}
+
+// Токен не должен попадать в логи
+impl fmt::Debug for ApiConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiConfig")
+            .field("enabled", &self.enabled)
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field("max_sessions", &self.max_sessions)
+            .field("session_ttl_minutes", &self.session_ttl_minutes)
+            .field("max_player_chars", &self.max_player_chars)
+            .finish()
+    }
+}
+
+/// Задержки, в миллисекундах
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DelaysConfig {
+    /// Ожидание окончания анимации кубика перед объявлением результата
+    pub dice_animation_ms: u64,
+    /// Пауза перед предложением сыграть еще раз
+    pub offer_new_game_ms: u64,
+    /// Пауза после запуска HTTP сервера и бота
+    pub startup_ms: u64,
+    /// Сколько ждать идущие раунды при завершении работы
+    pub shutdown_grace_ms: u64,
+}
+
+impl Default for DelaysConfig {
+    fn default() -> Self {
+        Self {
+            dice_animation_ms: 3000,
+            offer_new_game_ms: 2000,
+            startup_ms: 2000,
+            shutdown_grace_ms: 10000,
+        }
+    }
+}
+
+impl DelaysConfig {
+    pub fn dice_animation(&self) -> Duration {
+        Duration::from_millis(self.dice_animation_ms)
+    }
+
+    pub fn offer_new_game(&self) -> Duration {
+        Duration::from_millis(self.offer_new_game_ms)
+    }
+
+    pub fn startup(&self) -> Duration {
+        Duration::from_millis(self.startup_ms)
+    }
+
+    pub fn shutdown_grace(&self) -> Duration {
+        Duration::from_millis(self.shutdown_grace_ms)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamesConfig {
+    pub enabled: Vec<GameType>,
+}
+
+impl Default for GamesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: GameType::ALL.to_vec(),
+        }
+    }
+}
+
+impl GamesConfig {
+    pub fn is_enabled(&self, game: &GameType) -> bool {
+        self.enabled.contains(game)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct EconomyConfig {
+    pub starting_balance: i64,
+    pub stake: i64,
+    pub payouts: Payouts,
+}
+
+impl Default for EconomyConfig {
+    fn default() -> Self {
+        Self {
+            starting_balance: DEFAULT_STARTING_BALANCE,
+            stake: 10,
+            payouts: Payouts::default(),
+        }
+    }
+}
+
+/// Бесплатные фишки: ежедневный бонус, «кран» при низком балансе и сброс при банкротстве
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BonusConfig {
+    /// Бонус за первый день серии
+    pub daily_base: i64,
+    /// Прибавка к бонусу за каждый следующий день серии
+    pub daily_step: i64,
+    /// После скольких дней серии бонус перестает расти
+    pub daily_max_streak: u32,
+    /// Кран доступен, пока баланс ниже этого значения
+    pub faucet_below: i64,
+    pub faucet_amount: i64,
+    pub faucet_cooldown_minutes: u64,
+    /// Баланс после банкротства; 0 — сброс отключен
+    pub bankruptcy_balance: i64,
+    pub bankruptcy_cooldown_hours: u64,
+}
+
+impl Default for BonusConfig {
+    fn default() -> Self {
+        Self {
+            daily_base: 50,
+            daily_step: 10,
+            daily_max_streak: 7,
+            faucet_below: 10,
+            faucet_amount: 30,
+            faucet_cooldown_minutes: 60,
+            bankruptcy_balance: DEFAULT_STARTING_BALANCE,
+            bankruptcy_cooldown_hours: 24,
+        }
+    }
+}
+
+impl BonusConfig {
+    pub fn faucet_cooldown(&self) -> Duration {
+        Duration::from_secs(self.faucet_cooldown_minutes * 60)
+    }
+
+    pub fn bankruptcy_cooldown(&self) -> Duration {
+        Duration::from_secs(self.bankruptcy_cooldown_hours * 3600)
+    }
+}
+
+/// Уровни игроков
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LevelsConfig {
+    /// Открывать игры по уровням; `false` — все включенные игры доступны сразу
+    pub lock_games: bool,
+}
+
+impl Default for LevelsConfig {
+    fn default() -> Self {
+        Self { lock_games: true }
+    }
+}
+
+/// Турниры в чатах
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TournamentConfig {
+    /// Взнос участника; взносы образуют призовой фонд
+    pub entry_fee: i64,
+    /// Длительность регистрации по умолчанию
+    pub registration_minutes: u64,
+    /// Пауза между раундами
+    pub round_minutes: u64,
+    pub max_players: usize,
+    /// Доли фонда по местам в процентах, в сумме 100
+    pub prize_shares: Vec<u32>,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            entry_fee: 10,
+            registration_minutes: 60,
+            round_minutes: 5,
+            max_players: 32,
+            prize_shares: vec![60, 30, 10],
+        }
+    }
+}
+
+impl TournamentConfig {
+    pub fn round_delay(&self) -> Duration {
+        Duration::from_secs(self.round_minutes * 60)
+    }
+}
+
+/// Прогрессивный джекпот
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct JackpotConfig {
+    pub enabled: bool,
+    /// Общий фонд или свой в каждом чате
+    pub scope: Scope,
+    /// Процент проигранной ставки, который уходит в фонд
+    pub share_percent: u32,
+    /// Начальный фонд и фонд после выигрыша
+    pub seed: i64,
+    /// Сколько раз подряд нужно угадать точное число
+    pub exact_streak: u32,
+}
+
+impl Default for JackpotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scope: Scope::Global,
+            share_percent: 10,
+            seed: 100,
+            exact_streak: 3,
+        }
+    }
+}
+
+/// Переводы фишек между игроками
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransferConfig {
+    pub min_amount: i64,
+    pub max_amount: i64,
+    /// Сколько фишек игрок может отправить за сутки (UTC)
+    pub daily_limit: i64,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        Self {
+            min_amount: 1,
+            max_amount: 1000,
+            daily_limit: 2000,
+        }
+    }
+}
+
+/// Ответственная игра: ограничения, которые игрок задает себе командой /limits
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Через сколько часов вступает в силу ослабление ограничения
+    pub cool_off_hours: u64,
+    /// Перерыв между раундами, после которого начинается новая сессия
+    pub session_gap_minutes: u64,
+    /// Наибольший срок самоисключения
+    pub max_exclusion_days: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            cool_off_hours: 24,
+            session_gap_minutes: 30,
+            max_exclusion_days: 365,
+        }
+    }
+}
+
+/// Операторы бота
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// Telegram user id, которым доступны команды оператора
+    pub operators: Vec<u64>,
+}
+
+impl AdminConfig {
+    pub fn is_operator(&self, user_id: u64) -> bool {
+        self.operators.contains(&user_id)
+    }
+}
+
+/// Множители выплат (включая ставку); по умолчанию обратно пропорциональны шансу угадать
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Payouts {
+    pub even_odd: f64,
+    pub high_low: f64,
+    pub exact_number: f64,
+    pub guess_one_yes: f64,
+    pub guess_one_no: f64,
+}
+
+impl Default for Payouts {
+    fn default() -> Self {
+        Self {
+            even_odd: 2.0,
+            high_low: 2.0,
+            exact_number: 6.0,
+            guess_one_yes: 6.0,
+            guess_one_no: 1.2,
+        }
+    }
+}
+
+impl Payouts {
+    fn all(&self) -> [(&'static str, f64); 5] {
+        [
+            ("even_odd", self.even_odd),
+            ("high_low", self.high_low),
+            ("exact_number", self.exact_number),
+            ("guess_one_yes", self.guess_one_yes),
+            ("guess_one_no", self.guess_one_no),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// JSON-файл с пользовательскими данными
+    pub path: PathBuf,
+    /// Необязательный файл шаблонов текстов
+    pub templates: Option<PathBuf>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("data/storage.json"),
+            templates: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// `text` для разработки, `json` для сбора логов в продакшене
+    pub format: LogFormat,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Режим для чатов, где он не выбран командой /ui
+    pub mode: UiMode,
+}
+
+/// Лимиты частоты: входящие действия пользователей и исходящие сообщения бота
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Нажатий и сообщений от одного пользователя в минуту
+    pub user_per_minute: u32,
+    /// Сколько действий пользователь может сделать подряд без пауз
+    pub user_burst: u32,
+    /// Нажатий и сообщений в одном чате в минуту (от всех пользователей)
+    pub chat_per_minute: u32,
+    pub chat_burst: u32,
+    /// Исходящих сообщений в секунду на весь бот (лимит Telegram — 30)
+    pub outgoing_per_second: u32,
+    /// Исходящих сообщений в минуту в личный чат
+    pub outgoing_chat_per_minute: u32,
+    /// Исходящих сообщений в минуту в группу (лимит Telegram — 20)
+    pub outgoing_group_per_minute: u32,
+    /// Сколько сообщений в чат можно отправить подряд без пауз
+    pub outgoing_chat_burst: u32,
+    /// Повторов запроса после ответа 429 `retry_after`
+    pub retry_attempts: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            user_per_minute: 30,
+            user_burst: 5,
+            chat_per_minute: 60,
+            chat_burst: 10,
+            outgoing_per_second: 25,
+            outgoing_chat_per_minute: 60,
+            outgoing_group_per_minute: 20,
+            outgoing_chat_burst: 3,
+            retry_attempts: 3,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn all(&self) -> [(&'static str, u32); 8] {
+        [
+            ("user_per_minute", self.user_per_minute),
+            ("user_burst", self.user_burst),
+            ("chat_per_minute", self.chat_per_minute),
+            ("chat_burst", self.chat_burst),
+            ("outgoing_per_second", self.outgoing_per_second),
+            ("outgoing_chat_per_minute", self.outgoing_chat_per_minute),
+            ("outgoing_group_per_minute", self.outgoing_group_per_minute),
+            ("outgoing_chat_burst", self.outgoing_chat_burst),
+        ]
+    }
+}
+
+/// Все найденные при загрузке конфигурации проблемы
+#[derive(Debug, PartialEq)]
+pub struct ConfigErrors(pub Vec<String>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Ошибки конфигурации:")?;
+        for error in &self.0 {
+            writeln!(f, "  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+impl Config {
+    /// Загрузка из файла и переменных окружения процесса
+    pub fn load() -> Result<Config, ConfigErrors> {
+        let file = match std::env::var("CONFIG_PATH") {
+            Ok(path) => Some(PathBuf::from(path)),
+            Err(_) => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|p| p.exists()),
+        };
+        Self::load_from(file.as_deref(), |name| std::env::var(name).ok())
+    }
+
+    /// Загрузка из указанного файла и источника переменных окружения
+    pub fn load_from<F>(file: Option<&Path>, env: F) -> Result<Config, ConfigErrors>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut errors = Vec::new();
+        let mut config = Config::default();
+        // Поля файла, которые не разобрались: проверка их значений по умолчанию дала бы
+        // ложные ошибки. `None` — не разобрался весь файл
+        let mut failed = Some(Vec::new());
+
+        if let Some(path) = file {
+            match std::fs::read_to_string(path) {
+                Ok(content) => match toml::from_str::<toml::Table>(&content) {
+                    Ok(table) => {
+                        let mut parser = FileParser {
+                            path,
+                            errors: &mut errors,
+                            failed: Vec::new(),
+                        };
+                        config.parse_sections(table, &mut parser);
+                        failed = Some(parser.failed);
+                    }
+                    Err(e) => {
+                        errors.push(format!("{}: {}", path.display(), e));
+                        failed = None;
+                    }
+                },
+                Err(e) => {
+                    errors.push(format!(
+                        "{}: не удалось прочитать файл: {}",
+                        path.display(),
+                        e
+                    ));
+                    failed = None;
+                }
+            }
+        }
+
+        config.apply_env(&env, &mut errors);
+        if let Some(failed) = failed {
+            let mut invalid = Vec::new();
+            config.validate(&mut invalid);
+            invalid.retain(|error| !failed.iter().any(|field| error.starts_with(field.as_str())));
+            errors.extend(invalid);
+        }
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigErrors(errors))
+        }
+    }
+
+    /// Разделы файла по одному: ошибка в одном разделе не мешает разобрать остальные
+    fn parse_sections(&mut self, mut table: toml::Table, parser: &mut FileParser) {
+        parser.section(&mut table, "bot", &mut self.bot);
+        parser.section(&mut table, "server", &mut self.server);
+        parser.section(&mut table, "api", &mut self.api);
+        parser.section(&mut table, "delays", &mut self.delays);
+        parser.section(&mut table, "games", &mut self.games);
+        parser.section(&mut table, "economy", &mut self.economy);
+        parser.section(&mut table, "bonus", &mut self.bonus);
+        parser.section(&mut table, "levels", &mut self.levels);
+        parser.section(&mut table, "tournament", &mut self.tournament);
+        parser.section(&mut table, "jackpot", &mut self.jackpot);
+        parser.section(&mut table, "transfer", &mut self.transfer);
+        parser.section(&mut table, "limits", &mut self.limits);
+        parser.section(&mut table, "admin", &mut self.admin);
+        parser.section(&mut table, "storage", &mut self.storage);
+        parser.section(&mut table, "logging", &mut self.logging);
+        parser.section(&mut table, "rate_limit", &mut self.rate_limit);
+        parser.section(&mut table, "ui", &mut self.ui);
+        for name in table.keys() {
+            parser.fail(name, "неизвестный раздел");
+        }
+    }
+
+    /// Наложение переменных окружения поверх значений из файла
+    fn apply_env<F>(&mut self, env: &F, errors: &mut Vec<String>)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(token) = env("BOT_TOKEN") {
+            self.bot.token = Some(token);
+        }
+        if let Some(mode) = env("MODE") {
+            match mode.trim().to_lowercase().as_str() {
+                "polling" => self.bot.mode = Mode::Polling,
+                _ => errors.push(format!("MODE: '{}' — поддерживается только polling", mode)),
+            }
+        }
+        parse_env(env, "PORT", &mut self.server.port, errors);
+        parse_env(env, "API_ENABLED", &mut self.api.enabled, errors);
+        if let Some(token) = env("API_TOKEN") {
+            self.api.token = Some(token);
+        }
+        parse_env(
+            env,
+            "DICE_ANIMATION_MS",
+            &mut self.delays.dice_animation_ms,
+            errors,
+        );
+        parse_env(
+            env,
+            "OFFER_NEW_GAME_MS",
+            &mut self.delays.offer_new_game_ms,
+            errors,
+        );
+        parse_env(env, "STARTUP_DELAY_MS", &mut self.delays.startup_ms, errors);
+        parse_env(
+            env,
+            "SHUTDOWN_GRACE_MS",
+            &mut self.delays.shutdown_grace_ms,
+            errors,
+        );
+        parse_env(
+            env,
+            "STARTING_BALANCE",
+            &mut self.economy.starting_balance,
+            errors,
+        );
+        parse_env(env, "STAKE", &mut self.economy.stake, errors);
+        if let Some(games) = env("ENABLED_GAMES") {
+            let mut enabled = Vec::new();
+            for name in games.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                match GameType::parse(name) {
+                    Some(game) => enabled.push(game),
+                    None => errors.push(format!("ENABLED_GAMES: неизвестная игра '{}'", name)),
+                }
+            }
+            self.games.enabled = enabled;
+        }
+        if let Some(ids) = env("ADMIN_IDS") {
+            let mut operators = Vec::new();
+            for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                match id.parse() {
+                    Ok(id) => operators.push(id),
+                    Err(_) => errors.push(format!("ADMIN_IDS: некорректный user id '{}'", id)),
+                }
+            }
+            self.admin.operators = operators;
+        }
+        if let Some(path) = env("STORAGE_PATH") {
+            self.storage.path = PathBuf::from(path);
+        }
+        if let Some(path) = env("TEMPLATES_PATH") {
+            self.storage.templates = Some(PathBuf::from(path));
+        }
+        parse_env(
+            env,
+            "RATE_LIMIT_USER_PER_MINUTE",
+            &mut self.rate_limit.user_per_minute,
+            errors,
+        );
+        parse_env(
+            env,
+            "RATE_LIMIT_CHAT_PER_MINUTE",
+            &mut self.rate_limit.chat_per_minute,
+            errors,
+        );
+        if let Some(mode) = env("UI_MODE") {
+            match UiMode::parse(&mode) {
+                Some(mode) => self.ui.mode = mode,
+                None => errors.push(format!(
+                    "UI_MODE: '{}' — допустимы messages или in_place",
+                    mode
+                )),
+            }
+        }
+        if let Some(format) = env("LOG_FORMAT") {
+            match LogFormat::parse(&format) {
+                Some(format) => self.logging.format = format,
+                None => errors.push(format!(
+                    "LOG_FORMAT: '{}' — допустимы text или json",
+                    format
+                )),
+            }
+        }
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        match &self.bot.token {
+            Some(token) if !token.trim().is_empty() => {}
+            _ => errors.push("bot.token (BOT_TOKEN): токен бота не задан".to_string()),
+        }
+        if self.server.port == 0 {
+            errors.push("server.port (PORT): порт должен быть больше 0".to_string());
+        }
+        let api = &self.api;
+        if api
+            .token
+            .as_ref()
+            .is_some_and(|token| token.trim().is_empty())
+        {
+            errors.push("api.token (API_TOKEN): токен не может быть пустым".to_string());
+        }
+        for (name, value) in [
+            ("max_sessions", api.max_sessions as u64),
+            ("session_ttl_minutes", api.session_ttl_minutes),
+            ("max_player_chars", api.max_player_chars as u64),
+        ] {
+            if value == 0 {
+                errors.push(format!("api.{}: значение должно быть больше 0", name));
+            }
+        }
+        if self.games.enabled.is_empty() {
+            errors.push(
+                "games.enabled (ENABLED_GAMES): должна быть включена хотя бы одна игра".to_string(),
+            );
+        }
+        if self.economy.stake <= 0 {
+            errors.push(format!(
+                "economy.stake (STAKE): ставка должна быть положительной, указано {}",
+                self.economy.stake
+            ));
+        }
+        if self.economy.starting_balance < 0 {
+            errors.push(format!(
+                "economy.starting_balance (STARTING_BALANCE): баланс не может быть отрицательным, указано {}",
+                self.economy.starting_balance
+            ));
+        }
+        for (name, multiplier) in self.economy.payouts.all() {
+            if !(multiplier >= 1.0 && multiplier.is_finite()) {
+                errors.push(format!(
+                    "economy.payouts.{}: множитель должен быть не меньше 1.0, указано {}",
+                    name, multiplier
+                ));
+            }
+        }
+        let bonus = &self.bonus;
+        for (name, value) in [
+            ("daily_base", bonus.daily_base),
+            ("daily_step", bonus.daily_step),
+            ("faucet_below", bonus.faucet_below),
+            ("faucet_amount", bonus.faucet_amount),
+            ("bankruptcy_balance", bonus.bankruptcy_balance),
+        ] {
+            if value < 0 {
+                errors.push(format!(
+                    "bonus.{}: значение не может быть отрицательным, указано {}",
+                    name, value
+                ));
+            }
+        }
+        if bonus.daily_max_streak == 0 {
+            errors.push("bonus.daily_max_streak: значение должно быть больше 0".to_string());
+        }
+        let tournament = &self.tournament;
+        if tournament.entry_fee < 0 {
+            errors.push(format!(
+                "tournament.entry_fee: взнос не может быть отрицательным, указано {}",
+                tournament.entry_fee
+            ));
+        }
+        if tournament.max_players < 2 {
+            errors.push("tournament.max_players: нужно хотя бы 2 участника".to_string());
+        }
+        if tournament.prize_shares.iter().sum::<u32>() != 100 {
+            errors.push(format!(
+                "tournament.prize_shares: доли должны давать в сумме 100, указано {:?}",
+                tournament.prize_shares
+            ));
+        }
+        let jackpot = &self.jackpot;
+        if jackpot.share_percent > 100 {
+            errors.push(format!(
+                "jackpot.share_percent: доля не может быть больше 100, указано {}",
+                jackpot.share_percent
+            ));
+        }
+        if jackpot.seed < 0 {
+            errors.push(format!(
+                "jackpot.seed: фонд не может быть отрицательным, указано {}",
+                jackpot.seed
+            ));
+        }
+        if jackpot.exact_streak == 0 {
+            errors.push("jackpot.exact_streak: значение должно быть больше 0".to_string());
+        }
+        let transfer = &self.transfer;
+        if transfer.min_amount <= 0 || transfer.min_amount > transfer.max_amount {
+            errors.push(format!(
+                "transfer: нужно 0 < min_amount <= max_amount, указано {} и {}",
+                transfer.min_amount, transfer.max_amount
+            ));
+        }
+        if transfer.daily_limit < transfer.max_amount {
+            errors.push(format!(
+                "transfer.daily_limit: лимит не может быть меньше max_amount, указано {}",
+                transfer.daily_limit
+            ));
+        }
+        let limits = &self.limits;
+        for (name, value) in [
+            ("cool_off_hours", limits.cool_off_hours),
+            ("session_gap_minutes", limits.session_gap_minutes),
+            ("max_exclusion_days", limits.max_exclusion_days),
+        ] {
+            if value == 0 {
+                errors.push(format!("limits.{}: значение должно быть больше 0", name));
+            }
+        }
+        for (name, value) in self.rate_limit.all() {
+            if value == 0 {
+                errors.push(format!(
+                    "rate_limit.{}: значение должно быть больше 0",
+                    name
+                ));
+            }
+        }
+    }
+}
+
+/// Разбор разделов файла с ошибками по отдельным полям
+struct FileParser<'a> {
+    path: &'a Path,
+    errors: &'a mut Vec<String>,
+    /// Поля и разделы с ошибками (`economy.stake`, `ui`)
+    failed: Vec<String>,
+}
+
+impl FileParser<'_> {
+    /// Раздел `name` в `target`. Если раздел не разобрался целиком, поля проверяются по
+    /// одному: каждое ошибочное поле попадает в список ошибок, остальные применяются
+    fn section<T: DeserializeOwned>(
+        &mut self,
+        table: &mut toml::Table,
+        name: &str,
+        target: &mut T,
+    ) {
+        let Some(value) = table.remove(name) else {
+            return;
+        };
+        let error = match value.clone().try_into() {
+            Ok(parsed) => {
+                *target = parsed;
+                return;
+            }
+            Err(e) => e,
+        };
+        let toml::Value::Table(fields) = value else {
+            self.fail(name, error);
+            return;
+        };
+        let mut valid = toml::Table::new();
+        for (key, value) in fields {
+            let single = toml::Table::from_iter([(key.clone(), value.clone())]);
+            match toml::Value::Table(single).try_into::<T>() {
+                Ok(_) => {
+                    valid.insert(key, value);
+                }
+                Err(e) => self.fail(&format!("{}.{}", name, key), e),
+            }
+        }
+        if let Ok(parsed) = toml::Value::Table(valid).try_into() {
+            *target = parsed;
+        }
+    }
+
+    fn fail(&mut self, field: &str, error: impl fmt::Display) {
+        // Первая строка без приписки «in `поле`»: поле уже названо
+        let error = error.to_string();
+        let error = error.lines().next().unwrap_or_default();
+        self.errors
+            .push(format!("{}: {}: {}", self.path.display(), field, error));
+        self.failed.push(field.to_string());
+    }
+}
+
+fn parse_env<T, F>(env: &F, name: &str, target: &mut T, errors: &mut Vec<String>)
+where
+    T: std::str::FromStr,
+    F: Fn(&str) -> Option<String>,
+{
+    if let Some(value) = env(name) {
+        match value.trim().parse() {
+            Ok(parsed) => *target = parsed,
+            Err(_) => errors.push(format!("{}: некорректное значение '{}'", name, value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_defaults_with_token() {
+        let config = Config::load_from(None, env(&[("BOT_TOKEN", "123:abc")])).unwrap();
+        assert_eq!(config.server.port, 5000);
+        assert_eq!(config.delays.dice_animation(), Duration::from_secs(3));
+        assert_eq!(config.games.enabled, GameType::ALL.to_vec());
+        assert_eq!(config.economy.payouts, Payouts::default());
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+                [bot]
+                token = "from-file"
+
+                [server]
+                port = 8080
+
+                [games]
+                enabled = ["even_odd", "exact_number"]
+
+                [economy.payouts]
+                exact_number = 5.0
+
+                [logging]
+                format = "json"
+            "#,
+        )
+        .unwrap();
+
+        let vars = env(&[
+            ("PORT", "9000"),
+            ("ADMIN_IDS", "42, 7"),
+            ("API_ENABLED", "true"),
+            ("API_TOKEN", "api-secret"),
+        ]);
+        let config = Config::load_from(Some(&path), vars).unwrap();
+        assert_eq!(config.bot.token.as_deref(), Some("from-file"));
+        assert!(config.api.enabled);
+        assert_eq!(config.api.token.as_deref(), Some("api-secret"));
+        assert_eq!(config.server.port, 9000);
+        assert_eq!(
+            config.games.enabled,
+            vec![GameType::EvenOdd, GameType::ExactNumber]
+        );
+        assert_eq!(config.economy.payouts.exact_number, 5.0);
+        assert_eq!(config.economy.payouts.even_odd, 2.0);
+        assert_eq!(config.logging.format, LogFormat::Json);
+        assert!(config.admin.is_operator(7));
+        assert!(!config.admin.is_operator(8));
+    }
+
+    #[test]
+    fn test_all_errors_are_reported() {
+        let errors = Config::load_from(
+            None,
+            env(&[
+                ("PORT", "not-a-port"),
+                ("STAKE", "0"),
+                ("MODE", "carrier-pigeon"),
+                ("ENABLED_GAMES", "even_odd,roulette"),
+                ("LOG_FORMAT", "xml"),
+            ]),
+        )
+        .unwrap_err();
+        assert_eq!(errors.0.len(), 6, "{:#?}", errors.0);
+        assert!(errors.0.iter().any(|e| e.starts_with("PORT:")));
+        assert!(errors.0.iter().any(|e| e.starts_with("MODE:")));
+        assert!(errors.0.iter().any(|e| e.contains("roulette")));
+        assert!(errors.0.iter().any(|e| e.starts_with("economy.stake")));
+        assert!(errors.0.iter().any(|e| e.starts_with("bot.token")));
+        assert!(errors.0.iter().any(|e| e.starts_with("LOG_FORMAT:")));
+    }
+
+    #[test]
+    fn test_field_errors_are_collected_per_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+                [bot]
+                token = "t"
+                mode = "webhook"
+
+                [server]
+                port = "eighty"
+
+                [economy]
+                stake = -5
+                starting_balance = "many"
+
+                [transfer]
+                min_amount = 10
+                max_amount = 5
+
+                [casino]
+                open = true
+            "#,
+        )
+        .unwrap();
+        let errors = Config::load_from(Some(&path), env(&[])).unwrap_err();
+        let has = |prefix: &str| errors.0.iter().any(|e| e.contains(prefix));
+        assert_eq!(errors.0.len(), 6, "{:#?}", errors.0);
+        assert!(has("bot.mode"));
+        assert!(has("server.port"));
+        assert!(has("economy.starting_balance"));
+        assert!(has("casino: неизвестный раздел"));
+        // Разобравшиеся поля проверяются, даже если соседние не разобрались
+        assert!(has("economy.stake (STAKE)"));
+        assert!(has("transfer: нужно"));
+    }
+
+    #[test]
+    fn test_unknown_file_keys_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[server]\nprot = 1\n").unwrap();
+        let errors = Config::load_from(Some(&path), env(&[("BOT_TOKEN", "t")])).unwrap_err();
+        assert_eq!(errors.0.len(), 1);
+        assert!(errors.0[0].contains("prot"));
+    }
+
+    #[test]
+    fn test_token_is_not_logged() {
+        let mut config = Config::default();
+        config.bot.token = Some("secret".to_string());
+        config.api.token = Some("api-secret".to_string());
+        assert!(!format!("{:?}", config).contains("secret"));
+    }
+}
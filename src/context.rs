@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use teloxide::types::{ChatId, User};
+
+use crate::config::{Config, UiMode};
+use crate::health::Health;
+use crate::i18n::{I18n, Lang};
+use crate::metrics::Metrics;
+use crate::ratelimit::{RateLimiter, Throttle};
+use crate::rounds::Rounds;
+use crate::scheduler::Scheduler;
+use crate::settings::Cooldowns;
+use crate::shutdown::Shutdown;
+use crate::state::GameType;
+use crate::storage::Storage;
+use crate::telemetry::ErrorLog;
+
+/// Общие зависимости обработчиков бота
+pub struct AppContext {
+    pub config: Config,
+    pub i18n: I18n,
+    pub storage: Arc<Storage>,
+    pub metrics: Arc<Metrics>,
+    pub health: Arc<Health>,
+    pub shutdown: Arc<Shutdown>,
+    pub scheduler: Scheduler,
+    pub limiter: RateLimiter,
+    pub throttle: Arc<Throttle>,
+    pub rounds: Rounds,
+    /// Последние ошибки для `/admin_errors`
+    pub errors: Arc<ErrorLog>,
+    /// Паузы между раундами из настроек чатов
+    pub cooldowns: Cooldowns,
+}
+
+impl AppContext {
+    pub fn new(
+        config: Config,
+        i18n: I18n,
+        storage: Storage,
+        metrics: Arc<Metrics>,
+        health: Arc<Health>,
+        scheduler: Scheduler,
+        throttle: Arc<Throttle>,
+    ) -> Self {
+        Self {
+            limiter: RateLimiter::new(&config.rate_limit),
+            config,
+            i18n,
+            storage: Arc::new(storage),
+            metrics,
+            health,
+            shutdown: Arc::new(Shutdown::new()),
+            scheduler,
+            throttle,
+            rounds: Rounds::new(),
+            errors: Arc::new(ErrorLog::new()),
+            cooldowns: Cooldowns::new(),
+        }
+    }
+
+    /// Журнал ошибок, в который пишет подписчик `tracing`
+    pub fn with_error_log(mut self, errors: Arc<ErrorLog>) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    /// Доступна ли игра: включена в конфигурации и не выключена оператором
+    pub fn game_enabled(&self, game: &GameType) -> bool {
+        self.config.games.is_enabled(game) && !self.storage.game_disabled(game)
+    }
+
+    /// Доступна ли игра в чате: еще и не выключена администраторами чата
+    pub fn chat_game_enabled(&self, chat_id: ChatId, game: &GameType) -> bool {
+        self.game_enabled(game) && self.storage.chat(chat_id.0).game_enabled(game)
+    }
+
+    /// Режим интерфейса чата: выбранный командой /ui или из конфигурации
+    pub fn ui_mode(&self, chat_id: ChatId) -> UiMode {
+        self.storage
+            .chat(chat_id.0)
+            .ui
+            .unwrap_or(self.config.ui.mode)
+    }
+
+    /// Язык ответа в чате: выбранный в настройках чата или язык пользователя
+    pub fn lang_in(&self, chat_id: ChatId, user: Option<&User>) -> Lang {
+        self.storage
+            .chat(chat_id.0)
+            .lang
+            .unwrap_or_else(|| self.lang_for(user))
+    }
+
+    /// Язык пользователя: сохраненный выбор или `language_code` из Telegram
+    pub fn lang_for(&self, user: Option<&User>) -> Lang {
+        match user {
+            Some(user) => self
+                .storage
+                .user(user.id.0)
+                .lang
+                .unwrap_or_else(|| Lang::from_language_code(user.language_code.as_deref())),
+            None => Lang::from_language_code(None),
+        }
+    }
+}
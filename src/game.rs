@@ -1,6 +1,24 @@
-use crate::state::{EvenOddChoice, HighLowChoice, GuessOneChoice};
+use crate::config::Payouts;
+use crate::limits::LimitError;
+use crate::state::{EvenOddChoice, GameChoice, GuessOneChoice, HighLowChoice};
+use crate::storage::UserProfile;
 use rand::Rng;
 
+/// Начальный баланс нового игрока (в фишках), если не задан в конфигурации
+pub const DEFAULT_STARTING_BALANCE: i64 = 100;
+
+/// Почему ставка не принята
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StakeError {
+    /// Баланс заморожен оператором
+    Frozen,
+    NotEnoughChips {
+        balance: i64,
+    },
+    /// Ограничение, которое игрок задал себе через /limits
+    Limit(LimitError),
+}
+
 /// Структура для управления игровой логикой
 pub struct DiceGame;
 
@@ -11,9 +29,15 @@ impl DiceGame {
         rng.gen_range(1..=6)
     }
 
+    /// Бросок кубика с произвольным количеством граней
+    pub fn roll_die(sides: u8) -> u8 {
+        let mut rng = rand::thread_rng();
+        rng.gen_range(1..=sides)
+    }
+
     /// Проверка результата для игры "Четное/Нечетное"
     pub fn check_even_odd(dice_result: u8, user_choice: EvenOddChoice) -> bool {
-        let is_even = dice_result % 2 == 0;
+        let is_even = dice_result.is_multiple_of(2);
         match user_choice {
             EvenOddChoice::Even => is_even,
             EvenOddChoice::Odd => !is_even,
@@ -42,6 +66,46 @@ impl DiceGame {
         }
     }
 
+    /// Проверка результата для произвольного выбора
+    pub fn check(dice_result: u8, choice: &GameChoice) -> bool {
+        match choice {
+            GameChoice::EvenOdd(c) => Self::check_even_odd(dice_result, c.clone()),
+            GameChoice::HighLow(c) => Self::check_high_low(dice_result, c.clone()),
+            GameChoice::ExactNumber(n) => Self::check_exact_number(dice_result, *n),
+            GameChoice::GuessOne(c) => Self::check_guess_one(dice_result, c.clone()),
+        }
+    }
+
+    /// Выплата за выигрыш (включая ставку) по множителям из конфигурации
+    pub fn payout(choice: &GameChoice, stake: i64, payouts: &Payouts) -> i64 {
+        let multiplier = match choice {
+            GameChoice::EvenOdd(_) => payouts.even_odd,
+            GameChoice::HighLow(_) => payouts.high_low,
+            GameChoice::ExactNumber(_) => payouts.exact_number,
+            GameChoice::GuessOne(GuessOneChoice::Yes) => payouts.guess_one_yes,
+            GameChoice::GuessOne(GuessOneChoice::No) => payouts.guess_one_no,
+        };
+        (stake as f64 * multiplier).round() as i64
+    }
+
+    /// Проверка игрока перед ставкой. Повторяется при расчете раунда под блокировкой
+    /// хранилища: пока кубик катился, могли закончиться другие раунды игрока
+    pub fn check_stake(profile: &UserProfile, stake: i64, now: u64) -> Result<(), StakeError> {
+        if profile.frozen {
+            return Err(StakeError::Frozen);
+        }
+        if profile.balance < stake {
+            return Err(StakeError::NotEnoughChips {
+                balance: profile.balance,
+            });
+        }
+        profile
+            .limits
+            .clone()
+            .check(stake, now)
+            .map_err(StakeError::Limit)
+    }
+
     /// Получение эмодзи кубика по числу
     pub fn dice_emoji(number: u8) -> &'static str {
         match number {
@@ -54,40 +118,12 @@ impl DiceGame {
             _ => "🎲",
         }
     }
-
-    /// Получение сообщения о выигрыше
-    pub fn win_message() -> &'static str {
-        let messages = [
-            "🎉 Поздравляю! Вы угадали!",
-            "🎊 Отлично! Правильный ответ!",
-            "✨ Великолепно! Вы победили!",
-            "🏆 Браво! Точное попадание!",
-            "🎯 Превосходно! Вы угадали!",
-        ];
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..messages.len());
-        messages[index]
-    }
-
-    /// Получение ободряющего сообщения при проигрыше
-    pub fn lose_message() -> &'static str {
-        let messages = [
-            "😔 Не угадали, но не расстраивайтесь!",
-            "🎲 В этот раз не повезло, попробуйте еще!",
-            "💪 Ничего страшного, удача улыбнется в следующий раз!",
-            "🌟 Не переживайте, у вас все получится!",
-            "🎮 Попытка не пытка, играем еще!",
-        ];
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..messages.len());
-        messages[index]
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{EvenOddChoice, HighLowChoice, GuessOneChoice};
+    use crate::state::{EvenOddChoice, GameType, GuessOneChoice, HighLowChoice};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -134,6 +170,48 @@ mod tests {
         assert_eq!(DiceGame::dice_emoji(7), "🎲");
     }
 
+    #[test]
+    fn test_check_dispatches_by_choice() {
+        assert!(DiceGame::check(
+            4,
+            &GameChoice::EvenOdd(EvenOddChoice::Even)
+        ));
+        assert!(!DiceGame::check(
+            4,
+            &GameChoice::HighLow(HighLowChoice::Low)
+        ));
+        assert!(DiceGame::check(6, &GameChoice::ExactNumber(6)));
+        assert!(DiceGame::check(
+            1,
+            &GameChoice::GuessOne(GuessOneChoice::Yes)
+        ));
+    }
+
+    #[test]
+    fn test_payout_by_odds() {
+        let payouts = Payouts::default();
+        let payout = |choice| DiceGame::payout(&choice, 10, &payouts);
+        assert_eq!(payout(GameChoice::EvenOdd(EvenOddChoice::Odd)), 20);
+        assert_eq!(payout(GameChoice::HighLow(HighLowChoice::High)), 20);
+        assert_eq!(payout(GameChoice::ExactNumber(2)), 60);
+        assert_eq!(payout(GameChoice::GuessOne(GuessOneChoice::Yes)), 60);
+        assert_eq!(payout(GameChoice::GuessOne(GuessOneChoice::No)), 12);
+    }
+
+    #[test]
+    fn test_game_choice_parse() {
+        assert_eq!(
+            GameChoice::parse(&GameType::EvenOdd, " Even "),
+            Some(GameChoice::EvenOdd(EvenOddChoice::Even))
+        );
+        assert_eq!(
+            GameChoice::parse(&GameType::ExactNumber, "5"),
+            Some(GameChoice::ExactNumber(5))
+        );
+        assert_eq!(GameChoice::parse(&GameType::ExactNumber, "7"), None);
+        assert_eq!(GameChoice::parse(&GameType::HighLow, "even"), None);
+    }
+
     #[test]
     fn test_roll_dice_range() {
         for _ in 0..1000 {
@@ -0,0 +1,338 @@
+//! Пробы для оркестратора: `GET /livez` и `GET /readyz`.
+//!
+//! - `/livez` — процесс жив и задача диспетчера не завершилась; 503, если бот остановился
+//!   и процесс нужно перезапустить.
+//! - `/readyz` — бот действительно обслуживает пользователей: диспетчер работает, хранилище
+//!   доступно на запись, получение обновлений от Telegram не падает с ошибками и бот
+//!   не находится в процессе завершения работы.
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use dptree::{di::DependencySupplier, HandlerDescription};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use teloxide::{
+    dispatching::{DpHandlerDescription, UpdateHandler},
+    prelude::*,
+    RequestError,
+};
+
+use crate::context::AppContext;
+use crate::metrics;
+
+/// Сколько времени ошибка получения обновлений считается актуальной
+pub const TELEGRAM_ERROR_WINDOW: Duration = Duration::from_secs(120);
+
+/// Состояние задачи диспетчера
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DispatcherStatus {
+    Starting,
+    Running,
+    Stopped,
+}
+
+/// Последняя ошибка Telegram API
+#[derive(Clone, Debug)]
+struct TelegramError {
+    at: Instant,
+    kind: &'static str,
+    message: String,
+    /// Ошибка получения обновлений (а не отдельного запроса из обработчика)
+    listener: bool,
+}
+
+/// Состояние бота, которое собирают диспетчер и обработчики
+pub struct Health {
+    started_at: Instant,
+    dispatcher: Mutex<DispatcherStatus>,
+    last_update: Mutex<Option<Instant>>,
+    last_error: Mutex<Option<TelegramError>>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            dispatcher: Mutex::new(DispatcherStatus::Starting),
+            last_update: Mutex::new(None),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    /// Отметка о запуске диспетчера; при уничтожении guard (в том числе при панике
+    /// задачи) диспетчер считается остановленным
+    pub fn dispatcher_running(self: &Arc<Self>) -> DispatcherGuard {
+        *self.dispatcher.lock().unwrap() = DispatcherStatus::Running;
+        DispatcherGuard(self.clone())
+    }
+
+    pub fn dispatcher_status(&self) -> DispatcherStatus {
+        *self.dispatcher.lock().unwrap()
+    }
+
+    /// Получено обновление от Telegram
+    pub fn update_received(&self) {
+        *self.last_update.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Ошибка запроса к Telegram API
+    pub fn telegram_error(&self, error: &RequestError, listener: bool) {
+        *self.last_error.lock().unwrap() = Some(TelegramError {
+            at: Instant::now(),
+            kind: metrics::error_kind(error),
+            message: error.to_string(),
+            listener,
+        });
+    }
+
+    /// Telegram доступен, если ошибок получения обновлений не было, после последней
+    /// пришло обновление или она старше `TELEGRAM_ERROR_WINDOW`
+    fn telegram_reachable(&self, now: Instant) -> bool {
+        let last_error = self.last_error.lock().unwrap();
+        let Some(error) = last_error.as_ref().filter(|e| e.listener) else {
+            return true;
+        };
+        let recovered = matches!(*self.last_update.lock().unwrap(), Some(at) if at > error.at);
+        recovered || now.duration_since(error.at) > TELEGRAM_ERROR_WINDOW
+    }
+
+    fn liveness(&self) -> Liveness {
+        let dispatcher = self.dispatcher_status();
+        Liveness {
+            status: status(dispatcher != DispatcherStatus::Stopped),
+            dispatcher,
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+        }
+    }
+
+    fn readiness(&self, storage: Result<(), String>, shutting_down: bool) -> Readiness {
+        let now = Instant::now();
+        let dispatcher = self.dispatcher_status();
+        let telegram_ok = self.telegram_reachable(now);
+        let ready = dispatcher == DispatcherStatus::Running
+            && storage.is_ok()
+            && telegram_ok
+            && !shutting_down;
+        let last_error = self.last_error.lock().unwrap().clone();
+        Readiness {
+            status: status(ready),
+            dispatcher,
+            shutting_down,
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+            last_update_seconds_ago: self
+                .last_update
+                .lock()
+                .unwrap()
+                .map(|at| now.duration_since(at).as_secs()),
+            storage: Check {
+                ok: storage.is_ok(),
+                error: storage.err(),
+            },
+            telegram: TelegramCheck {
+                ok: telegram_ok,
+                last_error: last_error.map(|e| LastError {
+                    kind: e.kind,
+                    message: e.message,
+                    seconds_ago: now.duration_since(e.at).as_secs(),
+                    listener: e.listener,
+                }),
+            },
+        }
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Пока guard жив, диспетчер считается работающим
+pub struct DispatcherGuard(Arc<Health>);
+
+impl Drop for DispatcherGuard {
+    fn drop(&mut self) {
+        *self.0.dispatcher.lock().unwrap() = DispatcherStatus::Stopped;
+    }
+}
+
+fn status(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "unavailable"
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Liveness {
+    status: &'static str,
+    dispatcher: DispatcherStatus,
+    uptime_seconds: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    status: &'static str,
+    dispatcher: DispatcherStatus,
+    shutting_down: bool,
+    uptime_seconds: u64,
+    last_update_seconds_ago: Option<u64>,
+    storage: Check,
+    telegram: TelegramCheck,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    ok: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TelegramCheck {
+    ok: bool,
+    last_error: Option<LastError>,
+}
+
+#[derive(Debug, Serialize)]
+struct LastError {
+    kind: &'static str,
+    message: String,
+    seconds_ago: u64,
+    listener: bool,
+}
+
+/// Обертка над схемой обработчиков: отмечает время каждого полученного обновления
+pub fn track(handler: UpdateHandler<RequestError>) -> UpdateHandler<RequestError> {
+    dptree::from_fn_with_description(
+        DpHandlerDescription::entry(),
+        |deps: DependencyMap, cont| async move {
+            let ctx: Arc<AppContext> = deps.get();
+            ctx.health.update_received();
+            cont(deps).await
+        },
+    )
+    .chain(handler)
+}
+
+/// Роутер с эндпоинтами `/livez` и `/readyz`; `/` и `/health` оставлены для
+/// существующих проверок деплоя
+pub fn router(ctx: Arc<AppContext>) -> Router {
+    Router::new()
+        .route("/", get(livez))
+        .route("/health", get(readyz))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .with_state(ctx)
+}
+
+async fn livez(State(ctx): State<Arc<AppContext>>) -> (StatusCode, Json<Liveness>) {
+    let liveness = ctx.health.liveness();
+    (code(liveness.status), Json(liveness))
+}
+
+async fn readyz(State(ctx): State<Arc<AppContext>>) -> (StatusCode, Json<Readiness>) {
+    let storage = ctx.storage.check().map_err(|e| e.to_string());
+    let readiness = ctx.health.readiness(storage, ctx.shutdown.is_draining());
+    (code(readiness.status), Json(readiness))
+}
+
+fn code(status: &str) -> StatusCode {
+    if status == "ok" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::i18n::I18n;
+    use crate::metrics::Metrics;
+    use crate::ratelimit::Throttle;
+    use crate::scheduler::{Scheduler, ThrottledBot};
+    use crate::storage::Storage;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+    use teloxide::error_handlers::LoggingErrorHandler;
+    use tower::ServiceExt;
+
+    fn io_error() -> RequestError {
+        RequestError::Io(std::io::Error::other("connection reset"))
+    }
+
+    async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_listener_error_until_next_update() {
+        let health = Arc::new(Health::new());
+        let _running = health.dispatcher_running();
+        assert_eq!(health.readiness(Ok(()), false).status, "ok");
+
+        // Ошибка отдельного запроса не делает бота неготовым
+        health.telegram_error(&io_error(), false);
+        assert_eq!(health.readiness(Ok(()), false).status, "ok");
+
+        health.telegram_error(&io_error(), true);
+        let readiness = health.readiness(Ok(()), false);
+        assert_eq!(readiness.status, "unavailable");
+        assert_eq!(readiness.telegram.last_error.unwrap().kind, "io");
+
+        health.update_received();
+        assert_eq!(health.readiness(Ok(()), false).status, "ok");
+        assert_eq!(
+            health.readiness(Err("read-only".to_string()), false).status,
+            "unavailable"
+        );
+        assert_eq!(health.readiness(Ok(()), true).status, "unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_probes_follow_dispatcher() {
+        let dir = tempfile::tempdir().unwrap();
+        let health = Arc::new(Health::new());
+        let throttle = Arc::new(Throttle::new(&Config::default().rate_limit));
+        let sender = ThrottledBot {
+            bot: Bot::new("test"),
+            throttle: throttle.clone(),
+        };
+        let ctx = Arc::new(AppContext::new(
+            Config::default(),
+            I18n::new(),
+            Storage::open(dir.path().join("storage.json")).unwrap(),
+            Arc::new(Metrics::new()),
+            health.clone(),
+            Scheduler::start(sender, LoggingErrorHandler::new()),
+            throttle,
+        ));
+        let app = router(ctx);
+
+        let (status, body) = get(&app, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["dispatcher"], "starting");
+        assert_eq!(get(&app, "/livez").await.0, StatusCode::OK);
+
+        let running = health.dispatcher_running();
+        let (status, body) = get(&app, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["storage"]["ok"], true);
+        assert_eq!(body["last_update_seconds_ago"], Value::Null);
+
+        drop(running);
+        let (status, body) = get(&app, "/livez").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["dispatcher"], "stopped");
+    }
+}
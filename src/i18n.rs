@@ -0,0 +1,1888 @@
+//! Локализация: каталоги строк для русского и английского языков.
+//!
+//! Строки адресуются ключами (`"start.welcome"`), подстановки записываются как `{name}`.
+//! Пулы сообщений (например, `"pool.win"`) задаются повторением ключа, формы множественного
+//! числа — суффиксами `.one`/`.few`/`.many` (русский) и `.one`/`.other` (английский).
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::state::{EvenOddChoice, GameChoice, GameType, GuessOneChoice, HighLowChoice};
+
+/// Поддерживаемые языки
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    Ru,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Ru, Lang::En];
+
+    /// Язык по умолчанию по `language_code` из Telegram
+    pub fn from_language_code(code: Option<&str>) -> Lang {
+        match code.map(|c| c.to_lowercase()) {
+            None => Lang::Ru,
+            Some(code) if ["ru", "uk", "be", "kk"].iter().any(|p| code.starts_with(p)) => Lang::Ru,
+            Some(_) => Lang::En,
+        }
+    }
+
+    /// Разбор кода языка из команды `/lang`
+    pub fn parse(code: &str) -> Option<Lang> {
+        match code.trim().to_lowercase().as_str() {
+            "ru" | "рус" | "русский" => Some(Lang::Ru),
+            "en" | "eng" | "english" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::Ru => "ru",
+            Lang::En => "en",
+        }
+    }
+
+    /// Категория множественного числа для `n`
+    pub fn plural_category(self, n: u64) -> &'static str {
+        match self {
+            Lang::Ru => {
+                let (n10, n100) = (n % 10, n % 100);
+                if n10 == 1 && n100 != 11 {
+                    "one"
+                } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                    "few"
+                } else {
+                    "many"
+                }
+            }
+            Lang::En => {
+                if n == 1 {
+                    "one"
+                } else {
+                    "other"
+                }
+            }
+        }
+    }
+}
+
+/// Набор каталогов строк
+pub struct I18n {
+    catalogs: HashMap<Lang, HashMap<String, Vec<String>>>,
+}
+
+impl I18n {
+    /// Встроенные каталоги
+    pub fn new() -> Self {
+        let mut catalogs = HashMap::new();
+        for lang in Lang::ALL {
+            let mut catalog: HashMap<String, Vec<String>> = HashMap::new();
+            for (key, value) in builtin_catalog(lang) {
+                catalog
+                    .entry(key.to_string())
+                    .or_default()
+                    .push(value.to_string());
+            }
+            catalogs.insert(lang, catalog);
+        }
+        Self { catalogs }
+    }
+
+    /// Замена строк (или пула строк) для ключа
+    pub fn set_override(&mut self, lang: Lang, key: &str, values: Vec<String>) {
+        if let Some(catalog) = self.catalogs.get_mut(&lang) {
+            catalog.insert(key.to_string(), values);
+        }
+    }
+
+    fn entries(&self, lang: Lang, key: &str) -> Option<&Vec<String>> {
+        self.catalogs
+            .get(&lang)
+            .and_then(|catalog| catalog.get(key))
+            .or_else(|| self.catalogs.get(&Lang::Ru).and_then(|c| c.get(key)))
+    }
+
+    /// Строка по ключу (для пулов — первая). Отсутствующий ключ возвращается как есть.
+    pub fn text<'a>(&'a self, lang: Lang, key: &'a str) -> &'a str {
+        match self.entries(lang, key) {
+            Some(entries) => &entries[0],
+            None => key,
+        }
+    }
+
+    /// Строка по ключу с подстановкой `{name}`
+    pub fn format(&self, lang: Lang, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        substitute(self.text(lang, key), args)
+    }
+
+    /// Форма множественного числа для `n`; `{n}` подставляется автоматически
+    pub fn plural(&self, lang: Lang, key: &str, n: u64, args: &[(&str, &dyn Display)]) -> String {
+        let key = format!("{}.{}", key, lang.plural_category(n));
+        let mut all: Vec<(&str, &dyn Display)> = vec![("n", &n)];
+        all.extend_from_slice(args);
+        substitute(self.text(lang, &key), &all)
+    }
+
+    /// Случайная строка из пула с подстановкой `{name}`
+    pub fn random(&self, lang: Lang, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        match self.entries(lang, key) {
+            Some(entries) => {
+                let index = rand::thread_rng().gen_range(0..entries.len());
+                substitute(&entries[index], args)
+            }
+            None => key.to_string(),
+        }
+    }
+
+    /// Текст выбора игрока (`четное`, `больше 3.5 (4-6)`, `4`)
+    pub fn choice_text(&self, lang: Lang, choice: &GameChoice) -> String {
+        let key = match choice {
+            GameChoice::EvenOdd(EvenOddChoice::Even) => "choice.even",
+            GameChoice::EvenOdd(EvenOddChoice::Odd) => "choice.odd",
+            GameChoice::HighLow(HighLowChoice::High) => "choice.high",
+            GameChoice::HighLow(HighLowChoice::Low) => "choice.low",
+            GameChoice::GuessOne(GuessOneChoice::Yes) => "choice.yes",
+            GameChoice::GuessOne(GuessOneChoice::No) => "choice.no",
+            GameChoice::ExactNumber(n) => return n.to_string(),
+        };
+        self.text(lang, key).to_string()
+    }
+
+    /// Описание выпавшего результата в терминах игры (`четное`, `выпала единица`)
+    pub fn outcome_text(&self, lang: Lang, game: &GameType, dice_result: u8) -> String {
+        let key = match game {
+            GameType::EvenOdd if dice_result.is_multiple_of(2) => "outcome.even",
+            GameType::EvenOdd => "outcome.odd",
+            GameType::HighLow if dice_result >= 4 => "outcome.high",
+            GameType::HighLow => "outcome.low",
+            GameType::GuessOne if dice_result == 1 => "outcome.one",
+            GameType::GuessOne => "outcome.not_one",
+            GameType::ExactNumber => return dice_result.to_string(),
+        };
+        self.text(lang, key).to_string()
+    }
+}
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Подстановка значений в шаблон `{name}`
+pub fn substitute(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = template.to_string();
+    for (name, value) in args {
+        result = result.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    result
+}
+
+/// Семейства ключей с формами множественного числа для [`I18n::plural`]
+const PLURALS: &[&str] = &["inline.dice.", "bonus.streak.", "jackpot.streak."];
+
+/// Подстановки, которые код передает в строку с данным ключом
+pub fn placeholders(key: &str) -> &'static [&'static str] {
+    const RESULT: &[&str] = &["dice", "choice", "outcome", "verdict", "balance", "stake"];
+    if key.starts_with("pool.") {
+        return &["dice", "choice", "outcome", "balance"];
+    }
+    if key.ends_with(".win") || key.ends_with(".lose") {
+        return RESULT;
+    }
+    if key.ends_with(".chosen") {
+        return &["choice", "stake"];
+    }
+    if PLURALS.iter().any(|family| key.starts_with(family)) {
+        return &["n"];
+    }
+    match key {
+        "start.welcome" => &["balance", "stake"],
+        "help.body" => &["stake"],
+        "exact.title" | "inline.bet.description" => &["choice"],
+        "round.no_chips" => &["stake", "balance"],
+        "round.failed" => &["dice"],
+        "rate.cooldown" | "settings.cooldown_value" | "settings.wait" => &["seconds"],
+        "ui.changed" => &["mode"],
+        "again.repeat" => &["choice"],
+        "bonus.daily" => &["amount", "streak", "balance"],
+        "bonus.faucet" => &["amount", "balance"],
+        "bonus.bankrupt" => &["balance"],
+        "bonus.faucet_rich" | "bonus.bankrupt_rich" => &["limit"],
+        "achievement.unlocked" => &["badge", "title"],
+        "level.locked_game" | "level.up" | "skin.locked" => &["level"],
+        "level.unlock_game" => &["game"],
+        "stats.game" => &["game", "rounds", "wins"],
+        "level.unlock_skin" | "skin.changed" => &["skin"],
+        "level.unlock_bet" | "bet.changed" => &["stake"],
+        "stats.body" => &[
+            "level",
+            "xp",
+            "next",
+            "balance",
+            "stake",
+            "max_stake",
+            "rounds",
+            "wins",
+            "win_rate",
+        ],
+        "stats.next" => &["level", "xp"],
+        "bet.current" => &["stake", "min", "max"],
+        "achievement.line" => &["badge", "title", "description"],
+        "achievement.locked" => &["title", "description", "progress", "threshold"],
+        "achievements.header" => &["count", "total"],
+        "bonus.daily_wait" | "bonus.faucet_wait" | "bonus.bankrupt_wait" | "bonus.welcome_wait" => {
+            &["hours", "minutes"]
+        }
+        "tournament.created" => &["format", "fee", "max", "minutes"],
+        "tournament.joined" => &["name", "count", "pool"],
+        "tournament.registration" => &["format", "fee", "pool", "minutes", "count"],
+        "tournament.header" => &["format", "round", "rounds"],
+        "tournament.round" => &["round"],
+        "tournament.match_pending" => &["home", "away"],
+        "tournament.match_played" => &["home", "away", "home_roll", "away_roll", "winner"],
+        "tournament.bye" | "tournament.finished" => &["name"],
+        "tournament.standing" => &["place", "name", "wins"],
+        "tournament.prize" => &["name", "amount"],
+        "tournament.next" => &["minutes"],
+        "tournament.error.no_chips" => &["fee"],
+        "jackpot.menu" => &["amount", "streak"],
+        "jackpot.status" => &["amount", "streak", "share"],
+        "jackpot.won" => &["amount"],
+        "give.unknown" => &["name"],
+        "give.confirm" => &["amount", "name"],
+        "give.done" => &["amount", "balance"],
+        "give.error.amount" | "bet.invalid" | "settings.bet_range" => &["min", "max"],
+        "give.error.no_chips" => &["balance"],
+        "give.error.limit" => &["left"],
+        "settings.show" => &["games", "bet", "lang", "ui", "cooldown"],
+        "limits.show" => &["loss", "lost", "reminder", "exclusion"],
+        "limits.loss_value" => &["amount"],
+        "limits.reminder_value" | "limits.reminder" => &["minutes"],
+        "limits.excluded_value" | "limits.excluded" | "limits.error.excluded" => &["until"],
+        "limits.pending_loss" | "limits.pending_reminder" => &["value", "at"],
+        "limits.usage" => &["days", "hours"],
+        "limits.scheduled" => &["at"],
+        "limits.error.loss" => &["limit", "left"],
+        "history.line" => &["time", "kind", "amount", "balance"],
+        "admin.stats" => &[
+            "users",
+            "players",
+            "rounds",
+            "balance",
+            "jackpots",
+            "chats",
+            "tournaments",
+            "banned",
+            "frozen",
+        ],
+        "admin.unknown_user" => &["name"],
+        "admin.balance" => &["user", "amount", "balance"],
+        "admin.balance_negative" | "admin.balance_overflow" => &["user", "balance"],
+        "admin.frozen" | "admin.unfrozen" | "admin.banned" | "admin.unbanned" => &["user"],
+        "admin.broadcast_started" => &["count"],
+        "admin.broadcast_done" => &["sent", "failed"],
+        "admin.broadcast_aborted" => &["sent", "failed", "left"],
+        "admin.game_line" | "admin.game_changed" => &["name", "state"],
+        "admin.error_line" => &["time", "level", "message"],
+        "inline.roll.title" => &["dice", "sides"],
+        "inline.bet.text" => &["choice", "dice_emoji", "dice", "verdict"],
+        _ => &[],
+    }
+}
+
+/// Имена подстановок `{name}`, встречающихся в шаблоне
+pub fn template_placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        if let Some(end) = rest.find('}') {
+            let name = &rest[..end];
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+                names.push(name);
+                rest = &rest[end + 1..];
+            }
+        }
+    }
+    names
+}
+
+/// Все ключи встроенных каталогов
+pub fn builtin_keys() -> impl Iterator<Item = &'static str> {
+    Lang::ALL
+        .into_iter()
+        .flat_map(|lang| builtin_catalog(lang).iter().map(|(key, _)| *key))
+}
+
+fn builtin_catalog(lang: Lang) -> &'static [(&'static str, &'static str)] {
+    match lang {
+        Lang::Ru => RU,
+        Lang::En => EN,
+    }
+}
+
+const RU: &[(&str, &str)] = &[
+    (
+        "start.welcome",
+        "🎲 Привет! Добро пожаловать в игру с кубиками!\n\n\
+         Я предлагаю вам угадать результат броска кубика.\n\
+         Доступны четыре варианта игры:\n\n\
+         🔵 Четное/Нечетное - угадайте четность числа\n\
+         🔴 Больше/Меньше 3.5 - угадайте диапазон\n\
+         🎯 Точное число - угадайте конкретное число\n\
+         🎱 Угадать единицу - выпадет ли единица\n\n\
+         💰 Ваш баланс: {balance} 🪙, ставка за раунд: {stake} 🪙\n\n\
+         Используйте /play чтобы начать игру!",
+    ),
+    (
+        "help.body",
+        "🎮 <b>Помощь по игре в кубики</b>\n\n\
+         <b>Доступные команды:</b>\n\
+         /start - начать работу с ботом\n\
+         /play - начать новую игру\n\
+         /lang - сменить язык\n\
+         /ui - режим интерфейса в чате\n\
+         /daily - ежедневный бонус\n\
+         /faucet - фишки при низком балансе\n\
+         /bankrupt - сброс баланса при банкротстве\n\
+         /achievements - ваши достижения\n\
+         /stats - уровень и статистика\n\
+         /bet - размер ставки\n\
+         /skin - скин броска\n\
+         /tournament - турнир в чате\n\
+         /jackpot - текущий джекпот\n\
+         /give - передать фишки\n\
+         /history - история операций\n\
+         /settings - настройки чата (для администраторов)\n\
+         /limits - лимиты проигрыша, напоминания и самоисключение\n\
+         /export - выгрузка ваших данных (json или csv)\n\
+         /forget_me - удалить ваши данные\n\
+         /help - показать эту справку\n\n\
+         <b>Варианты игры:</b>\n\n\
+         🔵 <b>Четное/Нечетное</b>\n\
+         Угадайте, будет ли результат четным или нечетным числом\n\n\
+         🔴 <b>Больше/Меньше 3.5</b>\n\
+         Угадайте, будет ли результат больше 3.5 (4-6) или меньше 3.5 (1-3)\n\n\
+         🎯 <b>Точное число</b>\n\
+         Угадайте конкретное число от 1 до 6\n\n\
+         ⚀ <b>Угадать единицу</b>\n\
+         Угадайте, выпадет ли на кубике единица\n\n\
+         <b>Ставки:</b> {stake} 🪙 за раунд. Выигрыш x2 в играх Четное/Нечетное и \
+         Больше/Меньше, x6 за точное число и за выпавшую единицу, x1.2 если единица не выпала",
+    ),
+    ("menu.prompt", "🎲 Выберите вариант игры:"),
+    ("menu.even_odd", "🔵 Четное/Нечетное"),
+    ("menu.high_low", "🔴 Больше/Меньше 3.5"),
+    ("menu.exact", "🎯 Точное число"),
+    ("menu.guess_one", "⚀ Угадать единицу"),
+    (
+        "even_odd.intro",
+        "🔵 <b>Игра: Четное/Нечетное</b>\n\n\
+         Выберите, будет ли результат броска четным или нечетным числом:",
+    ),
+    ("even_odd.even", "🔵 Четное"),
+    ("even_odd.odd", "🔴 Нечетное"),
+    (
+        "high_low.intro",
+        "🔴 <b>Игра: Больше/Меньше 3.5</b>\n\n\
+         Выберите, будет ли результат больше или меньше 3.5:",
+    ),
+    ("high_low.high", "⬆️ Больше 3.5 (4-6)"),
+    ("high_low.low", "⬇️ Меньше 3.5 (1-3)"),
+    (
+        "exact.intro",
+        "🎯 <b>Игра: Точное число</b>\n\n\
+         Выберите число от 1 до 6, которое выпадет на кубике:",
+    ),
+    ("exact.title", "🎯 Число {choice}"),
+    (
+        "guess_one.intro",
+        "⚀ <b>Игра: Угадать единицу</b>\n\n\
+         Выпадет ли на кубике единица?",
+    ),
+    ("guess_one.yes", "✅ Да, выпадет единица"),
+    ("guess_one.no", "❌ Нет, не выпадет"),
+    ("choice.even", "четное"),
+    ("choice.odd", "нечетное"),
+    ("choice.high", "больше 3.5 (4-6)"),
+    ("choice.low", "меньше 3.5 (1-3)"),
+    ("choice.yes", "да, выпадет единица"),
+    ("choice.no", "нет, не выпадет единица"),
+    ("outcome.even", "четное"),
+    ("outcome.odd", "нечетное"),
+    ("outcome.high", "больше 3.5"),
+    ("outcome.low", "меньше 3.5"),
+    ("outcome.one", "выпала единица"),
+    ("outcome.not_one", "единица не выпала"),
+    (
+        "even_odd.chosen",
+        "🎯 Вы выбрали: {choice}\n🎲 Бросаю кубик...",
+    ),
+    (
+        "high_low.chosen",
+        "📊 Вы выбрали: {choice}\n🎲 Бросаю кубик...",
+    ),
+    (
+        "exact.chosen",
+        "🎯 Вы выбрали число: {choice}\n🎲 Бросаю кубик...",
+    ),
+    (
+        "guess_one.chosen",
+        "⚀ Вы выбрали: {choice}\n🎲 Бросаю кубик...",
+    ),
+    (
+        "even_odd.win",
+        "🎉 Число {dice}: {outcome}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "even_odd.lose",
+        "😔 Число {dice}: {outcome}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "high_low.win",
+        "🎉 Число {dice} - {outcome}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "high_low.lose",
+        "😔 Число {dice} - {outcome}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "exact.win",
+        "🎉 Выпало число: {dice}\nВы угадали!\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "exact.lose",
+        "😔 Выпало число: {dice}\nВы выбрали: {choice}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "guess_one.win",
+        "🎉 Число {dice}: {outcome}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "guess_one.lose",
+        "😔 Число {dice}: {outcome}\n\n{verdict}\n💰 Баланс: {balance} 🪙",
+    ),
+    ("pool.win", "🎉 Поздравляю! Вы угадали!"),
+    ("pool.win", "🎊 Отлично! Правильный ответ!"),
+    ("pool.win", "✨ Великолепно! Вы победили!"),
+    ("pool.win", "🏆 Браво! Точное попадание!"),
+    ("pool.win", "🎯 Превосходно! Вы угадали!"),
+    ("pool.lose", "😔 Не угадали, но не расстраивайтесь!"),
+    ("pool.lose", "🎲 В этот раз не повезло, попробуйте еще!"),
+    (
+        "pool.lose",
+        "💪 Ничего страшного, удача улыбнется в следующий раз!",
+    ),
+    ("pool.lose", "🌟 Не переживайте, у вас все получится!"),
+    ("pool.lose", "🎮 Попытка не пытка, играем еще!"),
+    (
+        "game.disabled",
+        "🚫 Эта игра сейчас отключена. Выберите другую: /play",
+    ),
+    (
+        "round.no_chips",
+        "😕 Недостаточно фишек: ставка {stake} 🪙, ваш баланс {balance} 🪙\n\
+         Бесплатные фишки: /daily, /faucet или /bankrupt",
+    ),
+    ("round.void", "↩️ Бросок не засчитан, ставка не списана."),
+    (
+        "round.failed",
+        "⚠️ Выпало {dice}, но результат раунда не удалось сохранить. Попробуйте позже",
+    ),
+    (
+        "bonus.daily",
+        "🎁 Ежедневный бонус: +{amount} 🪙 (серия {streak})\n💰 Баланс: {balance} 🪙",
+    ),
+    ("bonus.streak.one", "{n} день"),
+    ("bonus.streak.few", "{n} дня"),
+    ("bonus.streak.many", "{n} дней"),
+    (
+        "bonus.daily_wait",
+        "⏳ Бонус за сегодня уже получен. Следующий через {hours} ч {minutes} мин",
+    ),
+    (
+        "bonus.faucet",
+        "🚰 Кран: +{amount} 🪙\n💰 Баланс: {balance} 🪙",
+    ),
+    (
+        "bonus.faucet_rich",
+        "🚰 Кран доступен, пока баланс меньше {limit} 🪙",
+    ),
+    (
+        "bonus.faucet_wait",
+        "⏳ Кран снова будет доступен через {hours} ч {minutes} мин",
+    ),
+    (
+        "bonus.bankrupt",
+        "♻️ Баланс восстановлен: {balance} 🪙. Удачи!",
+    ),
+    (
+        "bonus.bankrupt_rich",
+        "💰 Сброс доступен, только если баланс меньше ставки ({limit} 🪙)",
+    ),
+    (
+        "bonus.bankrupt_wait",
+        "⏳ Сброс снова будет доступен через {hours} ч {minutes} мин",
+    ),
+    ("bonus.disabled", "🚫 Эта функция отключена"),
+    (
+        "bonus.welcome_ready",
+        "🎁 Ежедневный бонус ждет вас: /daily",
+    ),
+    (
+        "bonus.welcome_wait",
+        "🎁 Следующий ежедневный бонус через {hours} ч {minutes} мин",
+    ),
+    (
+        "bonus.welcome_broke",
+        "🆘 Фишки закончились? Используйте /faucet или /bankrupt",
+    ),
+    (
+        "achievement.unlocked",
+        "🏆 Новое достижение: {badge} {title}",
+    ),
+    (
+        "level.locked_game",
+        "🔒 Эта игра откроется на уровне {level}",
+    ),
+    ("level.up", "⭐ Новый уровень: {level}!"),
+    ("level.unlock_game", "🔓 Открыта игра: {game}"),
+    ("level.unlock_skin", "🎨 Новый скин броска: {skin} (/skin)"),
+    (
+        "level.unlock_bet",
+        "💰 Максимальная ставка: {stake} 🪙 (/bet)",
+    ),
+    (
+        "stats.body",
+        "📊 <b>Статистика</b>\n\n\
+         ⭐ Уровень {level}: {xp} XP{next}\n\
+         💰 Баланс: {balance} 🪙\n\
+         🎯 Ставка: {stake} 🪙 (максимум {max_stake} 🪙)\n\
+         🎲 Раундов: {rounds}, побед: {wins} ({win_rate}%)",
+    ),
+    ("stats.next", ", до уровня {level} — {xp} XP"),
+    ("stats.game", "{game}: раундов {rounds}, побед {wins}"),
+    (
+        "bet.current",
+        "🎯 Ваша ставка: {stake} 🪙. Можно от {min} до {max} 🪙: /bet <сумма>",
+    ),
+    ("bet.changed", "✅ Ставка: {stake} 🪙"),
+    ("bet.invalid", "🤔 Ставка должна быть от {min} до {max} 🪙"),
+    ("skin.prompt", "🎨 Выберите скин броска:"),
+    ("skin.dice", "🎲 Кубик"),
+    ("skin.darts", "🎯 Дартс"),
+    ("skin.bowling", "🎳 Боулинг"),
+    ("skin.changed", "✅ Скин броска: {skin}"),
+    ("skin.locked", "🔒 Этот скин откроется на уровне {level}"),
+    (
+        "jackpot.menu",
+        "🎰 Джекпот: {amount} 🪙 — угадайте точное число {streak}",
+    ),
+    ("jackpot.streak.one", "{n} раз подряд"),
+    ("jackpot.streak.few", "{n} раза подряд"),
+    ("jackpot.streak.many", "{n} раз подряд"),
+    (
+        "jackpot.status",
+        "🎰 <b>Джекпот: {amount} 🪙</b>\n\n\
+         {share}% каждой проигранной ставки идет в фонд. Весь фонд забирает тот, кто \
+         угадает точное число {streak}.",
+    ),
+    ("jackpot.won", "🎰🎰🎰 ДЖЕКПОТ! +{amount} 🪙"),
+    ("jackpot.disabled", "Джекпот отключен"),
+    (
+        "give.usage",
+        "Использование: /give @username сумма или /give сумма в ответ на сообщение игрока",
+    ),
+    (
+        "give.unknown",
+        "🤔 Не знаю игрока {name}: он еще не играл с ботом",
+    ),
+    ("give.confirm", "💸 Передать {amount} 🪙 игроку {name}?"),
+    ("give.yes", "✅ Передать"),
+    ("give.no", "❌ Отмена"),
+    (
+        "give.done",
+        "✅ Передано {amount} 🪙. Ваш баланс: {balance} 🪙",
+    ),
+    (
+        "give.failed",
+        "⚠️ Не удалось выполнить перевод, попробуйте позже",
+    ),
+    ("give.cancelled", "Перевод отменен"),
+    (
+        "give.not_yours",
+        "Подтвердить перевод может только отправитель",
+    ),
+    ("give.expired", "Этот перевод уже подтвержден или устарел"),
+    (
+        "give.error.amount",
+        "🤔 Сумма перевода должна быть от {min} до {max} 🪙",
+    ),
+    ("give.error.self", "🤔 Нельзя передать фишки самому себе"),
+    (
+        "give.error.no_chips",
+        "💸 Не хватает фишек: на балансе {balance} 🪙",
+    ),
+    (
+        "give.error.limit",
+        "⏳ Суточный лимит переводов: сегодня можно отправить еще {left} 🪙",
+    ),
+    (
+        "history.header",
+        "📒 <b>Последние операции</b> (время UTC):",
+    ),
+    ("history.empty", "📒 Операций пока нет"),
+    ("history.line", "{time} {kind}: {amount} 🪙 → {balance} 🪙"),
+    ("ledger.opening", "начальный баланс"),
+    ("ledger.bet", "ставка"),
+    ("ledger.payout", "выигрыш"),
+    ("ledger.jackpot", "джекпот"),
+    ("ledger.daily", "ежедневный бонус"),
+    ("ledger.faucet", "кран"),
+    ("ledger.bankruptcy", "банкротство"),
+    ("ledger.tournament_fee", "взнос за турнир"),
+    ("ledger.tournament_refund", "возврат взноса"),
+    ("ledger.tournament_prize", "приз турнира"),
+    ("ledger.transfer_in", "перевод от игрока"),
+    ("ledger.transfer_out", "перевод игроку"),
+    ("ledger.admin", "изменение оператором"),
+    ("ledger.adjustment", "корректировка"),
+    (
+        "account.frozen",
+        "🧊 Ваш баланс заморожен оператором: игры, бонусы и переводы недоступны",
+    ),
+    ("account.banned", "🚫 Доступ к боту заблокирован"),
+    (
+        "give.error.frozen",
+        "🧊 Перевод невозможен: баланс отправителя или получателя заморожен",
+    ),
+    (
+        "tournament.error.frozen",
+        "🧊 Ваш баланс заморожен: участие в турнире недоступно",
+    ),
+    (
+        "admin.help",
+        "🛠 <b>Команды оператора</b>\n\
+         /admin_stats — сводка по боту\n\
+         /admin_balance &lt;кто&gt; &lt;±сумма&gt; — изменить баланс\n\
+         /admin_freeze, /admin_unfreeze &lt;кто&gt; — заморозить баланс\n\
+         /admin_ban, /admin_unban &lt;кто&gt; — заблокировать в боте\n\
+         /admin_broadcast &lt;текст&gt; — объявление во все чаты\n\
+         /admin_game [игра on|off] — включить или выключить игру\n\
+         /admin_errors — последние ошибки\n\n\
+         Кто — id, @username или ответ на сообщение пользователя",
+    ),
+    (
+        "admin.stats",
+        "📊 <b>Сводка</b>\n\
+         Пользователей: {users} (играли: {players})\n\
+         Раундов: {rounds}\n\
+         Фишек на балансах: {balance} 🪙\n\
+         Фонды джекпота: {jackpots} 🪙\n\
+         Чатов: {chats}, турниров идет: {tournaments}\n\
+         Заблокировано: {banned}, заморожено: {frozen}",
+    ),
+    ("admin.unknown_user", "🤔 Пользователь {name} не найден"),
+    (
+        "admin.balance",
+        "✅ Баланс {user} изменен на {amount} 🪙: теперь {balance} 🪙",
+    ),
+    (
+        "admin.balance_negative",
+        "🤔 Нельзя списать больше баланса: у {user} {balance} 🪙",
+    ),
+    (
+        "admin.balance_overflow",
+        "🤔 Слишком большая сумма: у {user} {balance} 🪙",
+    ),
+    ("admin.frozen", "🧊 Баланс {user} заморожен"),
+    ("admin.unfrozen", "✅ Баланс {user} разморожен"),
+    ("admin.banned", "🚫 {user} заблокирован"),
+    ("admin.unbanned", "✅ {user} разблокирован"),
+    ("admin.failed", "⚠️ Не удалось сохранить изменение"),
+    (
+        "admin.broadcast_started",
+        "📢 Объявление отправляется, получателей: {count}",
+    ),
+    (
+        "admin.broadcast_done",
+        "📢 Объявление доставлено: {sent}, не доставлено: {failed}",
+    ),
+    (
+        "admin.broadcast_aborted",
+        "🔧 Рассылка прервана перезапуском бота. Доставлено: {sent}, не доставлено: {failed}, \
+         не отправлено: {left}",
+    ),
+    (
+        "admin.broadcast_refused",
+        "🔧 Бот перезапускается, объявление не отправлено",
+    ),
+    ("admin.games", "🎲 <b>Игры</b> (/admin_game игра on|off):"),
+    ("admin.game_line", "<code>{name}</code> — {state}"),
+    ("admin.game_changed", "✅ Игра <code>{name}</code> {state}"),
+    ("admin.game_on", "включена"),
+    ("admin.game_off", "выключена"),
+    ("admin.game_config_off", "выключена в конфигурации"),
+    (
+        "admin.game_unknown",
+        "🤔 Неизвестная игра. Доступны: even_odd, high_low, exact_number, guess_one",
+    ),
+    ("admin.errors", "⚠️ <b>Последние ошибки</b> (время UTC):"),
+    ("admin.errors_empty", "✅ Ошибок нет"),
+    ("admin.error_line", "{time} {level} {message}"),
+    ("tournament.format.knockout", "олимпийская система"),
+    ("tournament.format.round_robin", "круговая система"),
+    (
+        "tournament.created",
+        "🏆 Открыта регистрация на турнир ({format})!\n\
+         Взнос: {fee} 🪙, мест: {max}. Старт через {minutes} мин.\n\
+         Участвовать: /tournament join",
+    ),
+    (
+        "tournament.joined",
+        "✅ {name} в турнире! Участников: {count}, призовой фонд: {pool} 🪙",
+    ),
+    (
+        "tournament.registration",
+        "🏆 <b>Турнир ({format})</b>: идет регистрация\n\
+         Взнос: {fee} 🪙, призовой фонд: {pool} 🪙\n\
+         Старт через {minutes} мин. Участвовать: /tournament join\n\n\
+         Участники ({count}):",
+    ),
+    (
+        "tournament.header",
+        "🏆 <b>Турнир ({format})</b>, раунд {round} из {rounds}",
+    ),
+    ("tournament.round", "<b>Раунд {round}</b>"),
+    ("tournament.match_pending", "{home} — {away}"),
+    (
+        "tournament.match_played",
+        "{home} {home_roll}:{away_roll} {away} → {winner}",
+    ),
+    ("tournament.bye", "{name} проходит без соперника"),
+    ("tournament.standings", "<b>Таблица</b>"),
+    ("tournament.standing", "{place}. {name} — побед: {wins}"),
+    ("tournament.next", "⏱ Следующий раунд через {minutes} мин."),
+    (
+        "tournament.finished",
+        "🏁 <b>Турнир завершен!</b> Победитель: {name}",
+    ),
+    ("tournament.prize", "🎁 {name}: +{amount} 🪙"),
+    (
+        "tournament.cancelled",
+        "❌ Турнир отменен: нужно хотя бы два участника. Взносы возвращены.",
+    ),
+    (
+        "tournament.none",
+        "В этом чате нет турнира. Создать: /tournament create [knockout|round_robin] [минуты]",
+    ),
+    (
+        "tournament.usage",
+        "Команды турнира: /tournament create [knockout|round_robin] [минуты], \
+         /tournament join, /tournament start",
+    ),
+    (
+        "tournament.error.exists",
+        "🏆 В чате уже идет турнир: /tournament",
+    ),
+    (
+        "tournament.error.not_found",
+        "В этом чате нет открытого турнира",
+    ),
+    ("tournament.error.closed", "Регистрация уже закрыта"),
+    ("tournament.error.joined", "Вы уже участвуете в турнире"),
+    ("tournament.error.full", "Все места в турнире заняты"),
+    (
+        "tournament.error.no_chips",
+        "💸 Не хватает фишек на взнос {fee} 🪙",
+    ),
+    (
+        "tournament.error.not_enough",
+        "Для старта нужно хотя бы два участника",
+    ),
+    (
+        "tournament.error.not_allowed",
+        "Это может сделать создатель турнира или администратор чата",
+    ),
+    (
+        "achievements.header",
+        "🏆 <b>Достижения</b> ({count} из {total})",
+    ),
+    ("achievement.line", "{badge} <b>{title}</b> — {description}"),
+    (
+        "achievement.locked",
+        "🔒 {title} — {description} ({progress}/{threshold})",
+    ),
+    ("achievement.first_win.title", "Первая победа"),
+    ("achievement.first_win.description", "выиграйте любой раунд"),
+    ("achievement.regular.title", "Завсегдатай"),
+    ("achievement.regular.description", "сыграйте 100 раундов"),
+    ("achievement.sniper.title", "Снайпер"),
+    (
+        "achievement.sniper.description",
+        "угадайте точное число 5 раз",
+    ),
+    ("achievement.even_odd_streak.title", "В ударе"),
+    (
+        "achievement.even_odd_streak.description",
+        "10 побед подряд в «Четное/Нечетное»",
+    ),
+    ("achievement.high_low_master.title", "Чувство меры"),
+    (
+        "achievement.high_low_master.description",
+        "25 побед в «Больше/Меньше»",
+    ),
+    ("achievement.snake_eyes.title", "Змеиный глаз"),
+    (
+        "achievement.snake_eyes.description",
+        "выбросите единицу 6 раз в «Угадать единицу»",
+    ),
+    ("round.again", "🎮 Хотите сыграть еще раз?"),
+    ("again.repeat", "🔁 Еще раз: {choice}"),
+    ("again.other", "🔀 Другой выбор"),
+    ("again.menu", "🎮 Сменить игру"),
+    (
+        "round.shutting_down",
+        "🔧 Бот перезапускается, новые раунды временно не принимаются. Попробуйте через минуту!",
+    ),
+    (
+        "rate.cooldown",
+        "⏳ Не так быстро! Попробуйте снова через {seconds} с.",
+    ),
+    ("round.already_played", "🎲 Этот раунд уже сыгран!"),
+    (
+        "callback.outdated",
+        "⌛ Эта кнопка устарела. Начните новую игру: /play",
+    ),
+    (
+        "callback.invalid",
+        "🤔 Неизвестная кнопка. Начните новую игру: /play",
+    ),
+    (
+        "round.expired",
+        "⌛ Эта игра устарела. Начните новую командой /play",
+    ),
+    (
+        "message.greeting",
+        "🎲 Привет! Хотите сыграть в кубики? Используйте /play",
+    ),
+    (
+        "message.unknown",
+        "🤔 Не понимаю команду. Используйте /help для получения справки или /play для игры.",
+    ),
+    ("lang.prompt", "🌐 Выберите язык:"),
+    ("lang.changed", "✅ Язык переключен на русский"),
+    ("lang.unknown", "🤔 Неизвестный язык. Доступны: ru, en"),
+    ("ui.prompt", "🖥 Как показывать игру в этом чате?"),
+    ("ui.messages", "💬 Отдельными сообщениями"),
+    ("ui.in_place", "✏️ В одном сообщении"),
+    ("ui.changed", "✅ Режим интерфейса: {mode}"),
+    (
+        "ui.unknown",
+        "🤔 Неизвестный режим. Доступны: messages, in_place",
+    ),
+    (
+        "settings.show",
+        "⚙️ <b>Настройки чата</b>\n\
+         Игры: {games}\n\
+         Ставка: {bet}\n\
+         Язык: {lang}\n\
+         Интерфейс: {ui}\n\
+         Пауза между раундами: {cooldown}",
+    ),
+    (
+        "settings.usage",
+        "Изменить:\n\
+         /settings game &lt;игра&gt; on|off\n\
+         /settings bet &lt;мин&gt; &lt;макс&gt; или bet off\n\
+         /settings lang ru|en|off\n\
+         /settings ui messages|in_place\n\
+         /settings cooldown &lt;секунды&gt; (0 — без паузы)",
+    ),
+    ("settings.bet_range", "от {min} до {max} 🪙"),
+    ("settings.bet_default", "как выбрал игрок"),
+    ("settings.lang_default", "язык каждого игрока"),
+    ("settings.cooldown_off", "нет"),
+    ("settings.cooldown_value", "{seconds} с"),
+    ("settings.changed", "✅ Настройки сохранены"),
+    (
+        "settings.failed",
+        "⚠️ Не удалось сохранить настройки, попробуйте позже",
+    ),
+    ("settings.invalid", "🤔 Не понял настройку"),
+    (
+        "settings.not_admin",
+        "🔒 Настройки чата меняют только администраторы",
+    ),
+    (
+        "settings.wait",
+        "⏳ Следующий раунд в этом чате — через {seconds} с",
+    ),
+    (
+        "limits.show",
+        "🛡 <b>Ответственная игра</b>\n\
+         Лимит проигрыша за сутки: {loss}\n\
+         Проигрыш сегодня: {lost} 🪙\n\
+         Напоминание о перерыве: {reminder}\n\
+         Самоисключение: {exclusion}",
+    ),
+    (
+        "limits.usage",
+        "Изменить:\n\
+         /limits loss &lt;фишки&gt; или loss off\n\
+         /limits reminder &lt;минуты&gt; или reminder off\n\
+         /limits exclude &lt;дни&gt; (от 1 до {days}, отменить нельзя)\n\
+         Ужесточение действует сразу, ослабление — через {hours} ч.",
+    ),
+    ("limits.none", "нет"),
+    ("limits.loss_value", "{amount} 🪙"),
+    ("limits.reminder_value", "каждые {minutes} мин"),
+    ("limits.excluded_value", "до {until} UTC"),
+    (
+        "limits.pending_loss",
+        "⏳ С {at} UTC лимит проигрыша: {value}",
+    ),
+    (
+        "limits.pending_reminder",
+        "⏳ С {at} UTC напоминание: {value}",
+    ),
+    ("limits.applied", "✅ Ограничение действует"),
+    ("limits.scheduled", "⏳ Ослабление вступит в силу {at} UTC"),
+    (
+        "limits.excluded",
+        "🛑 Вы исключили себя из игры до {until} UTC",
+    ),
+    ("limits.invalid", "🤔 Не понял ограничение"),
+    (
+        "limits.failed",
+        "⚠️ Не удалось сохранить ограничение, попробуйте позже",
+    ),
+    (
+        "limits.error.excluded",
+        "🛑 Вы исключили себя из игры до {until} UTC",
+    ),
+    (
+        "limits.error.loss",
+        "🛑 Суточный лимит проигрыша {limit} 🪙: сегодня можно проиграть еще {left} 🪙",
+    ),
+    (
+        "limits.reminder",
+        "⏰ Вы играете уже {minutes} мин. Может, пора сделать перерыв?",
+    ),
+    (
+        "privacy.private",
+        "🔒 Эта команда работает только в личном чате с ботом",
+    ),
+    (
+        "export.usage",
+        "🤔 Формат выгрузки: /export json или /export csv",
+    ),
+    (
+        "export.caption",
+        "📦 Ваши данные: профиль, раунды и журнал операций",
+    ),
+    (
+        "export.failed",
+        "⚠️ Не удалось подготовить выгрузку, попробуйте позже",
+    ),
+    (
+        "forget.confirm",
+        "🗑 Удалить ваши данные?\n\n\
+         Профиль, достижения и все записи журнала операций будут удалены без возможности \
+         восстановления. Останутся только баланс, сроки бонусов и ваши ограничения \
+         /limits. В истории турниров и переводов других игроков вы останетесь безымянным \
+         участником. Сохранить данные можно командой /export.",
+    ),
+    ("forget.yes", "🗑 Удалить"),
+    ("forget.no", "❌ Отмена"),
+    (
+        "forget.done",
+        "✅ Ваши данные удалены. Баланс и ограничения сохранены.",
+    ),
+    ("forget.cancelled", "❌ Удаление отменено"),
+    ("forget.expired", "⌛ Это подтверждение уже использовано"),
+    ("forget.not_yours", "🔒 Это подтверждение другого игрока"),
+    (
+        "forget.failed",
+        "⚠️ Не удалось удалить данные, попробуйте позже",
+    ),
+    (
+        "forget.error.tournament",
+        "🏆 Вы участвуете в незавершенном турнире: удалить данные можно после его окончания",
+    ),
+    ("inline.dice.one", "{n} кубик"),
+    ("inline.dice.few", "{n} кубика"),
+    ("inline.dice.many", "{n} кубиков"),
+    ("inline.roll.title", "🎲 Бросить {dice} d{sides}"),
+    (
+        "inline.roll.description",
+        "Результат уже определён — нажмите, чтобы отправить",
+    ),
+    ("inline.bet.description", "Ставка: {choice}"),
+    (
+        "inline.bet.text",
+        "🎯 Ставка: {choice}\n🎲 Выпало: {dice_emoji} {dice}\n\n{verdict}",
+    ),
+    ("command.start", "Начать игру"),
+    ("command.help", "Помощь"),
+    ("command.play", "Играть в кубики"),
+    ("command.lang", "Сменить язык / Change language"),
+    ("command.ui", "Режим интерфейса: messages или in_place"),
+    ("command.daily", "Ежедневный бонус"),
+    ("command.faucet", "Фишки при низком балансе"),
+    ("command.bankrupt", "Сброс баланса при банкротстве"),
+    ("command.achievements", "Ваши достижения"),
+    ("command.stats", "Уровень и статистика"),
+    ("command.bet", "Размер ставки"),
+    ("command.skin", "Скин броска"),
+    ("command.tournament", "Турнир: create, join, start"),
+    ("command.jackpot", "Текущий джекпот"),
+    ("command.give", "Передать фишки: /give @username сумма"),
+    ("command.history", "История операций"),
+    ("command.settings", "Настройки чата (для администраторов)"),
+    (
+        "command.limits",
+        "Лимиты проигрыша, напоминания и самоисключение",
+    ),
+    ("command.export", "Выгрузка ваших данных: json или csv"),
+    ("command.forget_me", "Удалить ваши данные"),
+];
+
+const EN: &[(&str, &str)] = &[
+    (
+        "start.welcome",
+        "🎲 Hi! Welcome to the dice game!\n\n\
+         Try to guess the result of a dice roll.\n\
+         There are four game modes:\n\n\
+         🔵 Even/Odd - guess the parity\n\
+         🔴 High/Low 3.5 - guess the range\n\
+         🎯 Exact number - guess the exact number\n\
+         🎱 Guess one - will a one come up\n\n\
+         💰 Your balance: {balance} 🪙, stake per round: {stake} 🪙\n\n\
+         Use /play to start a game!",
+    ),
+    (
+        "help.body",
+        "🎮 <b>Dice game help</b>\n\n\
+         <b>Commands:</b>\n\
+         /start - start using the bot\n\
+         /play - start a new game\n\
+         /lang - change language\n\
+         /ui - interface mode for this chat\n\
+         /daily - daily bonus\n\
+         /faucet - chips when your balance is low\n\
+         /bankrupt - reset your balance when you are broke\n\
+         /achievements - your achievements\n\
+         /stats - level and stats\n\
+         /bet - stake size\n\
+         /skin - roll skin\n\
+         /tournament - chat tournament\n\
+         /jackpot - current jackpot\n\
+         /give - give chips to a player\n\
+         /history - transaction history\n\
+         /settings - chat settings (for admins)\n\
+         /limits - loss limits, reminders and self-exclusion\n\
+         /export - download your data (json or csv)\n\
+         /forget_me - delete your data\n\
+         /help - show this help\n\n\
+         <b>Game modes:</b>\n\n\
+         🔵 <b>Even/Odd</b>\n\
+         Guess whether the result is even or odd\n\n\
+         🔴 <b>High/Low 3.5</b>\n\
+         Guess whether the result is above 3.5 (4-6) or below 3.5 (1-3)\n\n\
+         🎯 <b>Exact number</b>\n\
+         Guess the exact number from 1 to 6\n\n\
+         ⚀ <b>Guess one</b>\n\
+         Guess whether the die shows a one\n\n\
+         <b>Stakes:</b> {stake} 🪙 per round. Wins pay x2 in Even/Odd and High/Low, \
+         x6 for the exact number and for a one, x1.2 when no one comes up",
+    ),
+    ("menu.prompt", "🎲 Choose a game mode:"),
+    ("menu.even_odd", "🔵 Even/Odd"),
+    ("menu.high_low", "🔴 High/Low 3.5"),
+    ("menu.exact", "🎯 Exact number"),
+    ("menu.guess_one", "⚀ Guess one"),
+    (
+        "even_odd.intro",
+        "🔵 <b>Game: Even/Odd</b>\n\n\
+         Will the roll be even or odd?",
+    ),
+    ("even_odd.even", "🔵 Even"),
+    ("even_odd.odd", "🔴 Odd"),
+    (
+        "high_low.intro",
+        "🔴 <b>Game: High/Low 3.5</b>\n\n\
+         Will the roll be above or below 3.5?",
+    ),
+    ("high_low.high", "⬆️ Above 3.5 (4-6)"),
+    ("high_low.low", "⬇️ Below 3.5 (1-3)"),
+    (
+        "exact.intro",
+        "🎯 <b>Game: Exact number</b>\n\n\
+         Pick the number from 1 to 6 the die will show:",
+    ),
+    ("exact.title", "🎯 Number {choice}"),
+    (
+        "guess_one.intro",
+        "⚀ <b>Game: Guess one</b>\n\n\
+         Will the die show a one?",
+    ),
+    ("guess_one.yes", "✅ Yes, a one"),
+    ("guess_one.no", "❌ No one"),
+    ("choice.even", "even"),
+    ("choice.odd", "odd"),
+    ("choice.high", "above 3.5 (4-6)"),
+    ("choice.low", "below 3.5 (1-3)"),
+    ("choice.yes", "yes, a one"),
+    ("choice.no", "no, not a one"),
+    ("outcome.even", "even"),
+    ("outcome.odd", "odd"),
+    ("outcome.high", "above 3.5"),
+    ("outcome.low", "below 3.5"),
+    ("outcome.one", "a one"),
+    ("outcome.not_one", "not a one"),
+    (
+        "even_odd.chosen",
+        "🎯 You chose: {choice}\n🎲 Rolling the die...",
+    ),
+    (
+        "high_low.chosen",
+        "📊 You chose: {choice}\n🎲 Rolling the die...",
+    ),
+    (
+        "exact.chosen",
+        "🎯 You chose number: {choice}\n🎲 Rolling the die...",
+    ),
+    (
+        "guess_one.chosen",
+        "⚀ You chose: {choice}\n🎲 Rolling the die...",
+    ),
+    (
+        "even_odd.win",
+        "🎉 Number {dice}: {outcome}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "even_odd.lose",
+        "😔 Number {dice}: {outcome}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "high_low.win",
+        "🎉 Number {dice} - {outcome}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "high_low.lose",
+        "😔 Number {dice} - {outcome}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "exact.win",
+        "🎉 Rolled: {dice}\nYou guessed it!\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "exact.lose",
+        "😔 Rolled: {dice}\nYou chose: {choice}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "guess_one.win",
+        "🎉 Number {dice}: {outcome}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "guess_one.lose",
+        "😔 Number {dice}: {outcome}\n\n{verdict}\n💰 Balance: {balance} 🪙",
+    ),
+    ("pool.win", "🎉 Congratulations! You guessed it!"),
+    ("pool.win", "🎊 Great! That's right!"),
+    ("pool.win", "✨ Excellent! You win!"),
+    ("pool.win", "🏆 Bravo! Spot on!"),
+    ("pool.win", "🎯 Superb! You guessed it!"),
+    ("pool.lose", "😔 Missed, but don't be upset!"),
+    ("pool.lose", "🎲 No luck this time, try again!"),
+    ("pool.lose", "💪 No worries, luck will smile next time!"),
+    ("pool.lose", "🌟 Don't worry, you'll get it!"),
+    (
+        "pool.lose",
+        "🎮 Nothing ventured, nothing gained — play again!",
+    ),
+    (
+        "game.disabled",
+        "🚫 This game is currently disabled. Pick another one: /play",
+    ),
+    (
+        "round.no_chips",
+        "😕 Not enough chips: the stake is {stake} 🪙, your balance is {balance} 🪙\n\
+         Free chips: /daily, /faucet or /bankrupt",
+    ),
+    (
+        "round.void",
+        "↩️ The roll does not count, your stake was not taken.",
+    ),
+    (
+        "round.failed",
+        "⚠️ You rolled {dice}, but the round result could not be saved. Please try again later",
+    ),
+    (
+        "bonus.daily",
+        "🎁 Daily bonus: +{amount} 🪙 (streak: {streak})\n💰 Balance: {balance} 🪙",
+    ),
+    ("bonus.streak.one", "{n} day"),
+    ("bonus.streak.other", "{n} days"),
+    (
+        "bonus.daily_wait",
+        "⏳ You already claimed today's bonus. Next one in {hours} h {minutes} min",
+    ),
+    (
+        "bonus.faucet",
+        "🚰 Faucet: +{amount} 🪙\n💰 Balance: {balance} 🪙",
+    ),
+    (
+        "bonus.faucet_rich",
+        "🚰 The faucet works while your balance is below {limit} 🪙",
+    ),
+    (
+        "bonus.faucet_wait",
+        "⏳ The faucet will be available again in {hours} h {minutes} min",
+    ),
+    (
+        "bonus.bankrupt",
+        "♻️ Balance restored: {balance} 🪙. Good luck!",
+    ),
+    (
+        "bonus.bankrupt_rich",
+        "💰 A reset is only available when your balance is below the stake ({limit} 🪙)",
+    ),
+    (
+        "bonus.bankrupt_wait",
+        "⏳ A reset will be available again in {hours} h {minutes} min",
+    ),
+    ("bonus.disabled", "🚫 This feature is disabled"),
+    (
+        "bonus.welcome_ready",
+        "🎁 Your daily bonus is waiting: /daily",
+    ),
+    (
+        "bonus.welcome_wait",
+        "🎁 Next daily bonus in {hours} h {minutes} min",
+    ),
+    (
+        "bonus.welcome_broke",
+        "🆘 Out of chips? Use /faucet or /bankrupt",
+    ),
+    (
+        "achievement.unlocked",
+        "🏆 Achievement unlocked: {badge} {title}",
+    ),
+    ("level.locked_game", "🔒 This game unlocks at level {level}"),
+    ("level.up", "⭐ Level up: {level}!"),
+    ("level.unlock_game", "🔓 New game: {game}"),
+    ("level.unlock_skin", "🎨 New roll skin: {skin} (/skin)"),
+    ("level.unlock_bet", "💰 Maximum stake: {stake} 🪙 (/bet)"),
+    (
+        "stats.body",
+        "📊 <b>Stats</b>\n\n\
+         ⭐ Level {level}: {xp} XP{next}\n\
+         💰 Balance: {balance} 🪙\n\
+         🎯 Stake: {stake} 🪙 (maximum {max_stake} 🪙)\n\
+         🎲 Rounds: {rounds}, wins: {wins} ({win_rate}%)",
+    ),
+    ("stats.next", ", level {level} at {xp} XP"),
+    ("stats.game", "{game}: {rounds} rounds, {wins} wins"),
+    (
+        "bet.current",
+        "🎯 Your stake: {stake} 🪙. Allowed {min} to {max} 🪙: /bet <amount>",
+    ),
+    ("bet.changed", "✅ Stake: {stake} 🪙"),
+    (
+        "bet.invalid",
+        "🤔 The stake must be between {min} and {max} 🪙",
+    ),
+    ("skin.prompt", "🎨 Choose a roll skin:"),
+    ("skin.dice", "🎲 Dice"),
+    ("skin.darts", "🎯 Darts"),
+    ("skin.bowling", "🎳 Bowling"),
+    ("skin.changed", "✅ Roll skin: {skin}"),
+    ("skin.locked", "🔒 This skin unlocks at level {level}"),
+    (
+        "jackpot.menu",
+        "🎰 Jackpot: {amount} 🪙 — guess the exact number {streak}",
+    ),
+    ("jackpot.streak.one", "{n} time in a row"),
+    ("jackpot.streak.other", "{n} times in a row"),
+    (
+        "jackpot.status",
+        "🎰 <b>Jackpot: {amount} 🪙</b>\n\n\
+         {share}% of every lost stake goes to the pool. Guess the exact number {streak} \
+         to take it all.",
+    ),
+    ("jackpot.won", "🎰🎰🎰 JACKPOT! +{amount} 🪙"),
+    ("jackpot.disabled", "The jackpot is disabled"),
+    (
+        "give.usage",
+        "Usage: /give @username amount, or /give amount in reply to a player's message",
+    ),
+    (
+        "give.unknown",
+        "🤔 I don't know {name}: they haven't played with the bot yet",
+    ),
+    ("give.confirm", "💸 Give {amount} 🪙 to {name}?"),
+    ("give.yes", "✅ Give"),
+    ("give.no", "❌ Cancel"),
+    (
+        "give.done",
+        "✅ Sent {amount} 🪙. Your balance: {balance} 🪙",
+    ),
+    (
+        "give.failed",
+        "⚠️ The transfer failed, please try again later",
+    ),
+    ("give.cancelled", "Transfer cancelled"),
+    (
+        "give.not_yours",
+        "Only the sender can confirm this transfer",
+    ),
+    (
+        "give.expired",
+        "This transfer is already confirmed or outdated",
+    ),
+    (
+        "give.error.amount",
+        "🤔 The amount must be between {min} and {max} 🪙",
+    ),
+    ("give.error.self", "🤔 You can't give chips to yourself"),
+    (
+        "give.error.no_chips",
+        "💸 Not enough chips: your balance is {balance} 🪙",
+    ),
+    (
+        "give.error.limit",
+        "⏳ Daily transfer limit: you can send {left} 🪙 more today",
+    ),
+    ("history.header", "📒 <b>Recent transactions</b> (UTC):"),
+    ("history.empty", "📒 No transactions yet"),
+    ("history.line", "{time} {kind}: {amount} 🪙 → {balance} 🪙"),
+    ("ledger.opening", "opening balance"),
+    ("ledger.bet", "stake"),
+    ("ledger.payout", "win"),
+    ("ledger.jackpot", "jackpot"),
+    ("ledger.daily", "daily bonus"),
+    ("ledger.faucet", "faucet"),
+    ("ledger.bankruptcy", "bankruptcy"),
+    ("ledger.tournament_fee", "tournament fee"),
+    ("ledger.tournament_refund", "fee refund"),
+    ("ledger.tournament_prize", "tournament prize"),
+    ("ledger.transfer_in", "transfer received"),
+    ("ledger.transfer_out", "transfer sent"),
+    ("ledger.admin", "operator adjustment"),
+    ("ledger.adjustment", "correction"),
+    (
+        "account.frozen",
+        "🧊 Your balance is frozen by an operator: games, bonuses and transfers are unavailable",
+    ),
+    ("account.banned", "🚫 Your access to the bot is blocked"),
+    (
+        "give.error.frozen",
+        "🧊 The transfer is not possible: a balance is frozen",
+    ),
+    (
+        "tournament.error.frozen",
+        "🧊 Your balance is frozen: you can't join the tournament",
+    ),
+    (
+        "admin.help",
+        "🛠 <b>Operator commands</b>\n\
+         /admin_stats — bot overview\n\
+         /admin_balance &lt;who&gt; &lt;±amount&gt; — adjust a balance\n\
+         /admin_freeze, /admin_unfreeze &lt;who&gt; — freeze a balance\n\
+         /admin_ban, /admin_unban &lt;who&gt; — block from the bot\n\
+         /admin_broadcast &lt;text&gt; — announcement to all chats\n\
+         /admin_game [game on|off] — turn a game on or off\n\
+         /admin_errors — recent errors\n\n\
+         Who — an id, @username or a reply to the user's message",
+    ),
+    (
+        "admin.stats",
+        "📊 <b>Overview</b>\n\
+         Users: {users} (played: {players})\n\
+         Rounds: {rounds}\n\
+         Chips on balances: {balance} 🪙\n\
+         Jackpot pools: {jackpots} 🪙\n\
+         Chats: {chats}, tournaments running: {tournaments}\n\
+         Banned: {banned}, frozen: {frozen}",
+    ),
+    ("admin.unknown_user", "🤔 User {name} not found"),
+    (
+        "admin.balance",
+        "✅ Balance of {user} changed by {amount} 🪙: now {balance} 🪙",
+    ),
+    (
+        "admin.balance_negative",
+        "🤔 Can't take more than the balance: {user} has {balance} 🪙",
+    ),
+    (
+        "admin.balance_overflow",
+        "🤔 The amount is too large: {user} has {balance} 🪙",
+    ),
+    ("admin.frozen", "🧊 Balance of {user} is frozen"),
+    ("admin.unfrozen", "✅ Balance of {user} is unfrozen"),
+    ("admin.banned", "🚫 {user} is banned"),
+    ("admin.unbanned", "✅ {user} is unbanned"),
+    ("admin.failed", "⚠️ Failed to save the change"),
+    (
+        "admin.broadcast_started",
+        "📢 Sending the announcement, recipients: {count}",
+    ),
+    (
+        "admin.broadcast_done",
+        "📢 Announcement delivered: {sent}, failed: {failed}",
+    ),
+    (
+        "admin.broadcast_aborted",
+        "🔧 The announcement was interrupted by a bot restart. Delivered: {sent}, failed: \
+         {failed}, not sent: {left}",
+    ),
+    (
+        "admin.broadcast_refused",
+        "🔧 The bot is restarting, the announcement was not sent",
+    ),
+    ("admin.games", "🎲 <b>Games</b> (/admin_game game on|off):"),
+    ("admin.game_line", "<code>{name}</code> — {state}"),
+    (
+        "admin.game_changed",
+        "✅ Game <code>{name}</code> is {state}",
+    ),
+    ("admin.game_on", "on"),
+    ("admin.game_off", "off"),
+    ("admin.game_config_off", "off in the configuration"),
+    (
+        "admin.game_unknown",
+        "🤔 Unknown game. Available: even_odd, high_low, exact_number, guess_one",
+    ),
+    ("admin.errors", "⚠️ <b>Recent errors</b> (UTC):"),
+    ("admin.errors_empty", "✅ No errors"),
+    ("admin.error_line", "{time} {level} {message}"),
+    ("tournament.format.knockout", "knockout"),
+    ("tournament.format.round_robin", "round robin"),
+    (
+        "tournament.created",
+        "🏆 Tournament registration is open ({format})!\n\
+         Entry fee: {fee} 🪙, {max} places. Starts in {minutes} min.\n\
+         Join: /tournament join",
+    ),
+    (
+        "tournament.joined",
+        "✅ {name} joined the tournament! Players: {count}, prize pool: {pool} 🪙",
+    ),
+    (
+        "tournament.registration",
+        "🏆 <b>Tournament ({format})</b>: registration is open\n\
+         Entry fee: {fee} 🪙, prize pool: {pool} 🪙\n\
+         Starts in {minutes} min. Join: /tournament join\n\n\
+         Players ({count}):",
+    ),
+    (
+        "tournament.header",
+        "🏆 <b>Tournament ({format})</b>, round {round} of {rounds}",
+    ),
+    ("tournament.round", "<b>Round {round}</b>"),
+    ("tournament.match_pending", "{home} vs {away}"),
+    (
+        "tournament.match_played",
+        "{home} {home_roll}:{away_roll} {away} → {winner}",
+    ),
+    ("tournament.bye", "{name} advances without an opponent"),
+    ("tournament.standings", "<b>Standings</b>"),
+    ("tournament.standing", "{place}. {name} — {wins} wins"),
+    ("tournament.next", "⏱ Next round in {minutes} min."),
+    (
+        "tournament.finished",
+        "🏁 <b>Tournament over!</b> Winner: {name}",
+    ),
+    ("tournament.prize", "🎁 {name}: +{amount} 🪙"),
+    (
+        "tournament.cancelled",
+        "❌ Tournament cancelled: at least two players are needed. Entry fees were refunded.",
+    ),
+    (
+        "tournament.none",
+        "No tournament in this chat. Create one: /tournament create [knockout|round_robin] \
+         [minutes]",
+    ),
+    (
+        "tournament.usage",
+        "Tournament commands: /tournament create [knockout|round_robin] [minutes], \
+         /tournament join, /tournament start",
+    ),
+    (
+        "tournament.error.exists",
+        "🏆 A tournament is already running: /tournament",
+    ),
+    (
+        "tournament.error.not_found",
+        "There is no open tournament in this chat",
+    ),
+    ("tournament.error.closed", "Registration is already closed"),
+    (
+        "tournament.error.joined",
+        "You are already in the tournament",
+    ),
+    ("tournament.error.full", "The tournament is full"),
+    (
+        "tournament.error.no_chips",
+        "💸 Not enough chips for the {fee} 🪙 entry fee",
+    ),
+    (
+        "tournament.error.not_enough",
+        "At least two players are needed to start",
+    ),
+    (
+        "tournament.error.not_allowed",
+        "Only the tournament creator or a chat admin can do this",
+    ),
+    (
+        "achievements.header",
+        "🏆 <b>Achievements</b> ({count} of {total})",
+    ),
+    ("achievement.line", "{badge} <b>{title}</b> — {description}"),
+    (
+        "achievement.locked",
+        "🔒 {title} — {description} ({progress}/{threshold})",
+    ),
+    ("achievement.first_win.title", "First win"),
+    ("achievement.first_win.description", "win any round"),
+    ("achievement.regular.title", "Regular"),
+    ("achievement.regular.description", "play 100 rounds"),
+    ("achievement.sniper.title", "Sniper"),
+    (
+        "achievement.sniper.description",
+        "guess the exact number 5 times",
+    ),
+    ("achievement.even_odd_streak.title", "On fire"),
+    (
+        "achievement.even_odd_streak.description",
+        "10 wins in a row in Even/Odd",
+    ),
+    ("achievement.high_low_master.title", "Sense of balance"),
+    (
+        "achievement.high_low_master.description",
+        "25 wins in High/Low",
+    ),
+    ("achievement.snake_eyes.title", "Snake eyes"),
+    (
+        "achievement.snake_eyes.description",
+        "roll a one 6 times in Guess one",
+    ),
+    ("round.again", "🎮 Want to play again?"),
+    ("again.repeat", "🔁 Again: {choice}"),
+    ("again.other", "🔀 Different choice"),
+    ("again.menu", "🎮 Change game"),
+    (
+        "round.shutting_down",
+        "🔧 The bot is restarting and not accepting new rounds. Try again in a minute!",
+    ),
+    ("rate.cooldown", "⏳ Slow down! Try again in {seconds} s."),
+    (
+        "round.already_played",
+        "🎲 This round has already been played!",
+    ),
+    (
+        "callback.outdated",
+        "⌛ This button is outdated. Start a new game with /play",
+    ),
+    (
+        "callback.invalid",
+        "🤔 Unknown button. Start a new game with /play",
+    ),
+    (
+        "round.expired",
+        "⌛ This game has expired. Start a new one with /play",
+    ),
+    ("message.greeting", "🎲 Hi! Want to play dice? Use /play"),
+    (
+        "message.unknown",
+        "🤔 I don't understand. Use /help for help or /play to play.",
+    ),
+    ("lang.prompt", "🌐 Choose a language:"),
+    ("lang.changed", "✅ Language switched to English"),
+    ("lang.unknown", "🤔 Unknown language. Available: ru, en"),
+    ("ui.prompt", "🖥 How should games look in this chat?"),
+    ("ui.messages", "💬 Separate messages"),
+    ("ui.in_place", "✏️ One message"),
+    ("ui.changed", "✅ Interface mode: {mode}"),
+    (
+        "ui.unknown",
+        "🤔 Unknown mode. Available: messages, in_place",
+    ),
+    (
+        "settings.show",
+        "⚙️ <b>Chat settings</b>\n\
+         Games: {games}\n\
+         Stake: {bet}\n\
+         Language: {lang}\n\
+         Interface: {ui}\n\
+         Pause between rounds: {cooldown}",
+    ),
+    (
+        "settings.usage",
+        "Change:\n\
+         /settings game &lt;game&gt; on|off\n\
+         /settings bet &lt;min&gt; &lt;max&gt; or bet off\n\
+         /settings lang ru|en|off\n\
+         /settings ui messages|in_place\n\
+         /settings cooldown &lt;seconds&gt; (0 — no pause)",
+    ),
+    ("settings.bet_range", "from {min} to {max} 🪙"),
+    ("settings.bet_default", "chosen by each player"),
+    ("settings.lang_default", "each player's language"),
+    ("settings.cooldown_off", "none"),
+    ("settings.cooldown_value", "{seconds} s"),
+    ("settings.changed", "✅ Settings saved"),
+    (
+        "settings.failed",
+        "⚠️ Failed to save the settings, please try again later",
+    ),
+    ("settings.invalid", "🤔 Unknown setting"),
+    (
+        "settings.not_admin",
+        "🔒 Only chat admins can change the settings",
+    ),
+    (
+        "settings.wait",
+        "⏳ Your next round in this chat is in {seconds} s",
+    ),
+    (
+        "limits.show",
+        "🛡 <b>Responsible gaming</b>\n\
+         Daily loss limit: {loss}\n\
+         Lost today: {lost} 🪙\n\
+         Break reminder: {reminder}\n\
+         Self-exclusion: {exclusion}",
+    ),
+    (
+        "limits.usage",
+        "Change:\n\
+         /limits loss &lt;chips&gt; or loss off\n\
+         /limits reminder &lt;minutes&gt; or reminder off\n\
+         /limits exclude &lt;days&gt; (1 to {days}, can't be cancelled)\n\
+         Stricter limits apply at once, looser ones after {hours} h.",
+    ),
+    ("limits.none", "none"),
+    ("limits.loss_value", "{amount} 🪙"),
+    ("limits.reminder_value", "every {minutes} min"),
+    ("limits.excluded_value", "until {until} UTC"),
+    (
+        "limits.pending_loss",
+        "⏳ From {at} UTC the loss limit is: {value}",
+    ),
+    (
+        "limits.pending_reminder",
+        "⏳ From {at} UTC the reminder is: {value}",
+    ),
+    ("limits.applied", "✅ The limit is in effect"),
+    (
+        "limits.scheduled",
+        "⏳ The looser limit takes effect at {at} UTC",
+    ),
+    (
+        "limits.excluded",
+        "🛑 You have excluded yourself from playing until {until} UTC",
+    ),
+    ("limits.invalid", "🤔 Unknown limit"),
+    (
+        "limits.failed",
+        "⚠️ Failed to save the limit, please try again later",
+    ),
+    (
+        "limits.error.excluded",
+        "🛑 You have excluded yourself from playing until {until} UTC",
+    ),
+    (
+        "limits.error.loss",
+        "🛑 Daily loss limit is {limit} 🪙: you can lose {left} 🪙 more today",
+    ),
+    (
+        "limits.reminder",
+        "⏰ You have been playing for {minutes} min. Time for a break?",
+    ),
+    (
+        "privacy.private",
+        "🔒 This command only works in a private chat with the bot",
+    ),
+    (
+        "export.usage",
+        "🤔 Export format: /export json or /export csv",
+    ),
+    (
+        "export.caption",
+        "📦 Your data: profile, rounds and transaction history",
+    ),
+    (
+        "export.failed",
+        "⚠️ Failed to prepare the export, please try again later",
+    ),
+    (
+        "forget.confirm",
+        "🗑 Delete your data?\n\n\
+         Your profile, achievements and all transaction history will be deleted \
+         permanently. Only your balance, bonus timers and /limits restrictions are kept. \
+         Tournaments and other players' transfers will keep you as an anonymous \
+         participant. You can save your data with /export first.",
+    ),
+    ("forget.yes", "🗑 Delete"),
+    ("forget.no", "❌ Cancel"),
+    (
+        "forget.done",
+        "✅ Your data has been deleted. Your balance and restrictions are kept.",
+    ),
+    ("forget.cancelled", "❌ Deletion cancelled"),
+    (
+        "forget.expired",
+        "⌛ This confirmation has already been used",
+    ),
+    (
+        "forget.not_yours",
+        "🔒 This confirmation belongs to another player",
+    ),
+    (
+        "forget.failed",
+        "⚠️ Failed to delete the data, please try again later",
+    ),
+    (
+        "forget.error.tournament",
+        "🏆 You are in an unfinished tournament: you can delete your data after it ends",
+    ),
+    ("inline.dice.one", "{n} die"),
+    ("inline.dice.other", "{n} dice"),
+    ("inline.roll.title", "🎲 Roll {dice} d{sides}"),
+    (
+        "inline.roll.description",
+        "The result is already decided — tap to send",
+    ),
+    ("inline.bet.description", "Bet: {choice}"),
+    (
+        "inline.bet.text",
+        "🎯 Bet: {choice}\n🎲 Rolled: {dice_emoji} {dice}\n\n{verdict}",
+    ),
+    ("command.start", "Start the game"),
+    ("command.help", "Help"),
+    ("command.play", "Play dice"),
+    ("command.lang", "Change language / Сменить язык"),
+    ("command.ui", "Interface mode: messages or in_place"),
+    ("command.daily", "Daily bonus"),
+    ("command.faucet", "Chips when your balance is low"),
+    ("command.bankrupt", "Reset your balance when broke"),
+    ("command.achievements", "Your achievements"),
+    ("command.stats", "Level and statistics"),
+    ("command.bet", "Stake size"),
+    ("command.skin", "Dice skin"),
+    ("command.tournament", "Tournament: create, join, start"),
+    ("command.jackpot", "Current jackpot"),
+    ("command.give", "Send chips: /give @username amount"),
+    ("command.history", "Transaction history"),
+    ("command.settings", "Chat settings (for admins)"),
+    (
+        "command.limits",
+        "Loss limits, reminders and self-exclusion",
+    ),
+    ("command.export", "Export your data: json or csv"),
+    ("command.forget_me", "Delete your data"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeSet;
+
+    fn base_keys(lang: Lang) -> BTreeSet<&'static str> {
+        builtin_catalog(lang)
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|key| !PLURALS.iter().any(|family| key.starts_with(family)))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_have_same_keys() {
+        assert_eq!(base_keys(Lang::Ru), base_keys(Lang::En));
+    }
+
+    #[test]
+    fn test_pools_have_variants() {
+        let i18n = I18n::new();
+        for lang in Lang::ALL {
+            assert_eq!(i18n.entries(lang, "pool.win").unwrap().len(), 5);
+            assert_eq!(i18n.entries(lang, "pool.lose").unwrap().len(), 5);
+        }
+    }
+
+    #[test]
+    fn test_builtin_strings_use_known_placeholders() {
+        for lang in Lang::ALL {
+            for (key, value) in builtin_catalog(lang) {
+                for name in template_placeholders(value) {
+                    assert!(
+                        placeholders(key).contains(&name),
+                        "{:?} {}: неизвестная подстановка {{{}}}",
+                        lang,
+                        key,
+                        name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_language_detection() {
+        assert_eq!(Lang::from_language_code(None), Lang::Ru);
+        assert_eq!(Lang::from_language_code(Some("ru")), Lang::Ru);
+        assert_eq!(Lang::from_language_code(Some("uk-UA")), Lang::Ru);
+        assert_eq!(Lang::from_language_code(Some("en-US")), Lang::En);
+        assert_eq!(Lang::from_language_code(Some("de")), Lang::En);
+        assert_eq!(Lang::parse(" EN "), Some(Lang::En));
+        assert_eq!(Lang::parse("fr"), None);
+    }
+
+    #[test]
+    fn test_plural_rules() {
+        let i18n = I18n::new();
+        let ru: Vec<String> = [1, 2, 5, 11, 21, 22, 112]
+            .iter()
+            .map(|&n| i18n.plural(Lang::Ru, "inline.dice", n, &[]))
+            .collect();
+        assert_eq!(
+            ru,
+            [
+                "1 кубик",
+                "2 кубика",
+                "5 кубиков",
+                "11 кубиков",
+                "21 кубик",
+                "22 кубика",
+                "112 кубиков"
+            ]
+        );
+        assert_eq!(i18n.plural(Lang::En, "inline.dice", 1, &[]), "1 die");
+        assert_eq!(i18n.plural(Lang::En, "inline.dice", 3, &[]), "3 dice");
+        assert_eq!(i18n.plural(Lang::En, "bonus.streak", 1, &[]), "1 day");
+        assert_eq!(i18n.plural(Lang::Ru, "bonus.streak", 5, &[]), "5 дней");
+        assert_eq!(
+            i18n.plural(Lang::Ru, "jackpot.streak", 5, &[]),
+            "5 раз подряд"
+        );
+    }
+
+    #[test]
+    fn test_format_and_fallback() {
+        let i18n = I18n::new();
+        assert_eq!(
+            i18n.format(Lang::En, "exact.title", &[("choice", &4)]),
+            "🎯 Number 4"
+        );
+        assert_eq!(i18n.text(Lang::En, "missing.key"), "missing.key");
+    }
+}
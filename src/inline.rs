@@ -0,0 +1,271 @@
+//! Inline-режим: `@botname 2d6`, `@botname even` и т.п. из любого чата.
+//!
+//! Бросок выполняется на сервере заранее, пользователь выбирает готовую карточку результата.
+
+use crate::game::DiceGame;
+use crate::i18n::{I18n, Lang};
+use crate::state::{EvenOddChoice, GameChoice, GuessOneChoice, HighLowChoice};
+
+/// Максимальное количество кубиков в одном броске
+pub const MAX_DICE: u8 = 10;
+/// Максимальное количество граней у кубика
+pub const MAX_SIDES: u8 = 100;
+
+/// Запрос, извлечённый из текста inline-запроса
+#[derive(Clone, Debug, PartialEq)]
+pub enum InlineRequest {
+    /// Бросок в нотации `NdM`
+    Roll { count: u8, sides: u8 },
+    /// Ставка в одной из игр
+    Bet(GameChoice),
+}
+
+/// Готовая карточка результата
+#[derive(Clone, Debug, PartialEq)]
+pub struct InlineCard {
+    pub title: String,
+    pub description: String,
+    pub text: String,
+}
+
+/// Разбор текста запроса. Пустой запрос возвращает набор вариантов по умолчанию.
+pub fn parse_query(query: &str) -> Vec<InlineRequest> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![
+            InlineRequest::Roll { count: 1, sides: 6 },
+            InlineRequest::Bet(GameChoice::EvenOdd(EvenOddChoice::Even)),
+            InlineRequest::Bet(GameChoice::EvenOdd(EvenOddChoice::Odd)),
+            InlineRequest::Bet(GameChoice::HighLow(HighLowChoice::High)),
+            InlineRequest::Bet(GameChoice::HighLow(HighLowChoice::Low)),
+        ];
+    }
+
+    if let Some((count, sides)) = parse_dice_notation(&query) {
+        return vec![InlineRequest::Roll { count, sides }];
+    }
+
+    let choice = match query.as_str() {
+        "even" | "четное" | "чет" => GameChoice::EvenOdd(EvenOddChoice::Even),
+        "odd" | "нечетное" | "нечет" => GameChoice::EvenOdd(EvenOddChoice::Odd),
+        "high" | "больше" => GameChoice::HighLow(HighLowChoice::High),
+        "low" | "меньше" => GameChoice::HighLow(HighLowChoice::Low),
+        "one" | "единица" => GameChoice::GuessOne(GuessOneChoice::Yes),
+        "not one" | "no one" | "не единица" => GameChoice::GuessOne(GuessOneChoice::No),
+        number => match number.parse::<u8>() {
+            Ok(n) if (1..=6).contains(&n) => GameChoice::ExactNumber(n),
+            _ => return Vec::new(),
+        },
+    };
+    vec![InlineRequest::Bet(choice)]
+}
+
+/// Разбор нотации `NdM` (`d6`, `2d6`, `3d20`)
+pub fn parse_dice_notation(input: &str) -> Option<(u8, u8)> {
+    let (count, sides) = input.split_once('d')?;
+    let count = if count.is_empty() {
+        1
+    } else {
+        count.parse::<u8>().ok()?
+    };
+    let sides = sides.parse::<u8>().ok()?;
+    if (1..=MAX_DICE).contains(&count) && (2..=MAX_SIDES).contains(&sides) {
+        Some((count, sides))
+    } else {
+        None
+    }
+}
+
+/// Сколько кубиков и с каким количеством граней нужно бросить для запроса
+pub fn dice_for(request: &InlineRequest) -> (u8, u8) {
+    match request {
+        InlineRequest::Roll { count, sides } => (*count, *sides),
+        InlineRequest::Bet(_) => (1, 6),
+    }
+}
+
+/// Форматирование карточки по заранее выполненному броску; `balance` — баланс игрока для
+/// подстановки в фразы из пулов (inline-ставка его не меняет)
+pub fn render(
+    i18n: &I18n,
+    lang: Lang,
+    request: &InlineRequest,
+    rolls: &[u8],
+    balance: i64,
+) -> InlineCard {
+    match request {
+        InlineRequest::Roll { count, sides } => {
+            let faces = rolls
+                .iter()
+                .map(|&value| format_face(value, *sides))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let total: u32 = rolls.iter().map(|&v| v as u32).sum();
+            let text = if rolls.len() > 1 {
+                format!("🎲 {}d{}: {} = {}", count, sides, faces, total)
+            } else {
+                format!("🎲 {}d{}: {}", count, sides, faces)
+            };
+            let dice = i18n.plural(lang, "inline.dice", *count as u64, &[]);
+            InlineCard {
+                title: i18n.format(
+                    lang,
+                    "inline.roll.title",
+                    &[("dice", &dice), ("sides", sides)],
+                ),
+                description: i18n.text(lang, "inline.roll.description").to_string(),
+                text,
+            }
+        }
+        InlineRequest::Bet(choice) => {
+            let dice_result = rolls[0];
+            let is_win = DiceGame::check(dice_result, choice);
+            let (title, choice_text) = choice_labels(i18n, lang, choice);
+            let outcome_text = i18n.outcome_text(lang, &choice.game_type(), dice_result);
+            let pool = if is_win { "pool.win" } else { "pool.lose" };
+            let verdict = i18n.random(
+                lang,
+                pool,
+                &[
+                    ("dice", &dice_result),
+                    ("choice", &choice_text),
+                    ("outcome", &outcome_text),
+                    ("balance", &balance),
+                ],
+            );
+            InlineCard {
+                title,
+                description: i18n.format(
+                    lang,
+                    "inline.bet.description",
+                    &[("choice", &choice_text)],
+                ),
+                text: i18n.format(
+                    lang,
+                    "inline.bet.text",
+                    &[
+                        ("choice", &choice_text),
+                        ("dice_emoji", &DiceGame::dice_emoji(dice_result)),
+                        ("dice", &dice_result),
+                        ("verdict", &verdict),
+                    ],
+                ),
+            }
+        }
+    }
+}
+
+fn format_face(value: u8, sides: u8) -> String {
+    if sides == 6 {
+        DiceGame::dice_emoji(value).to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Заголовок карточки и текст выбора
+fn choice_labels(i18n: &I18n, lang: Lang, choice: &GameChoice) -> (String, String) {
+    let (title_key, choice_key) = match choice {
+        GameChoice::EvenOdd(EvenOddChoice::Even) => ("even_odd.even", "choice.even"),
+        GameChoice::EvenOdd(EvenOddChoice::Odd) => ("even_odd.odd", "choice.odd"),
+        GameChoice::HighLow(HighLowChoice::High) => ("high_low.high", "choice.high"),
+        GameChoice::HighLow(HighLowChoice::Low) => ("high_low.low", "choice.low"),
+        GameChoice::GuessOne(GuessOneChoice::Yes) => ("guess_one.yes", "choice.yes"),
+        GameChoice::GuessOne(GuessOneChoice::No) => ("guess_one.no", "choice.no"),
+        GameChoice::ExactNumber(n) => {
+            return (
+                i18n.format(lang, "exact.title", &[("choice", n)]),
+                n.to_string(),
+            )
+        }
+    };
+    (
+        i18n.text(lang, title_key).to_string(),
+        i18n.text(lang, choice_key).to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_dice_notation() {
+        assert_eq!(parse_dice_notation("2d6"), Some((2, 6)));
+        assert_eq!(parse_dice_notation("d20"), Some((1, 20)));
+        assert_eq!(parse_dice_notation("0d6"), None);
+        assert_eq!(parse_dice_notation("11d6"), None);
+        assert_eq!(parse_dice_notation("2d1"), None);
+        assert_eq!(parse_dice_notation("2x6"), None);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(parse_query("  "), parse_query(""));
+        assert_eq!(parse_query("").len(), 5);
+        assert_eq!(
+            parse_query("EVEN"),
+            vec![InlineRequest::Bet(GameChoice::EvenOdd(EvenOddChoice::Even))]
+        );
+        assert_eq!(
+            parse_query("4"),
+            vec![InlineRequest::Bet(GameChoice::ExactNumber(4))]
+        );
+        assert_eq!(
+            parse_query("3d8"),
+            vec![InlineRequest::Roll { count: 3, sides: 8 }]
+        );
+        assert!(parse_query("weather").is_empty());
+    }
+
+    #[test]
+    fn test_render_roll_uses_dice_emoji() {
+        let i18n = I18n::new();
+        let card = render(
+            &i18n,
+            Lang::Ru,
+            &InlineRequest::Roll { count: 2, sides: 6 },
+            &[3, 5],
+            100,
+        );
+        assert_eq!(card.text, "🎲 2d6: ⚂ ⚄ = 8");
+        assert_eq!(card.title, "🎲 Бросить 2 кубика d6");
+
+        let card = render(
+            &i18n,
+            Lang::En,
+            &InlineRequest::Roll {
+                count: 1,
+                sides: 20,
+            },
+            &[17],
+            100,
+        );
+        assert_eq!(card.text, "🎲 1d20: 17");
+        assert_eq!(card.title, "🎲 Roll 1 die d20");
+    }
+
+    #[test]
+    fn test_render_bet() {
+        let mut i18n = I18n::new();
+        let bet = InlineRequest::Bet(GameChoice::HighLow(HighLowChoice::High));
+        let card = render(&i18n, Lang::Ru, &bet, &[5], 100);
+        assert!(card
+            .text
+            .starts_with("🎯 Ставка: больше 3.5 (4-6)\n🎲 Выпало: ⚄ 5"));
+
+        // Фразы из пулов получают подстановки броска
+        i18n.set_override(
+            Lang::En,
+            "pool.win",
+            vec!["{dice} is {outcome}, balance {balance}".to_string()],
+        );
+        let card = render(&i18n, Lang::En, &bet, &[5], 100);
+        assert!(
+            card.text.ends_with("5 is above 3.5, balance 100"),
+            "{}",
+            card.text
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Прогрессивный джекпот.
+//!
+//! Доля `jackpot.share_percent` каждой проигранной ставки уходит в фонд, общий для всех
+//! чатов или свой в каждом чате (`jackpot.scope`). Фонд целиком забирает игрок, угадавший
+//! точное число `jackpot.exact_streak` раз подряд, после чего фонд начинается заново с
+//! `jackpot.seed`. Баланс игрока и фонд меняются в одном `Storage::modify_round`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::PlayerStats;
+use crate::config::JackpotConfig;
+use crate::state::GameType;
+
+/// Ключ общего фонда в хранилище (id чатов в Telegram не бывают нулевыми)
+const GLOBAL: i64 = 0;
+
+/// Чей фонд пополняется
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Один фонд на все чаты
+    #[default]
+    Global,
+    /// Свой фонд в каждом чате
+    Chat,
+}
+
+/// Ключ фонда для раунда в чате `chat_id`
+pub fn pool_key(scope: Scope, chat_id: i64) -> i64 {
+    match scope {
+        Scope::Global => GLOBAL,
+        Scope::Chat => chat_id,
+    }
+}
+
+/// Выпал ли джекпот: раунд в «Точное число» выигран и серия побед кратна порогу.
+/// Статистика должна уже учитывать этот раунд
+pub fn is_hit(stats: &PlayerStats, game: &GameType, config: &JackpotConfig) -> bool {
+    *game == GameType::ExactNumber
+        && stats
+            .games
+            .get(game)
+            .is_some_and(|s| s.streak > 0 && s.streak % config.exact_streak == 0)
+}
+
+/// Учет раунда в фонде: доля проигранной ставки идет в фонд, при джекпоте фонд
+/// выплачивается и начинается заново. Возвращает выигрыш джекпота
+pub fn settle(
+    pool: &mut i64,
+    config: &JackpotConfig,
+    stake: i64,
+    win: bool,
+    hit: bool,
+) -> Option<i64> {
+    if !win {
+        *pool += stake * i64::from(config.share_percent) / 100;
+    }
+    if !hit {
+        return None;
+    }
+    let won = *pool;
+    *pool = config.seed;
+    Some(won)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_losses_fund_the_pool_until_hit() {
+        let config = JackpotConfig::default();
+        let mut pool = config.seed;
+        for _ in 0..10 {
+            assert_eq!(settle(&mut pool, &config, 20, false, false), None);
+        }
+        // Выигрыши фонд не пополняют
+        assert_eq!(settle(&mut pool, &config, 20, true, false), None);
+        assert_eq!(pool, config.seed + 10 * 2);
+
+        assert_eq!(
+            settle(&mut pool, &config, 20, true, true),
+            Some(config.seed + 20)
+        );
+        assert_eq!(pool, config.seed);
+    }
+
+    #[test]
+    fn test_hit_needs_exact_number_streak() {
+        let config = JackpotConfig::default();
+        let mut stats = PlayerStats::default();
+        let exact = GameType::ExactNumber;
+        stats.record(&exact, 4, true);
+        stats.record(&exact, 2, true);
+        assert!(!is_hit(&stats, &exact, &config));
+        stats.record(&exact, 5, true);
+        assert!(is_hit(&stats, &exact, &config));
+
+        // Серия в другой игре не считается
+        let mut other = PlayerStats::default();
+        for _ in 0..3 {
+            other.record(&GameType::EvenOdd, 2, true);
+        }
+        assert!(!is_hit(&other, &GameType::EvenOdd, &config));
+        assert_eq!(pool_key(Scope::Chat, -100), -100);
+        assert_eq!(pool_key(Scope::Global, -100), GLOBAL);
+    }
+}
@@ -0,0 +1,391 @@
+//! Журнал операций с фишками.
+//!
+//! Любое изменение баланса проводится через [`UserProfile::post`] с видом операции, а
+//! хранилище после каждого изменения дописывает проводки в JSON Lines файл рядом с
+//! файлом данных (`storage.json` → `storage.ledger.jsonl`). Файл только дописывается:
+//! первая запись пользователя (`opening`) — баланс на момент подключения журнала, и сумма
+//! всех его записей равна текущему балансу. Если код изменил баланс без проводки,
+//! разница записывается как `adjustment`, так что баланс восстанавливается всегда.
+//! Записи пользователя читаются по смещениям строк из индекса в памяти, а не перебором
+//! всего файла.
+//!
+//! [`UserProfile::post`]: crate::storage::UserProfile::post
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Вид операции
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// Баланс на момент первой записи пользователя в журнал
+    Opening,
+    Bet,
+    Payout,
+    Jackpot,
+    Daily,
+    Faucet,
+    Bankruptcy,
+    TournamentFee,
+    TournamentRefund,
+    TournamentPrize,
+    TransferIn,
+    TransferOut,
+    /// Изменение баланса оператором бота
+    Admin,
+    /// Изменение баланса без проводки
+    Adjustment,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Opening => "opening",
+            Kind::Bet => "bet",
+            Kind::Payout => "payout",
+            Kind::Jackpot => "jackpot",
+            Kind::Daily => "daily",
+            Kind::Faucet => "faucet",
+            Kind::Bankruptcy => "bankruptcy",
+            Kind::TournamentFee => "tournament_fee",
+            Kind::TournamentRefund => "tournament_refund",
+            Kind::TournamentPrize => "tournament_prize",
+            Kind::TransferIn => "transfer_in",
+            Kind::TransferOut => "transfer_out",
+            Kind::Admin => "admin",
+            Kind::Adjustment => "adjustment",
+        }
+    }
+}
+
+/// Изменение баланса, которое еще не записано в журнал
+#[derive(Clone, Debug, PartialEq)]
+pub struct Posting {
+    pub kind: Kind,
+    pub amount: i64,
+    /// Второй участник: получатель или отправитель перевода, оператор
+    pub peer: Option<u64>,
+}
+
+/// Запись журнала
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Сквозной номер записи
+    pub seq: u64,
+    /// Время (unix, секунды)
+    pub at: u64,
+    pub user_id: u64,
+    pub kind: Kind,
+    pub amount: i64,
+    /// Баланс после операции
+    pub balance: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer: Option<u64>,
+}
+
+/// Файл журнала
+pub struct Ledger {
+    path: PathBuf,
+    /// Смещения строк каждого пользователя в файле, чтобы `/history` и `/export` не читали
+    /// весь журнал. Строится при первом чтении и пополняется при дописывании
+    index: Mutex<Option<HashMap<u64, Vec<u64>>>>,
+}
+
+impl Ledger {
+    /// Журнал рядом с файлом данных
+    pub fn beside(storage_path: &Path) -> Self {
+        Self {
+            path: storage_path.with_extension("ledger.jsonl"),
+            index: Mutex::new(None),
+        }
+    }
+
+    /// Дописывание записей одной операцией записи; при ошибке файл возвращается к прежней
+    /// длине
+    pub fn append(&self, entries: &[Entry]) -> io::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        let mut offsets = Vec::new();
+        for entry in entries {
+            offsets.push((entry.user_id, lines.len() as u64));
+            serde_json::to_writer(&mut lines, entry)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            lines.push(b'\n');
+        }
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut index = self.index.lock().unwrap();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let start = file.metadata()?.len();
+        let written = file.write_all(&lines).and_then(|_| file.sync_data());
+        match (written, index.as_mut()) {
+            (Ok(()), Some(index)) => {
+                for (user_id, offset) in offsets {
+                    index.entry(user_id).or_default().push(start + offset);
+                }
+                Ok(())
+            }
+            (Ok(()), None) => Ok(()),
+            (Err(e), _) => {
+                // Недописанная строка обрезается, иначе следующая запись склеится с ней и
+                // журнал перестанет читаться. Если не вышло и это, индекс строится заново
+                if file.set_len(start).and_then(|_| file.sync_data()).is_err() {
+                    *index = None;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Все записи пользователя по порядку
+    pub fn entries(&self, user_id: u64) -> io::Result<Vec<Entry>> {
+        self.read(user_id, usize::MAX)
+    }
+
+    /// Последние `limit` записей пользователя, от новых к старым
+    pub fn recent(&self, user_id: u64, limit: usize) -> io::Result<Vec<Entry>> {
+        let mut entries = self.read(user_id, limit)?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Последние `limit` записей пользователя по порядку, по смещениям из индекса
+    fn read(&self, user_id: u64, limit: usize) -> io::Result<Vec<Entry>> {
+        // Блокировка держится и во время чтения, чтобы `replace` не заменил файл под
+        // прочитанными смещениями
+        let mut index = self.index.lock().unwrap();
+        if index.is_none() {
+            *index = Some(self.build_index()?);
+        }
+        let offsets = match index.as_ref().and_then(|index| index.get(&user_id)) {
+            Some(offsets) => &offsets[offsets.len().saturating_sub(limit)..],
+            None => return Ok(Vec::new()),
+        };
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut entries = Vec::with_capacity(offsets.len());
+        let mut line = String::new();
+        for &offset in offsets {
+            reader.seek(SeekFrom::Start(offset))?;
+            line.clear();
+            reader.read_line(&mut line)?;
+            entries.push(parse(&line)?);
+        }
+        Ok(entries)
+    }
+
+    /// Смещения строк по пользователям — единственное чтение всего файла
+    fn build_index(&self) -> io::Result<HashMap<u64, Vec<u64>>> {
+        let mut index: HashMap<u64, Vec<u64>> = HashMap::new();
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(index),
+            Err(e) => return Err(e),
+        };
+        let mut reader = BufReader::new(file);
+        let mut offset = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                return Ok(index);
+            }
+            let entry = parse(&line)?;
+            index.entry(entry.user_id).or_default().push(offset);
+            offset += read as u64;
+        }
+    }
+
+    /// Удаление данных пользователя — единственный случай, когда файл переписывается:
+    /// его записи убираются, а ссылки на него в чужих записях стираются. Первый шаг
+    /// переписывает файл во временный без блокировки журнала, так что дописывание в это
+    /// время продолжается
+    pub fn rewrite_without(&self, user_id: u64) -> io::Result<Rewrite> {
+        let tmp = self.path.with_extension("tmp");
+        let mut rewrite = Rewrite {
+            user_id,
+            file: File::create(&tmp)?,
+            tmp,
+            copied: 0,
+            removed: 0,
+        };
+        // Длина берется под блокировкой, чтобы не попасть на середину дописывания
+        let len = {
+            let _index = self.index.lock().unwrap();
+            file_len(&self.path)?
+        };
+        rewrite.copy(&self.path, len)?;
+        Ok(rewrite)
+    }
+
+    /// Второй шаг удаления: записи, дописанные после первого, тоже переносятся, и
+    /// временный файл заменяет журнал. Возвращает число удаленных записей
+    pub fn replace(&self, mut rewrite: Rewrite) -> io::Result<usize> {
+        let mut index = self.index.lock().unwrap();
+        let len = file_len(&self.path)?;
+        rewrite.copy(&self.path, len)?;
+        rewrite.file.sync_data()?;
+        std::fs::rename(&rewrite.tmp, &self.path)?;
+        // Смещения меняются: индекс строится заново при следующем чтении
+        *index = None;
+        Ok(rewrite.removed)
+    }
+}
+
+/// Журнал без записей одного пользователя во временном файле: заменяет журнал в
+/// [`Ledger::replace`], а если до этого не дошло, удаляется
+pub struct Rewrite {
+    user_id: u64,
+    tmp: PathBuf,
+    file: File,
+    /// Сколько байт журнала уже перенесено
+    copied: u64,
+    removed: usize,
+}
+
+impl Rewrite {
+    /// Перенос строк журнала до смещения `to`
+    fn copy(&mut self, path: &Path, to: u64) -> io::Result<()> {
+        if to <= self.copied {
+            return Ok(());
+        }
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(self.copied))?;
+        let mut lines = Vec::new();
+        for line in BufReader::new(file.take(to - self.copied)).lines() {
+            let mut entry = parse(&line?)?;
+            if entry.user_id == self.user_id {
+                self.removed += 1;
+                continue;
+            }
+            if entry.peer == Some(self.user_id) {
+                entry.peer = None;
+            }
+            serde_json::to_writer(&mut lines, &entry)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            lines.push(b'\n');
+        }
+        self.file.write_all(&lines)?;
+        self.copied = to;
+        Ok(())
+    }
+}
+
+impl Drop for Rewrite {
+    fn drop(&mut self) {
+        // После замены журнала временного файла уже нет
+        let _ = std::fs::remove_file(&self.tmp);
+    }
+}
+
+fn file_len(path: &Path) -> io::Result<u64> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+fn parse(line: &str) -> io::Result<Entry> {
+    serde_json::from_str(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Время записи в UTC: `2024-03-01 18:05`
+pub fn format_time(at: u64) -> String {
+    let days = (at / 86_400) as i64;
+    let minutes = at % 86_400 / 60;
+    // Перевод номера дня в дату григорианского календаря (алгоритм Х. Хиннанта)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(seq: u64, user_id: u64, kind: Kind, amount: i64, balance: i64) -> Entry {
+        Entry {
+            seq,
+            at: 0,
+            user_id,
+            kind,
+            amount,
+            balance,
+            peer: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::beside(&dir.path().join("data").join("storage.json"));
+        assert_eq!(
+            ledger.path,
+            dir.path().join("data").join("storage.ledger.jsonl")
+        );
+        assert!(ledger.entries(7).unwrap().is_empty());
+
+        ledger
+            .append(&[
+                entry(1, 7, Kind::Opening, 100, 100),
+                entry(2, 8, Kind::Opening, 100, 100),
+            ])
+            .unwrap();
+        ledger.append(&[entry(3, 7, Kind::Bet, -10, 90)]).unwrap();
+
+        let entries = ledger.entries(7).unwrap();
+        assert_eq!(entries.iter().map(|e| e.amount).sum::<i64>(), 90);
+        let recent = ledger.recent(7, 1).unwrap();
+        assert_eq!(recent, vec![entry(3, 7, Kind::Bet, -10, 90)]);
+
+        // Индекс нового экземпляра строится по файлу и сходится с дописанным
+        let reopened = Ledger::beside(&dir.path().join("data").join("storage.json"));
+        assert_eq!(reopened.entries(7).unwrap(), entries);
+        // Записи, дописанные между шагами удаления, тоже переносятся
+        let rewrite = ledger.rewrite_without(8).unwrap();
+        ledger
+            .append(&[
+                entry(4, 7, Kind::Payout, 20, 110),
+                entry(5, 8, Kind::Bet, -10, 90),
+            ])
+            .unwrap();
+        assert_eq!(ledger.replace(rewrite).unwrap(), 2);
+        assert!(!dir.path().join("data").join("storage.ledger.tmp").exists());
+        let recent = ledger.recent(7, 2).unwrap();
+        assert_eq!(recent.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![4, 3]);
+        assert!(ledger.entries(8).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+        assert_eq!(format_time(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_time(1_709_316_300), "2024-03-01 18:05");
+    }
+}
@@ -0,0 +1,194 @@
+//! Опыт и уровни игроков.
+//!
+//! За каждый раунд игрок получает опыт: чем труднее угадать результат, тем больше
+//! (победа удваивает награду). Уровни из таблицы [`LEVELS`] открывают игры, повышают
+//! максимальную ставку (`/bet`) и дают косметические скины кубика (`/skin`).
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use teloxide::types::DiceEmoji;
+
+use crate::state::GameType;
+
+/// Внешний вид броска; значение всегда от 1 до 6
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Skin {
+    #[default]
+    Dice,
+    Darts,
+    Bowling,
+}
+
+impl Skin {
+    pub const ALL: [Skin; 3] = [Skin::Dice, Skin::Darts, Skin::Bowling];
+
+    pub fn parse(name: &str) -> Option<Skin> {
+        match name.trim().to_lowercase().as_str() {
+            "dice" => Some(Skin::Dice),
+            "darts" => Some(Skin::Darts),
+            "bowling" => Some(Skin::Bowling),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skin::Dice => "dice",
+            Skin::Darts => "darts",
+            Skin::Bowling => "bowling",
+        }
+    }
+
+    /// Анимация Telegram для броска
+    pub fn emoji(&self) -> DiceEmoji {
+        match self {
+            Skin::Dice => DiceEmoji::Dice,
+            Skin::Darts => DiceEmoji::Darts,
+            Skin::Bowling => DiceEmoji::Bowling,
+        }
+    }
+
+    /// Уровень, на котором открывается скин
+    pub fn required_level(&self) -> u32 {
+        unlock_level(|level| level.skin == Some(*self))
+    }
+}
+
+/// Уровень и то, что он открывает
+#[derive(Debug, PartialEq)]
+pub struct Level {
+    /// Опыт, с которого начинается уровень
+    pub xp: u32,
+    /// Максимальная ставка в долях базовой ставки `economy.stake`
+    pub bet_multiplier: i64,
+    /// Игры, которые открываются на этом уровне
+    pub games: &'static [GameType],
+    pub skin: Option<Skin>,
+}
+
+/// Уровни по порядку, начиная с первого
+pub const LEVELS: &[Level] = &[
+    Level {
+        xp: 0,
+        bet_multiplier: 1,
+        games: &[GameType::EvenOdd, GameType::HighLow],
+        skin: Some(Skin::Dice),
+    },
+    Level {
+        xp: 100,
+        bet_multiplier: 2,
+        games: &[GameType::GuessOne],
+        skin: None,
+    },
+    Level {
+        xp: 300,
+        bet_multiplier: 3,
+        games: &[GameType::ExactNumber],
+        skin: Some(Skin::Darts),
+    },
+    Level {
+        xp: 600,
+        bet_multiplier: 5,
+        games: &[],
+        skin: None,
+    },
+    Level {
+        xp: 1000,
+        bet_multiplier: 10,
+        games: &[],
+        skin: Some(Skin::Bowling),
+    },
+];
+
+fn unlock_level(unlocks: impl Fn(&Level) -> bool) -> u32 {
+    LEVELS
+        .iter()
+        .position(unlocks)
+        .map_or(u32::MAX, |index| index as u32 + 1)
+}
+
+/// Параметры уровня `level` (нумерация с 1)
+pub fn level(level: u32) -> &'static Level {
+    let index = (level.max(1) as usize - 1).min(LEVELS.len() - 1);
+    &LEVELS[index]
+}
+
+/// Опыт за раунд: вес игры по сложности, победа удваивает
+pub fn xp_for(game: &GameType, win: bool) -> u32 {
+    let weight = match game {
+        GameType::EvenOdd | GameType::HighLow => 10,
+        GameType::GuessOne => 15,
+        GameType::ExactNumber => 30,
+    };
+    if win {
+        weight * 2
+    } else {
+        weight
+    }
+}
+
+/// Уровень по накопленному опыту
+pub fn level_for(xp: u32) -> u32 {
+    LEVELS.iter().filter(|level| xp >= level.xp).count() as u32
+}
+
+/// Опыт, нужный для следующего уровня (`None` — максимальный уровень)
+pub fn next_level_xp(xp: u32) -> Option<u32> {
+    LEVELS.get(level_for(xp) as usize).map(|level| level.xp)
+}
+
+/// Уровень, на котором открывается игра
+pub fn game_level(game: &GameType) -> u32 {
+    unlock_level(|level| level.games.contains(game))
+}
+
+/// Максимальная ставка на уровне
+pub fn max_stake(level_number: u32, base_stake: i64) -> i64 {
+    base_stake * level(level_number).bet_multiplier
+}
+
+/// Начисление опыта; возвращает новые уровни, если они получены
+pub fn gain(xp: &mut u32, amount: u32) -> Range<u32> {
+    let before = level_for(*xp);
+    *xp = xp.saturating_add(amount);
+    before + 1..level_for(*xp) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_levels_by_xp() {
+        assert_eq!(level_for(0), 1);
+        assert_eq!(level_for(99), 1);
+        assert_eq!(level_for(100), 2);
+        assert_eq!(level_for(5000), LEVELS.len() as u32);
+        assert_eq!(next_level_xp(150), Some(300));
+        assert_eq!(next_level_xp(5000), None);
+    }
+
+    #[test]
+    fn test_gain_reports_new_levels() {
+        let mut xp = 90;
+        assert!(gain(&mut xp, 5).is_empty());
+        assert_eq!(gain(&mut xp, 300).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(xp, 395);
+    }
+
+    #[test]
+    fn test_unlocks() {
+        assert_eq!(game_level(&GameType::EvenOdd), 1);
+        assert_eq!(game_level(&GameType::ExactNumber), 3);
+        assert_eq!(Skin::Bowling.required_level(), 5);
+        assert_eq!(max_stake(1, 10), 10);
+        assert_eq!(max_stake(4, 10), 50);
+        // Все игры открываются на каком-то уровне
+        for game in GameType::ALL {
+            assert!(game_level(&game) <= LEVELS.len() as u32);
+        }
+        assert!(xp_for(&GameType::ExactNumber, false) > xp_for(&GameType::EvenOdd, false));
+    }
+}
@@ -0,0 +1,362 @@
+//! Ответственная игра (`/limits`).
+//!
+//! Игрок сам задает суточный лимит проигрыша, напоминание о длительности сессии и
+//! самоисключение на несколько дней. Ужесточение действует сразу, а ослабление (больший
+//! лимит, более редкое напоминание или их отключение) — только через
+//! `limits.cool_off_hours`, чтобы решение не принималось в азарте. Самоисключение нельзя
+//! отменить или сократить, только продлить.
+//!
+//! Проигрыш за сутки (UTC) — ставки минус выплаты; выигрыш уменьшает его. Сессия — раунды
+//! с перерывами не дольше `limits.session_gap_minutes`.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::LimitsConfig;
+
+const DAY: u64 = 86_400;
+
+/// Ограничение, которое игрок может ужесточить или ослабить
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Limit {
+    /// Суточный лимит проигрыша; `None` — без лимита
+    Loss(Option<i64>),
+    /// Напоминание каждые N минут сессии; `None` — без напоминаний
+    Reminder(Option<u64>),
+}
+
+impl Limit {
+    /// Ослабляет ли новое значение текущее
+    fn loosens(&self, state: &LimitsState) -> bool {
+        fn looser<T: PartialOrd>(new: Option<T>, current: Option<T>) -> bool {
+            match (new, current) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(new), Some(current)) => new > current,
+            }
+        }
+        match *self {
+            Limit::Loss(amount) => looser(amount, state.loss_limit),
+            Limit::Reminder(minutes) => looser(minutes, state.reminder_minutes),
+        }
+    }
+
+    fn apply(self, state: &mut LimitsState) {
+        match self {
+            Limit::Loss(amount) => state.loss_limit = amount,
+            Limit::Reminder(minutes) => state.reminder_minutes = minutes,
+        }
+    }
+}
+
+/// Запрос из аргументов `/limits`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Request {
+    /// `loss <фишки>|off`, `reminder <минуты>|off`
+    Set(Limit),
+    /// `exclude <дни>`
+    Exclude(u64),
+}
+
+impl Request {
+    /// Разбор аргументов; `None` — неизвестная команда или недопустимое значение
+    pub fn parse(args: &str, config: &LimitsConfig) -> Option<Request> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_lowercase).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["loss", "off"] => Some(Request::Set(Limit::Loss(None))),
+            ["loss", amount] => {
+                let amount = amount.parse().ok().filter(|amount| *amount > 0)?;
+                Some(Request::Set(Limit::Loss(Some(amount))))
+            }
+            ["reminder", "off"] => Some(Request::Set(Limit::Reminder(None))),
+            ["reminder", minutes] => {
+                let minutes = minutes.parse().ok().filter(|minutes| *minutes > 0)?;
+                Some(Request::Set(Limit::Reminder(Some(minutes))))
+            }
+            ["exclude", days] => {
+                let days = days.parse().ok()?;
+                (1..=config.max_exclusion_days)
+                    .contains(&days)
+                    .then_some(Request::Exclude(days))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Ослабление, которое вступит в силу позже
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pending {
+    pub limit: Limit,
+    /// Время вступления в силу (unix, секунды)
+    pub at: u64,
+}
+
+/// Ограничения и счетчики игрока
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loss_limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminder_minutes: Option<u64>,
+    /// Самоисключение до (unix, секунды)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excluded_until: Option<u64>,
+    /// Отложенные ослабления, не больше одного на каждое ограничение
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<Pending>,
+    /// День (номер суток UTC) последнего раунда
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<u64>,
+    /// Проигрыш за этот день
+    pub lost: i64,
+    /// Начало текущей сессии
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_start: Option<u64>,
+    /// Время последнего раунда
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_round: Option<u64>,
+    /// Сколько напоминаний показано в текущей сессии
+    pub reminders: u64,
+}
+
+/// Почему раунд не начат
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LimitError {
+    /// Самоисключение еще действует
+    Excluded { until: u64 },
+    /// Ставка превысит суточный лимит проигрыша; `left` — сколько еще можно проиграть
+    LossLimit { limit: i64, left: i64 },
+}
+
+/// Результат изменения ограничения
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// Ужесточение, действует сразу
+    Applied,
+    /// Ослабление, вступит в силу в `at`
+    Scheduled { at: u64 },
+    /// Самоисключение до `until` (не раньше уже действующего)
+    Excluded { until: u64 },
+}
+
+impl LimitsState {
+    /// Применение отложенных ослаблений, срок которых наступил
+    pub fn refresh(&mut self, now: u64) {
+        let (due, waiting): (Vec<Pending>, Vec<Pending>) =
+            self.pending.iter().partition(|pending| pending.at <= now);
+        self.pending = waiting;
+        for pending in due {
+            pending.limit.apply(self);
+        }
+        if self.excluded_until.is_some_and(|until| until <= now) {
+            self.excluded_until = None;
+        }
+    }
+
+    /// Изменение ограничения или самоисключение
+    pub fn request(&mut self, request: Request, config: &LimitsConfig, now: u64) -> Outcome {
+        self.refresh(now);
+        match request {
+            Request::Set(limit) => {
+                let same = |pending: &Pending| {
+                    std::mem::discriminant(&pending.limit) == std::mem::discriminant(&limit)
+                };
+                self.pending.retain(|pending| !same(pending));
+                if limit.loosens(self) {
+                    let at = now + config.cool_off_hours * 3600;
+                    self.pending.push(Pending { limit, at });
+                    Outcome::Scheduled { at }
+                } else {
+                    limit.apply(self);
+                    Outcome::Applied
+                }
+            }
+            Request::Exclude(days) => {
+                let until = self.excluded_until.unwrap_or(0).max(now + days * DAY);
+                self.excluded_until = Some(until);
+                Outcome::Excluded { until }
+            }
+        }
+    }
+
+    /// Проверка перед раундом со ставкой `stake`
+    pub fn check(&mut self, stake: i64, now: u64) -> Result<(), LimitError> {
+        self.refresh(now);
+        if let Some(until) = self.excluded_until {
+            return Err(LimitError::Excluded { until });
+        }
+        if let Some(limit) = self.loss_limit {
+            let lost = self.lost_today(now);
+            if lost + stake > limit {
+                let left = (limit - lost).max(0);
+                return Err(LimitError::LossLimit { limit, left });
+            }
+        }
+        Ok(())
+    }
+
+    /// Проигрыш за сегодня (отрицательный, если игрок в плюсе)
+    pub fn lost_today(&self, now: u64) -> i64 {
+        match self.day {
+            Some(day) if day == now / DAY => self.lost,
+            _ => 0,
+        }
+    }
+
+    /// Фишки, вернувшиеся вне раунда (возврат взноса, приз турнира), уменьшают
+    /// проигрыш за сегодня
+    pub fn credit(&mut self, amount: i64, now: u64) {
+        self.lost = self.lost_today(now) - amount;
+        self.day = Some(now / DAY);
+    }
+
+    /// Учет сыгранного раунда с результатом `lost` (ставка минус выплаты). Возвращает
+    /// длительность сессии, если пора напомнить о перерыве
+    pub fn record_round(&mut self, lost: i64, config: &LimitsConfig, now: u64) -> Option<Duration> {
+        self.refresh(now);
+        self.lost = self.lost_today(now) + lost;
+        self.day = Some(now / DAY);
+
+        let gap = config.session_gap_minutes * 60;
+        let start = match (self.session_start, self.last_round) {
+            (Some(start), Some(last)) if now.saturating_sub(last) <= gap => start,
+            _ => {
+                self.reminders = 0;
+                now
+            }
+        };
+        self.session_start = Some(start);
+        self.last_round = Some(now);
+
+        let every = self.reminder_minutes? * 60;
+        let due = (now - start) / every;
+        if due > self.reminders {
+            self.reminders = due;
+            Some(Duration::from_secs(now - start))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const NOON: u64 = 20_000 * DAY + 12 * 3600;
+
+    #[test]
+    fn test_loosening_waits_for_cool_off() {
+        let config = LimitsConfig::default();
+        let mut state = LimitsState::default();
+        let parse = |args| Request::parse(args, &config).unwrap();
+
+        assert_eq!(
+            state.request(parse("loss 500"), &config, NOON),
+            Outcome::Applied
+        );
+        assert_eq!(
+            state.request(parse("loss 300"), &config, NOON),
+            Outcome::Applied
+        );
+        assert_eq!(state.loss_limit, Some(300));
+
+        let at = NOON + config.cool_off_hours * 3600;
+        assert_eq!(
+            state.request(parse("loss off"), &config, NOON),
+            Outcome::Scheduled { at }
+        );
+        assert_eq!(
+            state.check(301, at - 1),
+            Err(LimitError::LossLimit {
+                limit: 300,
+                left: 300
+            })
+        );
+        assert_eq!(state.check(301, at), Ok(()));
+        assert_eq!(state.loss_limit, None);
+
+        // Ужесточение отменяет отложенное ослабление
+        state.request(parse("reminder 30"), &config, NOON);
+        state.request(parse("reminder off"), &config, NOON);
+        state.request(parse("reminder 20"), &config, NOON);
+        assert_eq!((state.reminder_minutes, state.pending.len()), (Some(20), 0));
+
+        for invalid in [
+            "loss 0",
+            "loss -5",
+            "reminder soon",
+            "exclude 0",
+            "exclude 1000",
+        ] {
+            assert_eq!(Request::parse(invalid, &config), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_loss_limit_and_exclusion_block_rounds() {
+        let config = LimitsConfig::default();
+        let mut state = LimitsState::default();
+        state.request(Request::Set(Limit::Loss(Some(100))), &config, NOON);
+
+        state.record_round(80, &config, NOON);
+        assert_eq!(state.check(10, NOON), Ok(()));
+        assert_eq!(
+            state.check(30, NOON),
+            Err(LimitError::LossLimit {
+                limit: 100,
+                left: 20
+            })
+        );
+        // Выигрыш уменьшает проигрыш за день, новый день начинается с нуля
+        state.record_round(-40, &config, NOON);
+        assert_eq!(state.check(60, NOON), Ok(()));
+        // Так же действует возврат взноса или приз турнира
+        state.credit(20, NOON);
+        assert_eq!(state.check(80, NOON), Ok(()));
+        assert_eq!(state.lost_today(NOON), 20);
+        assert_eq!(state.check(100, NOON + DAY), Ok(()));
+
+        assert_eq!(
+            state.request(Request::Exclude(7), &config, NOON),
+            Outcome::Excluded {
+                until: NOON + 7 * DAY
+            }
+        );
+        // Самоисключение нельзя сократить
+        state.request(Request::Exclude(1), &config, NOON);
+        assert_eq!(
+            state.check(10, NOON + DAY),
+            Err(LimitError::Excluded {
+                until: NOON + 7 * DAY
+            })
+        );
+        assert_eq!(state.check(10, NOON + 7 * DAY), Ok(()));
+    }
+
+    #[test]
+    fn test_session_reminders() {
+        let config = LimitsConfig::default();
+        let mut state = LimitsState::default();
+        state.request(Request::Set(Limit::Reminder(Some(15))), &config, NOON);
+
+        let minutes = |state: &mut LimitsState, at: u64| {
+            state
+                .record_round(10, &config, NOON + at * 60)
+                .map(|d| d.as_secs() / 60)
+        };
+        assert_eq!(minutes(&mut state, 0), None);
+        assert_eq!(minutes(&mut state, 10), None);
+        assert_eq!(minutes(&mut state, 16), Some(16));
+        assert_eq!(minutes(&mut state, 20), None);
+        assert_eq!(minutes(&mut state, 31), Some(31));
+        // После долгого перерыва сессия начинается заново
+        assert_eq!(minutes(&mut state, 120), None);
+        assert_eq!(minutes(&mut state, 135), Some(15));
+    }
+}
@@ -1,98 +1,278 @@
-use axum::{http::StatusCode, response::Html, routing::get, Router};
-use log::{error, info};
+use axum::Router;
 use std::net::SocketAddr;
-use teloxide::prelude::*;
+use std::sync::Arc;
+use teloxide::{prelude::*, update_listeners};
+use tracing::{error, info, warn};
 
+mod achievements;
+mod admin;
+mod api;
+mod bonus;
 mod bot;
+mod callback;
+mod config;
+mod context;
 mod game;
+mod health;
+mod i18n;
+mod inline;
+mod jackpot;
+mod ledger;
+mod levels;
+mod limits;
+mod metrics;
+mod privacy;
+mod ratelimit;
+mod rounds;
+mod scheduler;
+mod settings;
+mod shutdown;
 mod state;
+mod storage;
+mod telemetry;
+mod templates;
+mod tournament;
+mod transfer;
 
-use bot::BotHandler;
+use bot::{BotHandler, Command};
+use config::Config;
+use context::AppContext;
+use health::Health;
+use i18n::{I18n, Lang};
+use metrics::{Metrics, TelegramErrorHandler};
+use ratelimit::Throttle;
+use scheduler::{Scheduler, ThrottledBot};
+use storage::Storage;
+use telemetry::ErrorLog;
 
 #[tokio::main]
 async fn main() {
-    // Инициализация логгера
-    env_logger::init();
-    
     // Автозагрузка переменных окружения из .env в dev-режиме
     // Не паникуем, если файла нет (prod окружение)
     let _ = dotenvy::dotenv();
 
+    // Загрузка конфигурации (файл + переменные окружения)
+    let config = Config::load();
+
+    // Инициализация логирования; формат берется из конфигурации, если она корректна.
+    // Последние ошибки остаются в памяти для команды оператора /admin_errors
+    let error_log = Arc::new(ErrorLog::new());
+    telemetry::init(
+        config
+            .as_ref()
+            .map(|config| config.logging.format)
+            .unwrap_or_default(),
+        error_log.clone(),
+    );
+
     info!("Запуск Telegram бота для игры в кубики");
 
-    // Получение токена бота из переменных окружения
-    let bot_token = std::env::var("BOT_TOKEN")
-        .expect("BOT_TOKEN должен быть установлен в переменных окружения");
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    info!(config = ?config, "Конфигурация загружена");
 
     info!("Подключение к Telegram API...");
-    let bot = Bot::new(bot_token);
-    
+    let bot = Bot::new(config.bot.token.clone().unwrap_or_default());
+
     // Создание обработчика бота
     let handler = BotHandler::new();
-    
-    // Получение порта из переменных окружения (по умолчанию 5000)
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "5000".to_string())
-        .parse::<u16>()
-        .expect("PORT должен быть валидным номером порта");
-    
+
+    // Открытие хранилища пользовательских данных
+    let storage = match Storage::open(&config.storage.path) {
+        Ok(storage) => storage.with_starting_balance(config.economy.starting_balance),
+        Err(e) => {
+            error!(
+                path = %config.storage.path.display(),
+                error = %e,
+                "Не удалось открыть хранилище"
+            );
+            std::process::exit(1);
+        }
+    };
+
+    // Переопределение текстов из файла шаблонов (если задан)
+    let mut i18n = I18n::new();
+    if let Some(templates_path) = &config.storage.templates {
+        match templates::load_file(&mut i18n, templates_path) {
+            Ok(count) => info!(
+                path = %templates_path.display(),
+                count,
+                "Шаблоны загружены"
+            ),
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let port = config.server.port;
+    let startup_delay = config.delays.startup();
+    let shutdown_grace = config.delays.shutdown_grace();
+    let api_state = config.api.enabled.then(|| {
+        Arc::new(api::ApiState::new(
+            config.games.enabled.clone(),
+            config.api.clone(),
+        ))
+    });
+    let metrics = Arc::new(Metrics::new());
+    let health = Arc::new(Health::new());
+    let throttle = Arc::new(Throttle::new(&config.rate_limit));
+    let sender = ThrottledBot {
+        bot: bot.clone(),
+        throttle: throttle.clone(),
+    };
+    let scheduler = Scheduler::start(
+        sender,
+        TelegramErrorHandler::new(metrics.clone(), health.clone()),
+    );
+    let ctx = Arc::new(
+        AppContext::new(
+            config,
+            i18n,
+            storage,
+            metrics.clone(),
+            health.clone(),
+            scheduler,
+            throttle,
+        )
+        .with_error_log(error_log),
+    );
+
+    // Меню команд: русское по умолчанию и английское для клиентов на английском
+    let menus = (
+        Command::menu(&ctx.i18n, Lang::Ru),
+        Command::menu(&ctx.i18n, Lang::En),
+    );
+    tokio::spawn({
+        let bot = bot.clone();
+        async move {
+            let results = [
+                bot.set_my_commands(menus.0).await,
+                bot.set_my_commands(menus.1).language_code("en").await,
+            ];
+            for e in results.into_iter().filter_map(Result::err) {
+                warn!(error = %e, "Не удалось зарегистрировать меню команд");
+            }
+        }
+    });
+
+    // Турниры продолжаются по срокам из хранилища, в том числе после перезапуска
+    tokio::spawn(BotHandler::run_tournaments(bot.clone(), ctx.clone()));
+
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    info!("HTTP сервер запускается на порту {}", port);
+    info!(port, "HTTP сервер запускается");
 
-    // Создание роутера с health check endpoint
-    let app = Router::new()
-        .route("/", get(health_check))
-        .route("/health", get(health_check));
+    // Создание роутера с пробами, JSON API (если включено) и метриками
+    let mut app = Router::new()
+        .merge(health::router(ctx.clone()))
+        .merge(metrics::router(metrics.clone()));
+    if let Some(api_state) = api_state {
+        info!("JSON API включено");
+        app = app.merge(api::router(api_state));
+    }
 
-    // Запуск HTTP сервера для health check в отдельной задаче
-    let server_handle = tokio::spawn(async move {
+    // HTTP сервер останавливается последним, после завершения раундов
+    let (stop_server, server_stopped) = tokio::sync::oneshot::channel::<()>();
+
+    // Запуск HTTP сервера для проб, API и метрик в отдельной задаче
+    let mut server_handle = tokio::spawn(async move {
         match tokio::net::TcpListener::bind(addr).await {
             Ok(listener) => {
-                info!("Health check сервер запущен на {}", addr);
-                if let Err(e) = axum::serve(listener, app).await {
-                    error!("Ошибка HTTP сервера: {}", e);
+                info!(%addr, "HTTP сервер запущен");
+                let shutdown = async {
+                    let _ = server_stopped.await;
+                };
+                if let Err(e) = axum::serve(listener, app)
+                    .with_graceful_shutdown(shutdown)
+                    .await
+                {
+                    error!(error = %e, "Ошибка HTTP сервера");
                 }
             }
             Err(e) => {
-                error!("Не удалось привязать адрес {}: {}", addr, e);
+                error!(%addr, error = %e, "Не удалось привязать адрес");
             }
         }
     });
 
-    // Запуск Telegram бота в основной задаче
-    let bot_handle = tokio::spawn(async move {
+    // Запуск Telegram бота в отдельной задаче; Ctrl-C обрабатываем сами
+    let schema = telemetry::instrument(health::track(handler.schema()));
+    let mut dispatcher = Dispatcher::builder(bot.clone(), schema)
+        .dependencies(dptree::deps![ctx.clone()])
+        .error_handler(TelegramErrorHandler::new(metrics.clone(), health.clone()))
+        .build();
+    let dispatcher_token = dispatcher.shutdown_token();
+    let mut bot_handle = tokio::spawn(async move {
         info!("Запуск Telegram бота...");
-        Dispatcher::builder(bot, handler.schema())
-            .dependencies(dptree::deps![])
-            .enable_ctrlc_handler()
-            .build()
-            .dispatch()
+        let _running = health.dispatcher_running();
+        let listener = update_listeners::polling_default(bot).await;
+        let listener_errors = TelegramErrorHandler::listener(metrics, health.clone());
+        dispatcher
+            .dispatch_with_listener(listener, listener_errors)
             .await;
-        info!("Telegram бот завершился");
     });
 
     // Даем время серверу запуститься
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-    info!("Приложение запущено. HTTP сервер работает на порту {}", port);
-
-    // Ожидание завершения любой из задач
-    tokio::select! {
-        result = server_handle => {
-            match result {
-                Ok(_) => info!("HTTP сервер завершился"),
-                Err(e) => error!("Ошибка в HTTP сервере: {}", e),
+    tokio::time::sleep(startup_delay).await;
+    info!(port, "Приложение запущено");
+
+    // Ожидание сигнала завершения или неожиданной остановки одной из задач
+    let bot_finished = tokio::select! {
+        _ = shutdown::signal() => {
+            info!("Получен сигнал завершения");
+            false
+        },
+        result = &mut server_handle => {
+            if let Err(e) = result {
+                error!(error = %e, "Ошибка в HTTP сервере");
             }
+            info!("HTTP сервер завершился");
+            return;
         },
-        result = bot_handle => {
-            match result {
-                Ok(_) => info!("Telegram бот завершился"),
-                Err(e) => error!("Ошибка в Telegram боте: {}", e),
+        result = &mut bot_handle => {
+            if let Err(e) = result {
+                error!(error = %e, "Ошибка в Telegram боте");
             }
+            info!("Telegram бот завершился");
+            true
         }
+    };
+
+    // Новые раунды больше не начинаются; ждем идущие и останавливаем получение обновлений
+    ctx.shutdown.begin();
+    info!(
+        in_flight = ctx.shutdown.in_flight(),
+        grace_ms = shutdown_grace.as_millis() as u64,
+        "Завершение работы: ожидание идущих раундов"
+    );
+    let drain = async {
+        if !bot_finished {
+            match dispatcher_token.shutdown() {
+                Ok(stopped) => stopped.await,
+                Err(_) => bot_handle.abort(),
+            }
+        }
+        ctx.shutdown.wait_idle().await;
+    };
+    if tokio::time::timeout(shutdown_grace, drain).await.is_err() {
+        warn!(
+            in_flight = ctx.shutdown.in_flight(),
+            "Не все раунды завершились до истечения времени ожидания"
+        );
     }
-}
 
-// Health check endpoint для Autoscale deployment
-async fn health_check() -> Result<Html<&'static str>, StatusCode> {
-    Ok(Html("<h1>Telegram Dice Bot is running</h1><p>Status: OK</p>"))
+    if let Err(e) = ctx.storage.flush() {
+        error!(error = %e, "Не удалось сохранить данные при завершении");
+    }
+
+    let _ = stop_server.send(());
+    if let Err(e) = server_handle.await {
+        error!(error = %e, "Ошибка в HTTP сервере");
+    }
+    info!("Приложение остановлено");
 }
@@ -0,0 +1,228 @@
+//! Метрики Prometheus, доступные на `GET /metrics`.
+
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use teloxide::{error_handlers::ErrorHandler, RequestError};
+use tracing::error;
+
+use crate::health::Health;
+use crate::state::GameType;
+
+pub struct Metrics {
+    registry: Registry,
+    commands: IntCounterVec,
+    callbacks: IntCounterVec,
+    rounds: IntCounterVec,
+    round_results: IntCounterVec,
+    dice_faces: IntCounterVec,
+    telegram_errors: IntCounterVec,
+    rate_limited: IntCounterVec,
+    handler_duration: HistogramVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("dice_bot".to_string()), None)
+            .expect("корректный префикс метрик");
+
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels)
+                .expect("корректное описание метрики");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("уникальное имя метрики");
+            counter
+        };
+
+        let commands = counter("commands_total", "Полученные команды", &["command"]);
+        let callbacks = counter("callbacks_total", "Нажатия inline-кнопок", &["kind"]);
+        let rounds = counter("rounds_total", "Сыгранные раунды", &["game"]);
+        let round_results = counter("round_results_total", "Исходы раундов", &["game", "result"]);
+        let dice_faces = counter("dice_faces_total", "Выпавшие значения кубика", &["face"]);
+        let telegram_errors = counter(
+            "telegram_errors_total",
+            "Ошибки запросов к Telegram API",
+            &["kind"],
+        );
+        let rate_limited = counter(
+            "rate_limited_total",
+            "Действия, отклоненные из-за лимита частоты",
+            &["update"],
+        );
+
+        let handler_duration = HistogramVec::new(
+            HistogramOpts::new("handler_duration_seconds", "Время обработки обновления")
+                .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+            &["handler"],
+        )
+        .expect("корректное описание метрики");
+        registry
+            .register(Box::new(handler_duration.clone()))
+            .expect("уникальное имя метрики");
+
+        Self {
+            registry,
+            commands,
+            callbacks,
+            rounds,
+            round_results,
+            dice_faces,
+            telegram_errors,
+            rate_limited,
+            handler_duration,
+        }
+    }
+
+    pub fn command(&self, command: &str) {
+        self.commands.with_label_values(&[command]).inc();
+    }
+
+    /// Нажатие кнопки по виду действия (`play`, `menu`, ...) или `outdated`/`invalid`
+    pub fn callback(&self, kind: &str) {
+        self.callbacks.with_label_values(&[kind]).inc();
+    }
+
+    /// Сыгранный раунд: игра, выпавшее значение и исход
+    pub fn round(&self, game: &GameType, dice: u8, is_win: bool) {
+        let game = game.name();
+        self.rounds.with_label_values(&[game]).inc();
+        let result = if is_win { "win" } else { "loss" };
+        self.round_results.with_label_values(&[game, result]).inc();
+        self.dice_faces
+            .with_label_values(&[&dice.to_string()])
+            .inc();
+    }
+
+    pub fn telegram_error(&self, error: &RequestError) {
+        self.telegram_errors
+            .with_label_values(&[error_kind(error)])
+            .inc();
+    }
+
+    /// Отклоненное лимитом действие: `message` или `callback`
+    pub fn rate_limited(&self, update: &str) {
+        self.rate_limited.with_label_values(&[update]).inc();
+    }
+
+    /// Таймер обработчика; время записывается при уничтожении таймера
+    pub fn handler_timer(&self, handler: &str) -> HistogramTimer {
+        self.handler_duration
+            .with_label_values(&[handler])
+            .start_timer()
+    }
+
+    /// Текстовое представление всех метрик
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("метрики кодируются в текст");
+        String::from_utf8(buffer).expect("метрики в UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Короткое имя вида ошибки Telegram API для меток и проб
+pub fn error_kind(error: &RequestError) -> &'static str {
+    match error {
+        RequestError::Api(_) => "api",
+        RequestError::MigrateToChatId(_) => "migrate_to_chat_id",
+        RequestError::RetryAfter(_) => "retry_after",
+        RequestError::Network(_) => "network",
+        RequestError::InvalidJson { .. } => "invalid_json",
+        RequestError::Io(_) => "io",
+    }
+}
+
+/// Обработчик ошибок диспетчера: логирует ошибку, учитывает ее в метриках и пробах
+pub struct TelegramErrorHandler {
+    metrics: Arc<Metrics>,
+    health: Arc<Health>,
+    listener: bool,
+}
+
+impl TelegramErrorHandler {
+    /// Для ошибок обработчиков обновлений
+    pub fn new(metrics: Arc<Metrics>, health: Arc<Health>) -> Arc<Self> {
+        Arc::new(Self {
+            metrics,
+            health,
+            listener: false,
+        })
+    }
+
+    /// Для ошибок получения обновлений (long polling)
+    pub fn listener(metrics: Arc<Metrics>, health: Arc<Health>) -> Arc<Self> {
+        Arc::new(Self {
+            metrics,
+            health,
+            listener: true,
+        })
+    }
+}
+
+impl ErrorHandler<RequestError> for TelegramErrorHandler {
+    fn handle_error(
+        self: Arc<Self>,
+        error: RequestError,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.metrics.telegram_error(&error);
+        self.health.telegram_error(&error, self.listener);
+        if self.listener {
+            error!(error = %error, "Ошибка получения обновлений от Telegram");
+        } else {
+            error!(error = %error, "Ошибка Telegram API");
+        }
+        Box::pin(async {})
+    }
+}
+
+/// Роутер с эндпоинтом `/metrics`
+pub fn router(metrics: Arc<Metrics>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(metrics)
+}
+
+async fn metrics_handler(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_contains_counters() {
+        let metrics = Metrics::new();
+        metrics.command("play");
+        metrics.callback("play");
+        metrics.round(&GameType::ExactNumber, 6, true);
+        metrics.round(&GameType::ExactNumber, 2, false);
+        drop(metrics.handler_timer("callback"));
+
+        let text = metrics.render();
+        assert!(text.contains("dice_bot_commands_total{command=\"play\"} 1"));
+        assert!(text.contains("dice_bot_callbacks_total{kind=\"play\"} 1"));
+        assert!(text.contains("dice_bot_rounds_total{game=\"exact_number\"} 2"));
+        assert!(
+            text.contains("dice_bot_round_results_total{game=\"exact_number\",result=\"win\"} 1")
+        );
+        assert!(text.contains("dice_bot_dice_faces_total{face=\"6\"} 1"));
+        assert!(text.contains("dice_bot_handler_duration_seconds_count{handler=\"callback\"} 1"));
+    }
+}
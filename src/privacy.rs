@@ -0,0 +1,255 @@
+//! Выгрузка и удаление данных пользователя (`/export`, `/forget_me`).
+//!
+//! Выгрузка — профиль, раунды и журнал операций одним документом JSON или CSV. Раунды
+//! восстанавливаются из журнала: ставка и следующие за ней выплата и джекпот.
+//!
+//! Удаление убирает все записи пользователя в журнале, а в записях других игроков —
+//! ссылки на него (переводы и операции оператора). От профиля остаются только баланс и
+//! ограничения: сроки бонусов, переводы за сутки, лимиты и решения оператора. В
+//! завершенных турнирах пользователь заменяется безымянным участником, чтобы сетка и
+//! призы остались целыми.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ledger::{self, Entry, Kind};
+use crate::storage::UserProfile;
+
+/// Имя удаленного пользователя в сохраненных турнирах
+pub const ANONYMOUS_NAME: &str = "—";
+
+/// Формат выгрузки
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Формат из аргумента `/export`; без аргумента — JSON
+    pub fn parse(value: &str) -> Option<ExportFormat> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Раунд, восстановленный из журнала
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RoundRecord {
+    /// Время (unix, секунды)
+    pub at: u64,
+    pub stake: i64,
+    /// Выплата вместе с джекпотом; 0 — проигрыш
+    pub payout: i64,
+    /// Баланс после раунда
+    pub balance: i64,
+}
+
+/// Раунды пользователя по его записям журнала
+pub fn rounds(entries: &[Entry]) -> Vec<RoundRecord> {
+    let mut rounds: Vec<RoundRecord> = Vec::new();
+    let mut open = false;
+    for entry in entries {
+        match entry.kind {
+            Kind::Bet => {
+                rounds.push(RoundRecord {
+                    at: entry.at,
+                    stake: -entry.amount,
+                    payout: 0,
+                    balance: entry.balance,
+                });
+                open = true;
+            }
+            Kind::Payout | Kind::Jackpot if open => {
+                if let Some(round) = rounds.last_mut() {
+                    round.payout += entry.amount;
+                    round.balance = entry.balance;
+                }
+            }
+            _ => open = false,
+        }
+    }
+    rounds
+}
+
+/// Все данные пользователя
+#[derive(Clone, Debug, Serialize)]
+pub struct Export {
+    pub user_id: u64,
+    /// Время выгрузки (unix, секунды)
+    pub exported_at: u64,
+    pub profile: UserProfile,
+    pub rounds: Vec<RoundRecord>,
+    pub ledger: Vec<Entry>,
+}
+
+impl Export {
+    pub fn new(user_id: u64, profile: UserProfile, ledger: Vec<Entry>, now: u64) -> Self {
+        Self {
+            user_id,
+            exported_at: now,
+            profile,
+            rounds: rounds(&ledger),
+            ledger,
+        }
+    }
+
+    /// Текст документа
+    pub fn render(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            ExportFormat::Csv => self.to_csv(),
+        }
+    }
+
+    /// Три таблицы через пустую строку: поля профиля, раунды и журнал
+    fn to_csv(&self) -> String {
+        let mut rows = vec![
+            "field,value".to_string(),
+            row(&["user_id".to_string(), self.user_id.to_string()]),
+            row(&[
+                "exported_at".to_string(),
+                ledger::format_time(self.exported_at),
+            ]),
+        ];
+        let mut fields = Vec::new();
+        let profile = serde_json::to_value(&self.profile).unwrap_or(Value::Null);
+        flatten("profile", &profile, &mut fields);
+        rows.extend(
+            fields
+                .iter()
+                .map(|(field, value)| row(&[field.clone(), value.clone()])),
+        );
+
+        rows.push(String::new());
+        rows.push("time,stake,payout,balance".to_string());
+        for round in &self.rounds {
+            rows.push(row(&[
+                ledger::format_time(round.at),
+                round.stake.to_string(),
+                round.payout.to_string(),
+                round.balance.to_string(),
+            ]));
+        }
+
+        rows.push(String::new());
+        rows.push("seq,time,kind,amount,balance,peer".to_string());
+        for entry in &self.ledger {
+            rows.push(row(&[
+                entry.seq.to_string(),
+                ledger::format_time(entry.at),
+                entry.kind.name().to_string(),
+                entry.amount.to_string(),
+                entry.balance.to_string(),
+                entry.peer.map(|peer| peer.to_string()).unwrap_or_default(),
+            ]));
+        }
+        rows.push(String::new());
+        rows.join("\n")
+    }
+}
+
+/// Поля вложенных объектов через точку (`profile.bonus.daily_streak`), списки — JSON
+fn flatten(prefix: &str, value: &Value, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&format!("{}.{}", prefix, key), value, fields);
+            }
+        }
+        Value::Null => {}
+        Value::String(text) => fields.push((prefix.to_string(), text.clone())),
+        other => fields.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/// Строка CSV; поля с запятыми, кавычками и переводами строк берутся в кавычки
+fn row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Почему данные не удалены
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForgetError {
+    /// Пользователь участвует в незавершенном турнире: взнос и место в сетке еще нужны
+    Tournament,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(seq: u64, kind: Kind, amount: i64, balance: i64) -> Entry {
+        Entry {
+            seq,
+            at: seq * 60,
+            user_id: 7,
+            kind,
+            amount,
+            balance,
+            peer: None,
+        }
+    }
+
+    #[test]
+    fn test_rounds_from_ledger() {
+        let entries = vec![
+            entry(1, Kind::Opening, 100, 100),
+            entry(2, Kind::Bet, -10, 90),
+            entry(3, Kind::Payout, 20, 110),
+            entry(4, Kind::Jackpot, 50, 160),
+            entry(5, Kind::Bet, -10, 150),
+            entry(6, Kind::Daily, 30, 180),
+            entry(7, Kind::Payout, 99, 279),
+        ];
+        let rounds: Vec<_> = rounds(&entries)
+            .into_iter()
+            .map(|r| (r.stake, r.payout, r.balance))
+            .collect();
+        assert_eq!(rounds, vec![(10, 70, 160), (10, 0, 150)]);
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let mut profile = UserProfile::new(100);
+        profile.username = Some("Bob, \"the\" player".to_string());
+        let export = Export::new(7, profile, vec![entry(1, Kind::Bet, -10, 90)], 0);
+        let csv = export.render(ExportFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[..3],
+            ["field,value", "user_id,7", "exported_at,1970-01-01 00:00"]
+        );
+        assert!(lines.contains(&"profile.username,\"Bob, \"\"the\"\" player\""));
+        assert!(lines.contains(&"profile.bonus.daily_streak,0"));
+        assert!(lines.contains(&"1970-01-01 00:01,10,0,90"));
+        assert_eq!(lines.last(), Some(&"1,1970-01-01 00:01,bet,-10,90,"));
+
+        let json: Value = serde_json::from_str(&export.render(ExportFormat::Json)).unwrap();
+        assert_eq!(json["profile"]["balance"], 100);
+        assert_eq!(json["rounds"][0]["stake"], 10);
+    }
+}
@@ -0,0 +1,363 @@
+//! Ограничение частоты запросов.
+//!
+//! - [`RateLimiter`] — входящие действия: не больше `*_per_minute` кнопок и сообщений от
+//!   одного пользователя и в одном чате (с запасом `*_burst` на короткие серии).
+//! - [`Throttle`] — исходящие сообщения: соблюдает лимиты Telegram на чат и на бота в целом
+//!   и повторяет запрос после ошибки 429 `retry_after`.
+//!
+//! Оба построены на GCRA: для каждого ключа хранится «теоретическое время прибытия»
+//! следующего запроса, так что состояние — одно `Instant` на пользователя или чат.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use teloxide::{
+    payloads::{EditMessageReplyMarkup, EditMessageText, SendDice, SendDocument, SendMessage},
+    requests::{HasPayload, JsonRequest, MultipartRequest, Output, Request},
+    types::{ChatId, Recipient},
+    RequestError,
+};
+use tracing::warn;
+
+use crate::config::RateLimitConfig;
+
+/// После скольких ключей чистить устаревшие записи
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Параметры GCRA: интервал между запросами и допустимая серия
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rate {
+    interval: Duration,
+    burst: u32,
+}
+
+impl Rate {
+    fn per_minute(count: u32, burst: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / count.max(1),
+            burst: burst.max(1),
+        }
+    }
+
+    fn per_second(count: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / count.max(1),
+            burst: 1,
+        }
+    }
+
+    fn tolerance(&self) -> Duration {
+        self.interval * (self.burst - 1)
+    }
+
+    /// Проверка без ожидания: `Err` со временем, через которое запрос будет разрешен
+    fn check(&self, tat: &mut Instant, now: Instant) -> Result<(), Duration> {
+        let base = (*tat).max(now);
+        let allowed_at = base.checked_sub(self.tolerance()).unwrap_or(now);
+        if allowed_at > now {
+            return Err(allowed_at - now);
+        }
+        *tat = base + self.interval;
+        Ok(())
+    }
+
+    /// Резервирование места в очереди: момент, когда можно отправить запрос
+    fn reserve(&self, tat: &mut Instant, now: Instant) -> Instant {
+        let base = (*tat).max(now);
+        let at = base.checked_sub(self.tolerance()).unwrap_or(now).max(now);
+        *tat = base + self.interval;
+        at
+    }
+}
+
+fn prune<K>(map: &mut HashMap<K, Instant>, now: Instant) {
+    if map.len() > PRUNE_THRESHOLD {
+        map.retain(|_, tat| *tat > now);
+    }
+}
+
+/// Отказ из-за превышения лимита
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limited {
+    /// Через сколько можно повторить
+    pub retry_in: Duration,
+    /// Нужно ли сообщить пользователю (один раз за период ожидания)
+    pub notify: bool,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    users: HashMap<u64, Instant>,
+    chats: HashMap<i64, Instant>,
+    notified: HashMap<u64, Instant>,
+}
+
+/// Лимит входящих действий на пользователя и на чат
+pub struct RateLimiter {
+    user: Rate,
+    chat: Rate,
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            user: Rate::per_minute(config.user_per_minute, config.user_burst),
+            chat: Rate::per_minute(config.chat_per_minute, config.chat_burst),
+            state: Mutex::new(LimiterState::default()),
+        }
+    }
+
+    /// Учет действия пользователя в чате; при превышении лимита действие не засчитывается
+    pub fn check(&self, user_id: u64, chat_id: ChatId) -> Result<(), Limited> {
+        self.check_at(user_id, chat_id, Instant::now())
+    }
+
+    fn check_at(&self, user_id: u64, chat_id: ChatId, now: Instant) -> Result<(), Limited> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        prune(&mut state.users, now);
+        prune(&mut state.chats, now);
+        prune(&mut state.notified, now);
+
+        let mut user_tat = state.users.get(&user_id).copied().unwrap_or(now);
+        let mut chat_tat = state.chats.get(&chat_id.0).copied().unwrap_or(now);
+        let result = self
+            .user
+            .check(&mut user_tat, now)
+            .and_then(|()| self.chat.check(&mut chat_tat, now));
+
+        match result {
+            Ok(()) => {
+                state.users.insert(user_id, user_tat);
+                state.chats.insert(chat_id.0, chat_tat);
+                Ok(())
+            }
+            Err(retry_in) => {
+                let notify = state
+                    .notified
+                    .get(&user_id)
+                    .is_none_or(|until| *until <= now);
+                if notify {
+                    state.notified.insert(user_id, now + retry_in);
+                }
+                Err(Limited { retry_in, notify })
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    global: Option<Instant>,
+    chats: HashMap<i64, Instant>,
+}
+
+/// Очередь исходящих запросов с лимитами Telegram
+pub struct Throttle {
+    global: Rate,
+    private: Rate,
+    group: Rate,
+    retry_attempts: u32,
+    state: Mutex<ThrottleState>,
+}
+
+impl Throttle {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            global: Rate::per_second(config.outgoing_per_second),
+            private: Rate::per_minute(config.outgoing_chat_per_minute, config.outgoing_chat_burst),
+            group: Rate::per_minute(config.outgoing_group_per_minute, config.outgoing_chat_burst),
+            retry_attempts: config.retry_attempts,
+            state: Mutex::new(ThrottleState::default()),
+        }
+    }
+
+    /// Момент, когда можно отправить следующее сообщение в чат
+    fn reserve(&self, chat_id: ChatId, now: Instant) -> Instant {
+        let mut state = self.state.lock().unwrap();
+        prune(&mut state.chats, now);
+        // Отрицательные идентификаторы — группы и каналы, у них лимит строже
+        let chat_rate = if chat_id.0 < 0 {
+            self.group
+        } else {
+            self.private
+        };
+        let chat_tat = state.chats.entry(chat_id.0).or_insert(now);
+        let chat_at = chat_rate.reserve(chat_tat, now);
+        let global_tat = state.global.get_or_insert(now);
+        let global_at = self.global.reserve(global_tat, chat_at);
+        global_at.max(chat_at)
+    }
+
+    /// Пауза для всех исходящих запросов после ответа 429
+    fn freeze(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut state = self.state.lock().unwrap();
+        let global = state.global.get_or_insert(until);
+        *global = (*global).max(until);
+    }
+
+    /// Отправка запроса в чат с соблюдением лимитов и повтором после `retry_after`
+    pub async fn send<Q>(&self, request: Q) -> Result<Output<Q>, RequestError>
+    where
+        Q: ChatRequest,
+    {
+        let chat_id = request.chat_id();
+        let mut attempt = 0;
+        loop {
+            let at = self.reserve(chat_id, Instant::now());
+            tokio::time::sleep_until(at.into()).await;
+            match request.send_ref().await {
+                Err(RequestError::RetryAfter(duration)) if attempt < self.retry_attempts => {
+                    attempt += 1;
+                    warn!(
+                        chat_id = chat_id.0,
+                        retry_after_ms = duration.as_millis() as u64,
+                        attempt,
+                        "Telegram ограничил частоту запросов, повторяем"
+                    );
+                    self.freeze(duration);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Запрос, адресованный конкретному чату
+pub trait ChatRequest: Request<Err = RequestError> {
+    fn chat_id(&self) -> ChatId;
+}
+
+fn recipient_chat(recipient: &Recipient) -> ChatId {
+    match recipient {
+        Recipient::Id(chat_id) => *chat_id,
+        // Каналы по имени встречаются редко; считаем их одной группой
+        Recipient::ChannelUsername(_) => ChatId(i64::MIN),
+    }
+}
+
+impl ChatRequest for JsonRequest<SendMessage> {
+    fn chat_id(&self) -> ChatId {
+        recipient_chat(&self.payload_ref().chat_id)
+    }
+}
+
+impl ChatRequest for JsonRequest<SendDice> {
+    fn chat_id(&self) -> ChatId {
+        recipient_chat(&self.payload_ref().chat_id)
+    }
+}
+
+impl ChatRequest for JsonRequest<EditMessageText> {
+    fn chat_id(&self) -> ChatId {
+        recipient_chat(&self.payload_ref().chat_id)
+    }
+}
+
+impl ChatRequest for JsonRequest<EditMessageReplyMarkup> {
+    fn chat_id(&self) -> ChatId {
+        recipient_chat(&self.payload_ref().chat_id)
+    }
+}
+
+impl ChatRequest for MultipartRequest<SendDocument> {
+    fn chat_id(&self) -> ChatId {
+        recipient_chat(&self.payload_ref().chat_id)
+    }
+}
+
+type ThrottledFuture<'a, Q> =
+    Pin<Box<dyn Future<Output = Result<Output<Q>, RequestError>> + Send + 'a>>;
+
+/// `bot.send_message(..).throttled(&ctx.throttle).await` вместо `.await`
+pub trait Throttled: ChatRequest + Send + Sized + 'static {
+    fn throttled(self, throttle: &Throttle) -> ThrottledFuture<'_, Self> {
+        Box::pin(throttle.send(self))
+    }
+}
+
+impl<Q: ChatRequest + Send + 'static> Throttled for Q {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn config() -> RateLimitConfig {
+        RateLimitConfig {
+            user_per_minute: 6,
+            user_burst: 2,
+            chat_per_minute: 60,
+            chat_burst: 10,
+            ..RateLimitConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_user_burst_then_cooldown() {
+        let limiter = RateLimiter::new(&config());
+        let now = Instant::now();
+        let chat = ChatId(1);
+
+        assert_eq!(limiter.check_at(7, chat, now), Ok(()));
+        assert_eq!(limiter.check_at(7, chat, now), Ok(()));
+        let limited = limiter.check_at(7, chat, now).unwrap_err();
+        assert_eq!(limited.retry_in, Duration::from_secs(10));
+        assert!(limited.notify);
+        // Повторное нарушение в тот же период не порождает новое предупреждение
+        assert!(!limiter.check_at(7, chat, now).unwrap_err().notify);
+        // Другой пользователь не страдает
+        assert_eq!(limiter.check_at(8, chat, now), Ok(()));
+        // После ожидания действие снова разрешено
+        assert_eq!(
+            limiter.check_at(7, chat, now + Duration::from_secs(10)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_chat_limit_applies_to_all_users() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            chat_per_minute: 1,
+            chat_burst: 2,
+            ..config()
+        });
+        let now = Instant::now();
+        let chat = ChatId(-100);
+        assert_eq!(limiter.check_at(1, chat, now), Ok(()));
+        assert_eq!(limiter.check_at(2, chat, now), Ok(()));
+        assert!(limiter.check_at(3, chat, now).is_err());
+        assert_eq!(limiter.check_at(3, ChatId(5), now), Ok(()));
+    }
+
+    #[test]
+    fn test_throttle_spaces_messages_per_chat_and_globally() {
+        let throttle = Throttle::new(&RateLimitConfig {
+            outgoing_per_second: 10,
+            outgoing_chat_per_minute: 60,
+            outgoing_group_per_minute: 20,
+            outgoing_chat_burst: 2,
+            ..RateLimitConfig::default()
+        });
+        let now = Instant::now();
+        let private = ChatId(1);
+        let group = ChatId(-1);
+
+        // Серия из двух сообщений уходит сразу (с учетом общего лимита 10/с)
+        assert_eq!(throttle.reserve(private, now), now);
+        assert_eq!(
+            throttle.reserve(private, now),
+            now + Duration::from_millis(100)
+        );
+        // Третье ждет окончания интервала чата
+        assert_eq!(throttle.reserve(private, now), now + Duration::from_secs(1));
+        // В группе интервал 3 секунды
+        throttle.reserve(group, now);
+        throttle.reserve(group, now);
+        assert_eq!(throttle.reserve(group, now), now + Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,109 @@
+//! Учет открытых раундов для идемпотентной обработки кнопок.
+//!
+//! Клавиатура выбора получает номер раунда в данных кнопок (`1:p:even_odd:even:17`). Первое
+//! нажатие закрывает раунд, повторные (двойной тап, старое сообщение) отклоняются.
+//! Состояние держится в памяти: после перезапуска старые кнопки считаются устаревшими.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use teloxide::types::ChatId;
+
+/// Сколько последних раундов помнить
+const CAPACITY: usize = 10_000;
+
+/// Почему нажатие не может начать раунд
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RoundError {
+    /// Раунд уже сыгран
+    AlreadyPlayed,
+    /// Раунд неизвестен: кнопка из старого сообщения или другого чата
+    Expired,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RoundState {
+    Open,
+    Settled,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    rounds: HashMap<u64, (ChatId, RoundState)>,
+    order: VecDeque<u64>,
+}
+
+/// Открытые и сыгранные раунды
+#[derive(Default)]
+pub struct Rounds {
+    registry: Mutex<Registry>,
+}
+
+impl Rounds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Новый раунд в чате; возвращает его номер для данных кнопок
+    pub fn open(&self, chat_id: ChatId) -> u64 {
+        let mut registry = self.registry.lock().unwrap();
+        registry.next_id += 1;
+        let id = registry.next_id;
+        registry.rounds.insert(id, (chat_id, RoundState::Open));
+        registry.order.push_back(id);
+        while registry.order.len() > CAPACITY {
+            if let Some(oldest) = registry.order.pop_front() {
+                registry.rounds.remove(&oldest);
+            }
+        }
+        id
+    }
+
+    /// Закрытие раунда по первому нажатию; повторные нажатия получают ошибку
+    pub fn settle(&self, id: u64, chat_id: ChatId) -> Result<(), RoundError> {
+        let mut registry = self.registry.lock().unwrap();
+        match registry.rounds.get_mut(&id) {
+            Some((chat, state)) if *chat == chat_id => match state {
+                RoundState::Open => {
+                    *state = RoundState::Settled;
+                    Ok(())
+                }
+                RoundState::Settled => Err(RoundError::AlreadyPlayed),
+            },
+            _ => Err(RoundError::Expired),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_round_is_settled_once() {
+        let rounds = Rounds::new();
+        let chat = ChatId(5);
+        let first = rounds.open(chat);
+        let second = rounds.open(chat);
+        assert_ne!(first, second);
+
+        assert_eq!(rounds.settle(first, chat), Ok(()));
+        assert_eq!(rounds.settle(first, chat), Err(RoundError::AlreadyPlayed));
+        // Раунд из другого чата и неизвестный раунд считаются устаревшими
+        assert_eq!(rounds.settle(second, ChatId(6)), Err(RoundError::Expired));
+        assert_eq!(rounds.settle(999, chat), Err(RoundError::Expired));
+        assert_eq!(rounds.settle(second, chat), Ok(()));
+    }
+
+    #[test]
+    fn test_oldest_rounds_are_forgotten() {
+        let rounds = Rounds::new();
+        let chat = ChatId(1);
+        let oldest = rounds.open(chat);
+        for _ in 0..CAPACITY {
+            rounds.open(chat);
+        }
+        assert_eq!(rounds.settle(oldest, chat), Err(RoundError::Expired));
+    }
+}
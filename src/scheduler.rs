@@ -0,0 +1,321 @@
+//! Отложенная доставка сообщений раунда.
+//!
+//! Обработчик раунда не ждет окончания анимации кубика: результат и предложение новой
+//! игры ставятся в очередь с задержкой (`DelayQueue`), а обработчик сразу возвращается.
+//! Очередь обслуживает одна фоновая задача; каждая отправка выполняется в своей задаче,
+//! поэтому медленный запрос к Telegram не задерживает остальные раунды.
+
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::{
+    error_handlers::ErrorHandler,
+    prelude::*,
+    types::{InlineKeyboardMarkup, MessageId, ParseMode},
+    RequestError,
+};
+use tokio::sync::mpsc;
+use tokio_util::time::DelayQueue;
+
+use crate::ratelimit::{Throttle, Throttled};
+use crate::shutdown::RoundGuard;
+
+type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), RequestError>> + Send + 'a>>;
+
+/// Отправка и редактирование текстовых сообщений
+pub trait MessageSender: Send + Sync + 'static {
+    fn send(
+        &self,
+        chat_id: ChatId,
+        text: String,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> SendFuture<'_>;
+
+    fn edit(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        text: String,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> SendFuture<'_>;
+}
+
+/// Бот, отправляющий отложенные сообщения с соблюдением лимитов Telegram. Текст
+/// размечен HTML, как и остальные сообщения бота
+pub struct ThrottledBot {
+    pub bot: Bot,
+    pub throttle: Arc<Throttle>,
+}
+
+impl MessageSender for ThrottledBot {
+    fn send(
+        &self,
+        chat_id: ChatId,
+        text: String,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> SendFuture<'_> {
+        Box::pin(async move {
+            let mut request = self.bot.send_message(chat_id, text);
+            request.parse_mode = Some(ParseMode::Html);
+            request.reply_markup = keyboard.map(Into::into);
+            request.throttled(&self.throttle).await.map(|_| ())
+        })
+    }
+
+    fn edit(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        text: String,
+        keyboard: Option<InlineKeyboardMarkup>,
+    ) -> SendFuture<'_> {
+        Box::pin(async move {
+            let mut request = self.bot.edit_message_text(chat_id, message_id, text);
+            request.parse_mode = Some(ParseMode::Html);
+            request.reply_markup = keyboard;
+            request.throttled(&self.throttle).await.map(|_| ())
+        })
+    }
+}
+
+/// Сообщение, которое нужно отправить позже
+pub struct Delivery {
+    pub chat_id: ChatId,
+    /// Текст в разметке HTML: подставленный в него текст пользователей должен быть
+    /// экранирован (`html::escape`)
+    pub text: String,
+    /// Сообщение, которое нужно отредактировать вместо отправки нового
+    pub edit: Option<MessageId>,
+    /// Кнопки под сообщением
+    pub keyboard: Option<InlineKeyboardMarkup>,
+    /// Следующее сообщение и пауза перед ним (отсчитывается после отправки текущего)
+    pub next: Option<(Duration, Box<Delivery>)>,
+    /// Раунд считается идущим, пока доставка не выполнена
+    pub round: Option<RoundGuard>,
+}
+
+impl Delivery {
+    pub fn new(chat_id: ChatId, text: impl Into<String>) -> Self {
+        Self {
+            chat_id,
+            text: text.into(),
+            edit: None,
+            keyboard: None,
+            next: None,
+            round: None,
+        }
+    }
+
+    /// Редактирование сообщения `message_id` (если задано) вместо отправки нового
+    pub fn editing(mut self, message_id: Option<MessageId>) -> Self {
+        self.edit = message_id;
+        self
+    }
+
+    /// Кнопки под сообщением
+    pub fn with_keyboard(mut self, keyboard: InlineKeyboardMarkup) -> Self {
+        self.keyboard = Some(keyboard);
+        self
+    }
+
+    /// Цепочка: после этого сообщения через `delay` отправить `next`
+    pub fn then(mut self, delay: Duration, next: Delivery) -> Self {
+        self.next = Some((delay, Box::new(next)));
+        self
+    }
+
+    /// Привязка к идущему раунду
+    pub fn with_round(mut self, round: RoundGuard) -> Self {
+        self.round = Some(round);
+        self
+    }
+}
+
+/// Очередь отложенных сообщений
+#[derive(Clone)]
+pub struct Scheduler {
+    tx: mpsc::UnboundedSender<(Duration, Delivery)>,
+}
+
+impl Scheduler {
+    /// Запуск фоновой задачи очереди; ошибки отправки передаются в `errors`
+    pub fn start<S, E>(sender: S, errors: Arc<E>) -> Self
+    where
+        S: MessageSender,
+        E: ErrorHandler<RequestError> + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let scheduler = Self { tx };
+        tokio::spawn(run(scheduler.clone(), rx, Arc::new(sender), errors));
+        scheduler
+    }
+
+    /// Отправка `delivery` через `delay`
+    pub fn schedule(&self, delay: Duration, delivery: Delivery) {
+        if self.tx.send((delay, delivery)).is_err() {
+            tracing::error!("Очередь отложенных сообщений остановлена");
+        }
+    }
+}
+
+async fn run<S, E>(
+    scheduler: Scheduler,
+    mut rx: mpsc::UnboundedReceiver<(Duration, Delivery)>,
+    sender: Arc<S>,
+    errors: Arc<E>,
+) where
+    S: MessageSender,
+    E: ErrorHandler<RequestError> + Send + Sync + 'static,
+{
+    let mut queue = DelayQueue::new();
+    loop {
+        tokio::select! {
+            received = rx.recv() => match received {
+                Some((delay, delivery)) => {
+                    queue.insert(delivery, delay);
+                }
+                None => break,
+            },
+            Some(expired) = poll_fn(|cx| queue.poll_expired(cx)), if !queue.is_empty() => {
+                let delivery = expired.into_inner();
+                let scheduler = scheduler.clone();
+                let sender = sender.clone();
+                let errors = errors.clone();
+                tokio::spawn(async move {
+                    let Delivery {
+                        chat_id,
+                        text,
+                        edit,
+                        keyboard,
+                        next,
+                        round,
+                    } = delivery;
+                    let sent = match edit {
+                        Some(message_id) => sender.edit(chat_id, message_id, text, keyboard).await,
+                        None => sender.send(chat_id, text, keyboard).await,
+                    };
+                    if let Err(e) = sent {
+                        errors.handle_error(e).await;
+                    }
+                    if let Some((delay, next)) = next {
+                        scheduler.schedule(delay, *next);
+                    }
+                    drop(round);
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown::Shutdown;
+    use pretty_assertions::assert_eq;
+    use std::sync::Mutex;
+    use std::time::Instant;
+    use teloxide::error_handlers::LoggingErrorHandler;
+
+    /// Запоминает отправленные сообщения; каждая отправка занимает `latency`
+    #[derive(Clone, Default)]
+    struct Recorder {
+        sent: Arc<Mutex<Vec<(ChatId, String)>>>,
+        latency: Duration,
+    }
+
+    impl MessageSender for Recorder {
+        fn send(
+            &self,
+            chat_id: ChatId,
+            text: String,
+            _keyboard: Option<InlineKeyboardMarkup>,
+        ) -> SendFuture<'_> {
+            Box::pin(async move {
+                tokio::time::sleep(self.latency).await;
+                self.sent.lock().unwrap().push((chat_id, text));
+                Ok(())
+            })
+        }
+
+        fn edit(
+            &self,
+            chat_id: ChatId,
+            message_id: MessageId,
+            text: String,
+            keyboard: Option<InlineKeyboardMarkup>,
+        ) -> SendFuture<'_> {
+            self.send(
+                chat_id,
+                format!("edit {}: {}", message_id.0, text),
+                keyboard,
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_rounds_do_not_block_each_other() {
+        const ROUNDS: i64 = 500;
+        let recorder = Recorder {
+            latency: Duration::from_millis(50),
+            ..Recorder::default()
+        };
+        let scheduler = Scheduler::start(recorder.clone(), LoggingErrorHandler::new());
+        let shutdown = Arc::new(Shutdown::new());
+
+        let started = Instant::now();
+        for chat in 0..ROUNDS {
+            let offer =
+                Delivery::new(ChatId(chat), "again").with_round(shutdown.start_round().unwrap());
+            let result =
+                Delivery::new(ChatId(chat), "result").then(Duration::from_millis(100), offer);
+            scheduler.schedule(Duration::from_millis(200), result);
+        }
+        // Постановка в очередь не ждет доставки
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(shutdown.in_flight(), ROUNDS as usize);
+
+        tokio::time::timeout(Duration::from_secs(5), shutdown.wait_idle())
+            .await
+            .expect("раунды не должны выполняться последовательно");
+        // Последовательно это заняло бы ROUNDS * 400 мс
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let sent = recorder.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2 * ROUNDS as usize);
+        for chat in 0..ROUNDS {
+            let texts: Vec<&str> = sent
+                .iter()
+                .filter(|(id, _)| *id == ChatId(chat))
+                .map(|(_, text)| text.as_str())
+                .collect();
+            assert_eq!(texts, vec!["result", "again"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_can_edit_message() {
+        let recorder = Recorder::default();
+        let scheduler = Scheduler::start(recorder.clone(), LoggingErrorHandler::new());
+        let shutdown = Arc::new(Shutdown::new());
+        let offer = Delivery::new(ChatId(1), "result + again")
+            .editing(Some(MessageId(10)))
+            .with_round(shutdown.start_round().unwrap());
+        let result = Delivery::new(ChatId(1), "result")
+            .editing(Some(MessageId(10)))
+            .then(Duration::from_millis(10), offer);
+        scheduler.schedule(Duration::from_millis(10), result);
+
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait_idle())
+            .await
+            .unwrap();
+        let sent = recorder.sent.lock().unwrap();
+        assert_eq!(
+            *sent,
+            vec![
+                (ChatId(1), "edit 10: result".to_string()),
+                (ChatId(1), "edit 10: result + again".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Настройки чата для администраторов группы (`/settings`).
+//!
+//! Администраторы (проверяются через getChatMember, в личном чате — сам пользователь)
+//! выключают игры, задают пределы ставки, язык бота, режим интерфейса и паузу между
+//! раундами. Настройки хранятся в [`ChatSettings`]; пауза отсчитывается в памяти
+//! ([`Cooldowns`]) и после перезапуска начинается заново.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use teloxide::types::{ChatId, UserId};
+
+use crate::config::UiMode;
+use crate::i18n::Lang;
+use crate::state::GameType;
+use crate::storage::ChatSettings;
+
+/// Наибольшая пауза между раундами, секунды
+pub const MAX_COOLDOWN_SECONDS: u64 = 3600;
+
+/// Изменение настроек из аргументов `/settings`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// `game <игра> on|off`
+    Game(GameType, bool),
+    /// `bet <min> <max>` или `bet off`
+    Stake(Option<(i64, i64)>),
+    /// `lang ru|en|off`
+    Lang(Option<Lang>),
+    /// `ui messages|in_place`
+    Ui(UiMode),
+    /// `cooldown <секунды>`
+    Cooldown(u64),
+}
+
+impl Change {
+    /// Разбор аргументов; `None` — неизвестная настройка или недопустимое значение
+    pub fn parse(args: &str) -> Option<Change> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_lowercase).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["game", name, "on"] => Some(Change::Game(GameType::parse(name)?, true)),
+            ["game", name, "off"] => Some(Change::Game(GameType::parse(name)?, false)),
+            ["bet", "off"] => Some(Change::Stake(None)),
+            ["bet", min, max] => {
+                let (min, max) = (min.parse().ok()?, max.parse().ok()?);
+                (0 < min && min <= max).then_some(Change::Stake(Some((min, max))))
+            }
+            ["lang", "off"] => Some(Change::Lang(None)),
+            ["lang", code] => Lang::parse(code).map(|lang| Change::Lang(Some(lang))),
+            ["ui", mode] => UiMode::parse(mode).map(Change::Ui),
+            ["cooldown", seconds] => {
+                let seconds = seconds.parse().ok()?;
+                (seconds <= MAX_COOLDOWN_SECONDS).then_some(Change::Cooldown(seconds))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, settings: &mut ChatSettings) {
+        match self {
+            Change::Game(game, enabled) => {
+                settings.disabled_games.retain(|disabled| *disabled != game);
+                if !enabled {
+                    settings.disabled_games.push(game);
+                }
+            }
+            Change::Stake(limits) => settings.stake_limits = limits,
+            Change::Lang(lang) => settings.lang = lang,
+            Change::Ui(mode) => settings.ui = Some(mode),
+            Change::Cooldown(seconds) => settings.cooldown_seconds = seconds,
+        }
+    }
+}
+
+/// Время последнего раунда каждого игрока в каждом чате
+#[derive(Default)]
+pub struct Cooldowns {
+    started: Mutex<HashMap<(ChatId, UserId), Instant>>,
+}
+
+impl Cooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Можно ли начать раунд; `Err` — сколько осталось ждать
+    pub fn check(&self, chat_id: ChatId, user_id: UserId, pause: Duration) -> Result<(), Duration> {
+        let started = self.started.lock().unwrap();
+        let left = started
+            .get(&(chat_id, user_id))
+            .and_then(|at| pause.checked_sub(at.elapsed()))
+            .filter(|left| !left.is_zero());
+        match left {
+            Some(left) => Err(left),
+            None => Ok(()),
+        }
+    }
+
+    /// Начало раунда; записи старше наибольшей паузы удаляются
+    pub fn record(&self, chat_id: ChatId, user_id: UserId) {
+        let mut started = self.started.lock().unwrap();
+        let max = Duration::from_secs(MAX_COOLDOWN_SECONDS);
+        started.retain(|_, at| at.elapsed() < max);
+        started.insert((chat_id, user_id), Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_and_apply_changes() {
+        let mut settings = ChatSettings::default();
+        for args in [
+            "game guess_one off",
+            "bet 20 50",
+            "lang EN",
+            "ui in_place",
+            "cooldown 30",
+        ] {
+            Change::parse(args).unwrap().apply(&mut settings);
+        }
+        assert_eq!(settings.disabled_games, vec![GameType::GuessOne]);
+        assert!(!settings.game_enabled(&GameType::GuessOne));
+        assert_eq!(settings.clamp_stake(10), 20);
+        assert_eq!(settings.clamp_stake(100), 50);
+        assert_eq!(settings.lang, Some(Lang::En));
+        assert_eq!(settings.ui, Some(UiMode::InPlace));
+        assert_eq!(settings.cooldown_seconds, 30);
+
+        Change::parse("game guess_one on")
+            .unwrap()
+            .apply(&mut settings);
+        Change::parse("bet off").unwrap().apply(&mut settings);
+        assert!(settings.disabled_games.is_empty());
+        assert_eq!(settings.clamp_stake(100), 100);
+
+        for invalid in [
+            "",
+            "bet 50 20",
+            "bet 0 10",
+            "game roulette off",
+            "cooldown 7200",
+        ] {
+            assert_eq!(Change::parse(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_cooldown_per_chat_and_user() {
+        let cooldowns = Cooldowns::new();
+        let (chat, user) = (ChatId(-100), UserId(7));
+        let pause = Duration::from_secs(60);
+        assert_eq!(cooldowns.check(chat, user, pause), Ok(()));
+        cooldowns.record(chat, user);
+        assert!(cooldowns.check(chat, user, pause).is_err());
+        assert_eq!(cooldowns.check(chat, user, Duration::ZERO), Ok(()));
+        assert_eq!(cooldowns.check(ChatId(-200), user, pause), Ok(()));
+        assert_eq!(cooldowns.check(chat, UserId(8), pause), Ok(()));
+    }
+}
@@ -0,0 +1,135 @@
+//! Согласованное завершение работы.
+//!
+//! По SIGTERM/Ctrl-C бот перестает принимать новые раунды, дожидается (в пределах
+//! `delays.shutdown_grace_ms`) раундов, которые уже идут, и рассылки объявления, и только
+//! после этого останавливается HTTP сервер.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Учет идущих раундов и признак завершения
+#[derive(Default)]
+pub struct Shutdown {
+    draining: AtomicBool,
+    rounds: AtomicUsize,
+    idle: Notify,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Начало завершения: новые раунды больше не начинаются
+    pub fn begin(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Регистрация нового раунда; `None`, если бот уже завершает работу.
+    /// Раунд считается идущим, пока жив guard
+    pub fn start_round(self: &Arc<Self>) -> Option<RoundGuard> {
+        // Сначала учитываем раунд, затем проверяем флаг: иначе `wait_idle` мог бы
+        // увидеть ноль раундов между проверкой и увеличением счетчика
+        self.rounds.fetch_add(1, Ordering::SeqCst);
+        let guard = RoundGuard(self.clone());
+        if self.is_draining() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Число идущих раундов
+    pub fn in_flight(&self) -> usize {
+        self.rounds.load(Ordering::SeqCst)
+    }
+
+    /// Ожидание завершения всех идущих раундов
+    pub async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            if self.in_flight() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Идущий раунд; при уничтожении уменьшает счетчик
+pub struct RoundGuard(Arc<Shutdown>);
+
+impl Drop for RoundGuard {
+    fn drop(&mut self) {
+        if self.0.rounds.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// Ожидание SIGTERM или Ctrl-C
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "Не удалось подписаться на Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Не удалось подписаться на SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_waits_for_in_flight_rounds() {
+        let shutdown = Arc::new(Shutdown::new());
+        let round = shutdown.start_round().unwrap();
+        shutdown.begin();
+
+        // Новые раунды после начала завершения не принимаются и не учитываются
+        assert!(shutdown.start_round().is_none());
+        assert_eq!(shutdown.in_flight(), 1);
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait_idle().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        drop(round);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("ожидание завершилось после последнего раунда")
+            .unwrap();
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+}
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 /// Состояния диалога с пользователем
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum DialogueState {
     /// Начальное состояние
+    #[default]
     Start,
     /// Пользователь выбирает тип игры
     SelectingGameType,
@@ -17,38 +19,130 @@ pub enum DialogueState {
     WaitingForNumber,
 }
 
-impl Default for DialogueState {
-    fn default() -> Self {
-        DialogueState::Start
-    }
-}
-
 /// Типы игр
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GameType {
-    EvenOdd,    // Четное/Нечетное
-    HighLow,    // Больше/Меньше 3.5
+    EvenOdd,     // Четное/Нечетное
+    HighLow,     // Больше/Меньше 3.5
     ExactNumber, // Точное число
-    GuessOne,   // Угадать единицу
+    GuessOne,    // Угадать единицу
+}
+
+impl GameType {
+    pub const ALL: [GameType; 4] = [
+        GameType::EvenOdd,
+        GameType::HighLow,
+        GameType::ExactNumber,
+        GameType::GuessOne,
+    ];
+
+    /// Разбор имени игры (`even_odd`, `high_low`, `exact_number`, `guess_one`)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "even_odd" => Some(GameType::EvenOdd),
+            "high_low" => Some(GameType::HighLow),
+            "exact_number" => Some(GameType::ExactNumber),
+            "guess_one" => Some(GameType::GuessOne),
+            _ => None,
+        }
+    }
+
+    /// Имя игры в конфигурации, API и метриках
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameType::EvenOdd => "even_odd",
+            GameType::HighLow => "high_low",
+            GameType::ExactNumber => "exact_number",
+            GameType::GuessOne => "guess_one",
+        }
+    }
+
+    /// Префикс ключей локализации для игры
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameType::EvenOdd => "even_odd",
+            GameType::HighLow => "high_low",
+            GameType::ExactNumber => "exact",
+            GameType::GuessOne => "guess_one",
+        }
+    }
 }
 
 /// Выбор пользователя в игре "Четное/Нечетное"
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EvenOddChoice {
-    Even,  // Четное
-    Odd,   // Нечетное
+    Even, // Четное
+    Odd,  // Нечетное
 }
 
 /// Выбор пользователя в игре "Больше/Меньше 3.5"
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HighLowChoice {
-    High,  // Больше 3.5 (4-6)
-    Low,   // Меньше 3.5 (1-3)
+    High, // Больше 3.5 (4-6)
+    Low,  // Меньше 3.5 (1-3)
 }
 
 /// Выбор пользователя в игре "Угадать единицу"
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GuessOneChoice {
-    Yes,   // Выпадет единица
-    No,    // Не выпадет единица
+    Yes, // Выпадет единица
+    No,  // Не выпадет единица
+}
+
+/// Выбор пользователя в любой из игр
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "game", content = "choice", rename_all = "snake_case")]
+pub enum GameChoice {
+    EvenOdd(EvenOddChoice),
+    HighLow(HighLowChoice),
+    ExactNumber(u8),
+    GuessOne(GuessOneChoice),
+}
+
+impl GameChoice {
+    /// Тип игры, к которой относится выбор
+    pub fn game_type(&self) -> GameType {
+        match self {
+            GameChoice::EvenOdd(_) => GameType::EvenOdd,
+            GameChoice::HighLow(_) => GameType::HighLow,
+            GameChoice::ExactNumber(_) => GameType::ExactNumber,
+            GameChoice::GuessOne(_) => GameType::GuessOne,
+        }
+    }
+
+    /// Разбор текстового выбора для заданного типа игры
+    /// (`even`/`odd`, `high`/`low`, `1`..`6`, `yes`/`no`)
+    pub fn parse(game: &GameType, input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        match (game, input.as_str()) {
+            (GameType::EvenOdd, "even") => Some(GameChoice::EvenOdd(EvenOddChoice::Even)),
+            (GameType::EvenOdd, "odd") => Some(GameChoice::EvenOdd(EvenOddChoice::Odd)),
+            (GameType::HighLow, "high") => Some(GameChoice::HighLow(HighLowChoice::High)),
+            (GameType::HighLow, "low") => Some(GameChoice::HighLow(HighLowChoice::Low)),
+            (GameType::GuessOne, "yes") => Some(GameChoice::GuessOne(GuessOneChoice::Yes)),
+            (GameType::GuessOne, "no") => Some(GameChoice::GuessOne(GuessOneChoice::No)),
+            (GameType::ExactNumber, number) => match number.parse::<u8>() {
+                Ok(n) if (1..=6).contains(&n) => Some(GameChoice::ExactNumber(n)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Текстовый выбор, обратный [`GameChoice::parse`]
+    pub fn code(&self) -> String {
+        match self {
+            GameChoice::EvenOdd(EvenOddChoice::Even) => "even".to_string(),
+            GameChoice::EvenOdd(EvenOddChoice::Odd) => "odd".to_string(),
+            GameChoice::HighLow(HighLowChoice::High) => "high".to_string(),
+            GameChoice::HighLow(HighLowChoice::Low) => "low".to_string(),
+            GameChoice::ExactNumber(number) => number.to_string(),
+            GameChoice::GuessOne(GuessOneChoice::Yes) => "yes".to_string(),
+            GameChoice::GuessOne(GuessOneChoice::No) => "no".to_string(),
+        }
+    }
 }
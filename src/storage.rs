@@ -0,0 +1,888 @@
+//! Хранилище пользовательских данных в JSON-файле.
+//!
+//! Все данные держатся в памяти и целиком сохраняются на диск после каждого изменения
+//! (через временный файл и `rename`, чтобы не оставить файл в полузаписанном состоянии).
+//! Перед сохранением изменения балансов дописываются в журнал операций ([`Ledger`]).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{error, info};
+
+use crate::achievements::PlayerStats;
+use crate::bonus::{self, BonusState};
+use crate::config::UiMode;
+use crate::game::DEFAULT_STARTING_BALANCE;
+use crate::i18n::Lang;
+use crate::ledger::{Entry, Kind, Ledger, Posting};
+use crate::levels::Skin;
+use crate::limits::LimitsState;
+use crate::privacy::{ForgetError, ANONYMOUS_NAME};
+use crate::state::GameType;
+use crate::tournament::Tournament;
+use crate::transfer::TransferState;
+
+/// Профиль пользователя
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserProfile {
+    /// Выбранный пользователем язык (если не выбран — берется из Telegram)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Lang>,
+    /// Имя пользователя в Telegram, чтобы находить получателя `/give @username`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Баланс фишек; меняется через [`UserProfile::post`]
+    pub balance: i64,
+    /// Ежедневный бонус, кран и банкротства
+    pub bonus: BonusState,
+    /// Статистика раундов для достижений
+    pub stats: PlayerStats,
+    /// Накопленный опыт
+    pub xp: u32,
+    /// Выбранная командой /bet ставка (если не выбрана — базовая)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake: Option<i64>,
+    /// Скин броска
+    pub skin: Skin,
+    /// Полученные достижения в порядке получения
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub achievements: Vec<String>,
+    /// Отправленные за сутки переводы
+    pub transfers: TransferState,
+    /// Ограничения ответственной игры
+    pub limits: LimitsState,
+    /// Начальный баланс уже записан в журнал
+    pub in_ledger: bool,
+    /// Заблокирован оператором: бот не отвечает пользователю
+    pub banned: bool,
+    /// Баланс заморожен оператором: ставки, бонусы, переводы и взносы недоступны
+    pub frozen: bool,
+    /// Данные удалены по `/forget_me`: объявления не приходят, пока пользователь снова
+    /// не напишет боту
+    pub forgotten: bool,
+    /// Проводки, которые хранилище запишет в журнал при сохранении
+    #[serde(skip)]
+    postings: Vec<Posting>,
+}
+
+impl UserProfile {
+    pub fn new(starting_balance: i64) -> Self {
+        Self {
+            lang: None,
+            username: None,
+            balance: starting_balance,
+            bonus: BonusState::default(),
+            stats: PlayerStats::default(),
+            xp: 0,
+            stake: None,
+            skin: Skin::default(),
+            achievements: Vec::new(),
+            transfers: TransferState::default(),
+            limits: LimitsState::default(),
+            in_ledger: false,
+            banned: false,
+            frozen: false,
+            forgotten: false,
+            postings: Vec::new(),
+        }
+    }
+
+    /// Что остается от профиля после удаления данных (`/forget_me`): баланс и все, что
+    /// ограничивает игру, — сроки бонусов, переводы за сутки, лимиты и решения оператора.
+    /// Иначе удаление вернуло бы начальный баланс и сняло ограничения раньше срока
+    pub fn residue(&self) -> UserProfile {
+        UserProfile {
+            bonus: self.bonus.clone(),
+            transfers: self.transfers.clone(),
+            limits: self.limits.clone(),
+            banned: self.banned,
+            frozen: self.frozen,
+            forgotten: true,
+            ..UserProfile::new(self.balance)
+        }
+    }
+
+    /// Изменение баланса с записью в журнал
+    pub fn post(&mut self, kind: Kind, amount: i64) {
+        self.balance += amount;
+        self.postings.push(Posting {
+            kind,
+            amount,
+            peer: None,
+        });
+    }
+
+    /// Изменение баланса, в котором участвует второй пользователь
+    pub fn post_with_peer(&mut self, kind: Kind, amount: i64, peer: u64) {
+        self.balance += amount;
+        self.postings.push(Posting {
+            kind,
+            amount,
+            peer: Some(peer),
+        });
+    }
+}
+
+impl Default for UserProfile {
+    fn default() -> Self {
+        Self::new(DEFAULT_STARTING_BALANCE)
+    }
+}
+
+/// Настройки чата (`/settings`)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSettings {
+    /// Режим интерфейса (если не выбран — берется из конфигурации)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui: Option<UiMode>,
+    /// Язык бота в чате (если не выбран — язык каждого пользователя)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Lang>,
+    /// Игры, выключенные администраторами чата
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disabled_games: Vec<GameType>,
+    /// Наименьшая и наибольшая ставка в чате
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stake_limits: Option<(i64, i64)>,
+    /// Пауза между раундами одного игрока в чате, секунды (0 — без паузы)
+    pub cooldown_seconds: u64,
+}
+
+impl ChatSettings {
+    pub fn game_enabled(&self, game: &GameType) -> bool {
+        !self.disabled_games.contains(game)
+    }
+
+    /// Ставка игрока в пределах чата
+    pub fn clamp_stake(&self, stake: i64) -> i64 {
+        match self.stake_limits {
+            Some((min, max)) => stake.max(min).min(max),
+            None => stake,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct StorageData {
+    #[serde(default)]
+    users: HashMap<u64, UserProfile>,
+    #[serde(default)]
+    chats: HashMap<i64, ChatSettings>,
+    /// Текущий или последний турнир каждого чата
+    #[serde(default)]
+    tournaments: HashMap<i64, Tournament>,
+    /// Фонды джекпота: общий (ключ 0) или по чатам
+    #[serde(default)]
+    jackpots: HashMap<i64, i64>,
+    /// Номер последней записи журнала операций
+    #[serde(default)]
+    ledger_seq: u64,
+    /// Игры, выключенные оператором
+    #[serde(default)]
+    disabled_games: Vec<GameType>,
+}
+
+/// Сводка по всем данным бота для оператора
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overview {
+    pub users: usize,
+    /// Пользователи, сыгравшие хотя бы один раунд
+    pub players: usize,
+    pub rounds: u64,
+    /// Фишки на балансах всех пользователей
+    pub balance: i64,
+    pub banned: usize,
+    pub frozen: usize,
+    /// Чаты с настройками или турнирами
+    pub chats: usize,
+    /// Турниры на регистрации или в игре
+    pub tournaments: usize,
+    /// Сумма фондов джекпота
+    pub jackpots: i64,
+}
+
+/// Профили нескольких пользователей в одном изменении (турнир, перевод)
+pub struct Accounts<'a> {
+    users: &'a mut HashMap<u64, UserProfile>,
+    starting_balance: i64,
+    /// Затронутые пользователи и их балансы до изменения
+    touched: Vec<(u64, i64)>,
+}
+
+impl<'a> Accounts<'a> {
+    fn new(users: &'a mut HashMap<u64, UserProfile>, starting_balance: i64) -> Self {
+        Self {
+            users,
+            starting_balance,
+            touched: Vec::new(),
+        }
+    }
+
+    pub fn user(&mut self, user_id: u64) -> &mut UserProfile {
+        let starting_balance = self.starting_balance;
+        let profile = self
+            .users
+            .entry(user_id)
+            .or_insert_with(|| UserProfile::new(starting_balance));
+        if !self.touched.iter().any(|(id, _)| *id == user_id) {
+            self.touched.push((user_id, profile.balance));
+        }
+        profile
+    }
+
+    /// Два разных профиля сразу; `None`, если это один и тот же пользователь
+    pub fn pair(&mut self, a: u64, b: u64) -> Option<(&mut UserProfile, &mut UserProfile)> {
+        if a == b {
+            return None;
+        }
+        self.user(a);
+        self.user(b);
+        match self.users.get_disjoint_mut([&a, &b]) {
+            [Some(a), Some(b)] => Some((a, b)),
+            _ => None,
+        }
+    }
+}
+
+/// Проводки измененных профилей в записи журнала. Первая запись пользователя — его баланс
+/// до изменения; баланс, измененный без проводки, записывается как `adjustment`
+fn journal(data: &mut StorageData, touched: &[(u64, i64)], now: u64) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for &(user_id, before) in touched {
+        let Some(profile) = data.users.get_mut(&user_id) else {
+            continue;
+        };
+        let mut balance = before;
+        let mut postings = std::mem::take(&mut profile.postings);
+        if !profile.in_ledger {
+            profile.in_ledger = true;
+            balance = 0;
+            postings.insert(
+                0,
+                Posting {
+                    kind: Kind::Opening,
+                    amount: before,
+                    peer: None,
+                },
+            );
+        }
+        let posted: i64 = postings.iter().map(|p| p.amount).sum();
+        if balance + posted != profile.balance {
+            postings.push(Posting {
+                kind: Kind::Adjustment,
+                amount: profile.balance - balance - posted,
+                peer: None,
+            });
+        }
+        for posting in postings {
+            balance += posting.amount;
+            data.ledger_seq += 1;
+            entries.push(Entry {
+                seq: data.ledger_seq,
+                at: now,
+                user_id,
+                kind: posting.kind,
+                amount: posting.amount,
+                balance,
+                peer: posting.peer,
+            });
+        }
+    }
+    entries
+}
+
+pub struct Storage {
+    path: Option<PathBuf>,
+    ledger: Option<Ledger>,
+    starting_balance: i64,
+    data: Mutex<StorageData>,
+    /// Удаления данных идут по очереди: временный файл журнала у них общий
+    io: Mutex<()>,
+}
+
+impl Storage {
+    /// Открытие хранилища; если файла еще нет, начинаем с пустых данных
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let data = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                info!(path = %path.display(), "Файл данных не найден, создаю новый");
+                StorageData::default()
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            ledger: Some(Ledger::beside(&path)),
+            path: Some(path),
+            starting_balance: DEFAULT_STARTING_BALANCE,
+            data: Mutex::new(data),
+            io: Mutex::new(()),
+        })
+    }
+
+    /// Начальный баланс для новых пользователей
+    pub fn with_starting_balance(mut self, starting_balance: i64) -> Self {
+        self.starting_balance = starting_balance;
+        self
+    }
+
+    /// Профиль пользователя (пустой, если пользователь еще не сохранялся)
+    pub fn user(&self, user_id: u64) -> UserProfile {
+        let data = self.data.lock().unwrap();
+        data.users
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| UserProfile::new(self.starting_balance))
+    }
+
+    /// Изменение профиля пользователя с сохранением на диск
+    pub fn update_user<F>(&self, user_id: u64, update: F) -> io::Result<UserProfile>
+    where
+        F: FnOnce(&mut UserProfile),
+    {
+        self.modify_user(user_id, |profile| {
+            update(profile);
+            profile.clone()
+        })
+    }
+
+    /// Проверка и изменение профиля под одной блокировкой: результат `modify` возвращается
+    /// вызывающему, а профиль сохраняется на диск
+    pub fn modify_user<F, R>(&self, user_id: u64, modify: F) -> io::Result<R>
+    where
+        F: FnOnce(&mut UserProfile) -> R,
+    {
+        let mut data = self.data.lock().unwrap();
+        let starting_balance = self.starting_balance;
+        let profile = data
+            .users
+            .entry(user_id)
+            .or_insert_with(|| UserProfile::new(starting_balance));
+        let before = profile.balance;
+        let result = modify(profile);
+        self.commit(&mut data, &[(user_id, before)])?;
+        Ok(result)
+    }
+
+    /// Изменение профилей нескольких пользователей под одной блокировкой
+    pub fn modify_accounts<F, R>(&self, modify: F) -> io::Result<R>
+    where
+        F: FnOnce(&mut Accounts) -> R,
+    {
+        let mut data = self.data.lock().unwrap();
+        let mut accounts = Accounts::new(&mut data.users, self.starting_balance);
+        let result = modify(&mut accounts);
+        let touched = accounts.touched;
+        self.commit(&mut data, &touched)?;
+        Ok(result)
+    }
+
+    /// Заблокирован ли пользователь оператором
+    pub fn is_banned(&self, user_id: u64) -> bool {
+        let data = self.data.lock().unwrap();
+        data.users
+            .get(&user_id)
+            .is_some_and(|profile| profile.banned)
+    }
+
+    /// Выключена ли игра оператором
+    pub fn game_disabled(&self, game: &GameType) -> bool {
+        let data = self.data.lock().unwrap();
+        data.disabled_games.contains(game)
+    }
+
+    /// Включение или выключение игры оператором с сохранением на диск
+    pub fn set_game_enabled(&self, game: &GameType, enabled: bool) -> io::Result<()> {
+        let mut data = self.data.lock().unwrap();
+        data.disabled_games.retain(|disabled| disabled != game);
+        if !enabled {
+            data.disabled_games.push(game.clone());
+        }
+        self.persist(&data)
+    }
+
+    /// Сводка для `/admin_stats`
+    pub fn overview(&self) -> Overview {
+        let data = self.data.lock().unwrap();
+        let chats: HashSet<_> = data.chats.keys().chain(data.tournaments.keys()).collect();
+        let mut overview = Overview {
+            users: data.users.len(),
+            chats: chats.len(),
+            tournaments: data.tournaments.values().filter(|t| !t.is_over()).count(),
+            jackpots: data.jackpots.values().sum(),
+            ..Overview::default()
+        };
+        for profile in data.users.values() {
+            let rounds: u64 = profile
+                .stats
+                .games
+                .values()
+                .map(|s| u64::from(s.rounds))
+                .sum();
+            overview.players += usize::from(rounds > 0);
+            overview.rounds += rounds;
+            overview.balance += profile.balance;
+            overview.banned += usize::from(profile.banned);
+            overview.frozen += usize::from(profile.frozen);
+        }
+        overview
+    }
+
+    /// Получатели объявления оператора: личные чаты незаблокированных пользователей, не
+    /// удалявших свои данные, и групповые чаты, в которых есть настройки или турнир
+    pub fn broadcast_chats(&self) -> Vec<i64> {
+        let data = self.data.lock().unwrap();
+        let users = data
+            .users
+            .iter()
+            .filter(|(_, profile)| !profile.banned && !profile.forgotten)
+            .map(|(user_id, _)| *user_id as i64);
+        let chats = data.chats.keys().chain(data.tournaments.keys()).copied();
+        let mut recipients: Vec<i64> = users.chain(chats).collect();
+        recipients.sort_unstable();
+        recipients.dedup();
+        recipients
+    }
+
+    /// Пользователь по имени в Telegram (без `@`, без учета регистра)
+    pub fn find_username(&self, username: &str) -> Option<u64> {
+        let data = self.data.lock().unwrap();
+        data.users
+            .iter()
+            .find(|(_, profile)| {
+                profile
+                    .username
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(username))
+            })
+            .map(|(user_id, _)| *user_id)
+    }
+
+    /// Все записи журнала пользователя по порядку
+    pub fn ledger_entries(&self, user_id: u64) -> io::Result<Vec<Entry>> {
+        match &self.ledger {
+            Some(ledger) => ledger.entries(user_id),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Удаление данных пользователя: записи в журнале и ссылки на него в чужих записях,
+    /// а от профиля остается только [`UserProfile::residue`]; в завершенных турнирах он
+    /// становится безымянным участником
+    pub fn forget_user(&self, user_id: u64) -> io::Result<Result<(), ForgetError>> {
+        let _io = self.io.lock().unwrap();
+        let in_tournament = |data: &StorageData| {
+            data.tournaments
+                .values()
+                .any(|t| !t.is_over() && t.involves(user_id))
+        };
+        if in_tournament(&self.data.lock().unwrap()) {
+            return Ok(Err(ForgetError::Tournament));
+        }
+        // Журнал переписывается без блокировки данных: обработчики в это время работают,
+        // а дописанные записи переносятся вторым шагом
+        let rewrite = match &self.ledger {
+            Some(ledger) => Some(ledger.rewrite_without(user_id)?),
+            None => None,
+        };
+        let mut data = self.data.lock().unwrap();
+        // Пока журнал переписывался, игрок мог записаться в турнир
+        if in_tournament(&data) {
+            return Ok(Err(ForgetError::Tournament));
+        }
+        // Изменения собираются в копии данных: память меняется, только когда записаны и
+        // файл данных, и журнал
+        let mut next = data.clone();
+        if let Some(profile) = next.users.get_mut(&user_id) {
+            *profile = profile.residue();
+        }
+        for tournament in next.tournaments.values_mut() {
+            tournament.anonymize(user_id, ANONYMOUS_NAME);
+        }
+        self.persist(&next)?;
+        // Журнал заменяется под блокировкой данных, чтобы между заменой и остатком профиля
+        // не дописались новые записи пользователя
+        if let (Some(ledger), Some(rewrite)) = (&self.ledger, rewrite) {
+            match ledger.replace(rewrite) {
+                Ok(removed) => info!(removed, "Записи пользователя удалены из журнала"),
+                Err(e) => {
+                    // Файл данных возвращается к прежнему состоянию, чтобы не разойтись
+                    // с журналом
+                    if let Err(e) = self.persist(&data) {
+                        error!(error = %e, "Не удалось вернуть файл данных после ошибки журнала");
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        *data = next;
+        Ok(Ok(()))
+    }
+
+    /// Последние записи журнала пользователя, от новых к старым
+    pub fn history(&self, user_id: u64, limit: usize) -> io::Result<Vec<Entry>> {
+        match &self.ledger {
+            Some(ledger) => ledger.recent(user_id, limit),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Фонд джекпота (`None`, если он еще ни разу не пополнялся)
+    pub fn jackpot(&self, key: i64) -> Option<i64> {
+        let data = self.data.lock().unwrap();
+        data.jackpots.get(&key).copied()
+    }
+
+    /// Расчет раунда: профиль игрока и фонд джекпота `key` меняются под одной блокировкой,
+    /// чтобы одновременные раунды не потеряли пополнение и не выплатили фонд дважды.
+    /// Новый фонд начинается с `seed`
+    pub fn modify_round<F, R>(&self, user_id: u64, key: i64, seed: i64, modify: F) -> io::Result<R>
+    where
+        F: FnOnce(&mut UserProfile, &mut i64) -> R,
+    {
+        let mut data = self.data.lock().unwrap();
+        let StorageData {
+            users, jackpots, ..
+        } = &mut *data;
+        let profile = users
+            .entry(user_id)
+            .or_insert_with(|| UserProfile::new(self.starting_balance));
+        let before = profile.balance;
+        let pool = jackpots.entry(key).or_insert(seed);
+        let result = modify(profile, pool);
+        self.commit(&mut data, &[(user_id, before)])?;
+        Ok(result)
+    }
+
+    /// Настройки чата (пустые, если чат еще не сохранялся)
+    pub fn chat(&self, chat_id: i64) -> ChatSettings {
+        let data = self.data.lock().unwrap();
+        data.chats.get(&chat_id).cloned().unwrap_or_default()
+    }
+
+    /// Изменение настроек чата с сохранением на диск
+    pub fn update_chat<F>(&self, chat_id: i64, update: F) -> io::Result<ChatSettings>
+    where
+        F: FnOnce(&mut ChatSettings),
+    {
+        let mut data = self.data.lock().unwrap();
+        let settings = data.chats.entry(chat_id).or_default();
+        update(settings);
+        let settings = settings.clone();
+        self.persist(&data)?;
+        Ok(settings)
+    }
+
+    /// Турнир чата (текущий или последний завершенный)
+    pub fn tournament(&self, chat_id: i64) -> Option<Tournament> {
+        let data = self.data.lock().unwrap();
+        data.tournaments.get(&chat_id).cloned()
+    }
+
+    /// Изменение турнира чата вместе с балансами участников под одной блокировкой:
+    /// взносы и призы не расходятся с состоянием турнира даже при одновременных командах
+    pub fn modify_tournament<F, R>(&self, chat_id: i64, modify: F) -> io::Result<R>
+    where
+        F: FnOnce(&mut Option<Tournament>, &mut Accounts) -> R,
+    {
+        let mut data = self.data.lock().unwrap();
+        let mut tournament = data.tournaments.remove(&chat_id);
+        let mut accounts = Accounts::new(&mut data.users, self.starting_balance);
+        let result = modify(&mut tournament, &mut accounts);
+        let touched = accounts.touched;
+        if let Some(tournament) = tournament {
+            data.tournaments.insert(chat_id, tournament);
+        }
+        self.commit(&mut data, &touched)?;
+        Ok(result)
+    }
+
+    /// Чаты, в турнирах которых пора начать игру или следующий раунд
+    pub fn due_tournaments(&self, now: u64) -> Vec<i64> {
+        let data = self.data.lock().unwrap();
+        data.tournaments
+            .iter()
+            .filter(|(_, tournament)| tournament.is_due(now))
+            .map(|(chat_id, _)| *chat_id)
+            .collect()
+    }
+
+    /// Запись текущих данных на диск (при завершении работы)
+    pub fn flush(&self) -> io::Result<()> {
+        let data = self.data.lock().unwrap();
+        self.persist(&data)
+    }
+
+    /// Проверка, что каталог данных доступен на запись
+    pub fn check(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let probe = path.with_extension("probe");
+        std::fs::write(&probe, b"ok")?;
+        std::fs::remove_file(&probe)
+    }
+
+    /// Запись проводок в журнал, затем сохранение данных
+    fn commit(&self, data: &mut StorageData, touched: &[(u64, i64)]) -> io::Result<()> {
+        let entries = journal(data, touched, bonus::now());
+        if let Some(ledger) = &self.ledger {
+            ledger.append(&entries)?;
+        }
+        self.persist(data)
+    }
+
+    fn persist(&self, data: &StorageData) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let bytes = serde_json::to_vec_pretty(data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_user_profile_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join("storage.json");
+
+        let storage = Storage::open(&path).unwrap().with_starting_balance(50);
+        assert_eq!(storage.user(7), UserProfile::new(50));
+        storage.update_user(7, |p| p.lang = Some(Lang::En)).unwrap();
+
+        storage
+            .update_chat(-100, |c| c.ui = Some(UiMode::InPlace))
+            .unwrap();
+
+        let reopened = Storage::open(&path).unwrap();
+        assert_eq!(reopened.chat(-100).ui, Some(UiMode::InPlace));
+        assert_eq!(reopened.chat(-200), ChatSettings::default());
+        assert_eq!(reopened.user(7).lang, Some(Lang::En));
+        assert_eq!(reopened.user(8).lang, None);
+        assert_eq!(reopened.user(7).balance, 50);
+        assert_eq!(reopened.user(8).balance, DEFAULT_STARTING_BALANCE);
+    }
+
+    #[test]
+    fn test_ledger_reconstructs_balances() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+        let storage = Storage::open(&path).unwrap().with_starting_balance(100);
+
+        storage.modify_user(1, |p| p.post(Kind::Bet, -10)).unwrap();
+        storage
+            .modify_accounts(|accounts| {
+                let (alice, bob) = accounts.pair(1, 2).unwrap();
+                alice.post_with_peer(Kind::TransferOut, -30, 2);
+                bob.post_with_peer(Kind::TransferIn, 30, 1);
+            })
+            .unwrap();
+        // Изменение без проводки тоже попадает в журнал
+        storage.update_user(2, |p| p.balance = 7).unwrap();
+        storage.update_user(2, |p| p.lang = Some(Lang::En)).unwrap();
+
+        let ledger = Ledger::beside(&path);
+        for user_id in [1, 2] {
+            let entries = ledger.entries(user_id).unwrap();
+            let replayed: i64 = entries.iter().map(|e| e.amount).sum();
+            assert_eq!(replayed, storage.user(user_id).balance);
+            assert_eq!(entries.last().unwrap().balance, replayed);
+        }
+        let kinds: Vec<Kind> = ledger.entries(2).unwrap().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![Kind::Opening, Kind::TransferIn, Kind::Adjustment]
+        );
+        assert_eq!(ledger.entries(1).unwrap()[2].peer, Some(2));
+
+        let same_user = storage.modify_accounts(|accounts| accounts.pair(1, 1).is_none());
+        assert!(same_user.unwrap());
+    }
+
+    #[test]
+    fn test_operator_state_is_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+        let storage = Storage::open(&path).unwrap().with_starting_balance(100);
+        storage.update_user(1, |p| p.banned = true).unwrap();
+        storage.update_user(2, |p| p.frozen = true).unwrap();
+        storage
+            .update_chat(-100, |c| c.ui = Some(UiMode::InPlace))
+            .unwrap();
+        storage
+            .set_game_enabled(&GameType::GuessOne, false)
+            .unwrap();
+
+        let reopened = Storage::open(&path).unwrap();
+        assert!(reopened.is_banned(1));
+        assert!(!reopened.is_banned(2));
+        assert!(reopened.game_disabled(&GameType::GuessOne));
+        assert!(!reopened.game_disabled(&GameType::EvenOdd));
+        assert_eq!(reopened.broadcast_chats(), vec![-100, 2]);
+        let overview = reopened.overview();
+        assert_eq!(
+            (overview.users, overview.banned, overview.frozen),
+            (2, 1, 1)
+        );
+        assert_eq!((overview.balance, overview.chats), (200, 1));
+
+        reopened
+            .set_game_enabled(&GameType::GuessOne, true)
+            .unwrap();
+        assert!(!reopened.game_disabled(&GameType::GuessOne));
+    }
+
+    #[test]
+    fn test_forget_user_leaves_no_personal_data() {
+        use crate::config::TournamentConfig;
+        use crate::tournament::{Entrant, Format, Stage};
+
+        const FORGOTTEN: u64 = 987_654_321;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+        let storage = Storage::open(&path).unwrap().with_starting_balance(100);
+        storage
+            .update_user(FORGOTTEN, |p| p.username = Some("SecretName".to_string()))
+            .unwrap();
+        storage
+            .modify_user(FORGOTTEN, |p| p.post(Kind::Bet, -10))
+            .unwrap();
+        storage
+            .modify_accounts(|accounts| {
+                let (them, alice) = accounts.pair(FORGOTTEN, 1).unwrap();
+                them.post_with_peer(Kind::TransferOut, -30, 1);
+                alice.post_with_peer(Kind::TransferIn, 30, FORGOTTEN);
+            })
+            .unwrap();
+        let join = |stage| {
+            storage.modify_tournament(-100, |current, accounts| {
+                let config = TournamentConfig::default();
+                let mut tournament = Tournament::new(1, Lang::Ru, Format::Knockout, &config, 0);
+                for (user_id, name) in [(1, "Alice"), (FORGOTTEN, "Secret Name")] {
+                    let entrant = Entrant {
+                        user_id,
+                        name: name.to_string(),
+                    };
+                    tournament
+                        .join(entrant, accounts.user(user_id), 32)
+                        .unwrap();
+                }
+                tournament.stage = stage;
+                *current = Some(tournament);
+            })
+        };
+
+        // Незавершенный турнир не дает удалить данные
+        join(Stage::Registration).unwrap();
+        assert_eq!(
+            storage.forget_user(FORGOTTEN).unwrap(),
+            Err(ForgetError::Tournament)
+        );
+        join(Stage::Finished).unwrap();
+        let until = bonus::now() + 3600;
+        let before = storage
+            .update_user(FORGOTTEN, |p| {
+                p.limits.loss_limit = Some(50);
+                p.limits.excluded_until = Some(until);
+                p.bonus.bankrupt_at = Some(until - 7200);
+            })
+            .unwrap();
+
+        assert_eq!(storage.forget_user(FORGOTTEN).unwrap(), Ok(()));
+        assert_eq!(storage.find_username("SecretName"), None);
+        assert!(storage.ledger_entries(FORGOTTEN).unwrap().is_empty());
+        let tournament = storage.tournament(-100).unwrap();
+        assert_eq!(tournament.entrants[1].name, ANONYMOUS_NAME);
+        assert!(!tournament.involves(FORGOTTEN));
+        assert!(!storage.broadcast_chats().contains(&(FORGOTTEN as i64)));
+        assert!(storage.broadcast_chats().contains(&1));
+        // Баланс, сроки бонусов и ограничения остаются, остальное удалено
+        let residue = storage.user(FORGOTTEN);
+        assert_eq!(residue, before.residue());
+        assert_eq!(residue.balance, before.balance);
+        assert_eq!(residue.limits.loss_limit, Some(50));
+        assert_eq!(residue.limits.excluded_until, Some(until));
+        assert_eq!(residue.bonus.bankrupt_at, Some(until - 7200));
+        assert_eq!(
+            (residue.username, residue.stats),
+            (None, PlayerStats::default())
+        );
+
+        // Ни в файле данных, ни в журнале не осталось имени, а id — только у остатка
+        // профиля
+        let ledger_path = path.with_extension("ledger.jsonl");
+        for file in [&path, &ledger_path] {
+            let text = std::fs::read_to_string(file).unwrap();
+            assert!(!text.contains("Secret"), "{}", file.display());
+        }
+        let ledger_text = std::fs::read_to_string(&ledger_path).unwrap();
+        assert!(!ledger_text.contains(&FORGOTTEN.to_string()));
+        let data_text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(data_text.matches(&FORGOTTEN.to_string()).count(), 1);
+        // Баланс второго участника перевода по-прежнему сходится с журналом
+        let entries = storage.ledger_entries(1).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.amount).sum::<i64>(),
+            storage.user(1).balance
+        );
+        assert!(entries.iter().all(|e| e.peer.is_none()));
+
+        // Следующее изменение баланса начинает журнал остатка профиля заново
+        storage
+            .modify_user(FORGOTTEN, |p| p.post(Kind::Bet, -10))
+            .unwrap();
+        let entries = storage.ledger_entries(FORGOTTEN).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.amount).sum::<i64>(),
+            before.balance - 10
+        );
+    }
+
+    #[test]
+    fn test_forget_user_keeps_data_when_ledger_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+        let storage = Storage::open(&path).unwrap();
+        storage
+            .update_user(1, |p| p.username = Some("alice".to_string()))
+            .unwrap();
+        // Журнал, который нельзя прочитать: на его месте каталог
+        let ledger_path = path.with_extension("ledger.jsonl");
+        std::fs::remove_file(&ledger_path).unwrap();
+        std::fs::create_dir(&ledger_path).unwrap();
+
+        assert!(storage.forget_user(1).is_err());
+        assert_eq!(storage.find_username("alice"), Some(1));
+        let reopened = Storage::open(&path).unwrap();
+        assert_eq!(reopened.find_username("alice"), Some(1));
+    }
+
+    #[test]
+    fn test_corrupted_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+        std::fs::write(&path, "not json").unwrap();
+        let error = Storage::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,234 @@
+//! Логирование через `tracing`: текстовый или JSON-формат и span на каждое обновление.
+//! Последние ошибки и предупреждения дополнительно хранятся в памяти ([`ErrorLog`]) и
+//! доступны операторам бота командой `/admin_errors`.
+
+use dptree::{di::DependencySupplier, HandlerDescription};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use teloxide::{
+    dispatching::{DpHandlerDescription, UpdateHandler},
+    prelude::*,
+    types::UpdateKind,
+    RequestError,
+};
+use tracing::{
+    field::{self, Field, Visit},
+    info_span, Event, Instrument, Level, Span, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, EnvFilter, Layer};
+
+use crate::bonus;
+
+/// Сколько последних ошибок хранит [`ErrorLog`]
+pub const ERROR_LOG_CAPACITY: usize = 20;
+
+/// Формат логов
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Человекочитаемый текст (для разработки)
+    #[default]
+    Text,
+    /// Одна JSON-запись на строку (для сбора логов в продакшене)
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Ошибка или предупреждение из лога
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorRecord {
+    /// Время (unix, секунды)
+    pub at: u64,
+    pub level: Level,
+    /// Сообщение и поля записи: `Ошибка Telegram API error=...`
+    pub message: String,
+}
+
+/// Последние [`ERROR_LOG_CAPACITY`] ошибок и предупреждений
+#[derive(Default)]
+pub struct ErrorLog {
+    records: Mutex<VecDeque<ErrorRecord>>,
+}
+
+impl ErrorLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, record: ErrorRecord) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() == ERROR_LOG_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Записи от новых к старым
+    pub fn recent(&self) -> Vec<ErrorRecord> {
+        self.records.lock().unwrap().iter().rev().cloned().collect()
+    }
+}
+
+/// Слой `tracing`, который складывает ошибки и предупреждения в [`ErrorLog`]
+struct ErrorCapture(Arc<ErrorLog>);
+
+impl<S: Subscriber> Layer<S> for ErrorCapture {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.0.push(ErrorRecord {
+            at: bonus::now(),
+            level,
+            message: visitor.text(),
+        });
+    }
+}
+
+/// Сообщение записи и ее поля `имя=значение`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl MessageVisitor {
+    fn text(self) -> String {
+        std::iter::once(self.message)
+            .chain(self.fields)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            name => self.fields.push(format!("{}={:?}", name, value)),
+        }
+    }
+}
+
+/// Инициализация подписчика; уровни задаются через `RUST_LOG` (по умолчанию `info`).
+/// Записи крейтов, использующих `log` (например, teloxide), тоже попадают в вывод,
+/// а ошибки и предупреждения — еще и в `errors`.
+pub fn init(format: LogFormat, errors: Arc<ErrorLog>) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (text, json) = match format {
+        LogFormat::Text => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(false),
+            ),
+        ),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(text)
+        .with(json)
+        .with(ErrorCapture(errors))
+        .init();
+}
+
+/// Span обновления с идентификаторами обновления, пользователя, чата и данными кнопки
+pub fn update_span(update: &Update) -> Span {
+    let span = info_span!(
+        "update",
+        update_id = update.id,
+        user_id = field::Empty,
+        chat_id = field::Empty,
+        callback_data = field::Empty,
+    );
+    if let Some(user) = update.user() {
+        span.record("user_id", user.id.0);
+    }
+    if let Some(chat) = update.chat() {
+        span.record("chat_id", chat.id.0);
+    }
+    if let UpdateKind::CallbackQuery(query) = &update.kind {
+        if let Some(data) = &query.data {
+            span.record("callback_data", data.as_str());
+        }
+    }
+    span
+}
+
+/// Обертка над схемой обработчиков: вся обработка обновления выполняется внутри его span
+pub fn instrument(handler: UpdateHandler<RequestError>) -> UpdateHandler<RequestError> {
+    dptree::from_fn_with_description(
+        DpHandlerDescription::entry(),
+        |deps: DependencyMap, cont| async move {
+            // Диспетчер всегда кладет обновление в зависимости
+            let update: Arc<Update> = deps.get();
+            let span = update_span(&update);
+            cont(deps).instrument(span).await
+        },
+    )
+    .chain(handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse(" Text "), Some(LogFormat::Text));
+        assert_eq!(LogFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_error_log_keeps_recent_errors() {
+        let log = Arc::new(ErrorLog::new());
+        let subscriber = tracing_subscriber::registry().with(ErrorCapture(log.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("Раунд сыгран");
+            tracing::error!(chat_id = 5, error = %"timeout", "Ошибка Telegram API");
+        });
+        let recent = log.recent();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].level, Level::ERROR);
+        assert_eq!(
+            recent[0].message,
+            "Ошибка Telegram API chat_id=5 error=timeout"
+        );
+
+        for index in 0..ERROR_LOG_CAPACITY {
+            log.push(ErrorRecord {
+                at: index as u64,
+                level: Level::WARN,
+                message: String::new(),
+            });
+        }
+        let recent = log.recent();
+        assert_eq!(recent.len(), ERROR_LOG_CAPACITY);
+        assert_eq!(recent[0].at, ERROR_LOG_CAPACITY as u64 - 1);
+        assert!(recent.iter().all(|record| record.level == Level::WARN));
+    }
+}
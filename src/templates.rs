@@ -0,0 +1,217 @@
+//! Переопределение текстов бота из файла шаблонов.
+//!
+//! Файл в формате TOML, секции — языки, ключи — те же, что в каталогах `i18n`:
+//!
+//! ```toml
+//! [ru]
+//! "start.welcome" = "🎲 Добро пожаловать в наше казино! Баланс: {balance} 🪙"
+//!
+//! [ru.pool]
+//! win = ["Есть!", "В точку!"]
+//! ```
+//!
+//! Файл проверяется при запуске: неизвестные языки, ключи и подстановки собираются
+//! в один список ошибок.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::i18n::{self, I18n, Lang};
+
+/// Ошибки проверки файла шаблонов
+#[derive(Debug, PartialEq)]
+pub struct TemplateErrors {
+    pub file: String,
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for TemplateErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Ошибки в файле шаблонов {}:", self.file)?;
+        for error in &self.errors {
+            writeln!(f, "  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TemplateErrors {}
+
+/// Загрузка файла шаблонов поверх встроенных каталогов; возвращает число замененных ключей
+pub fn load_file(i18n: &mut I18n, path: &Path) -> Result<usize, TemplateErrors> {
+    let file = path.display().to_string();
+    let content = std::fs::read_to_string(path).map_err(|e| TemplateErrors {
+        file: file.clone(),
+        errors: vec![format!("не удалось прочитать файл: {}", e)],
+    })?;
+    apply(i18n, &content).map_err(|errors| TemplateErrors { file, errors })
+}
+
+/// Проверка и применение шаблонов из TOML-строки
+pub fn apply(i18n: &mut I18n, content: &str) -> Result<usize, Vec<String>> {
+    let root: toml::Table = content
+        .parse()
+        .map_err(|e: toml::de::Error| vec![format!("синтаксическая ошибка TOML: {}", e)])?;
+
+    let known_keys: HashSet<&str> = i18n::builtin_keys().collect();
+    let mut overrides = Vec::new();
+    let mut errors = Vec::new();
+
+    for (section, value) in &root {
+        let Some(lang) = Lang::parse(section) else {
+            errors.push(format!(
+                "[{}]: неизвестный язык (допустимы: ru, en)",
+                section
+            ));
+            continue;
+        };
+        let Some(table) = value.as_table() else {
+            errors.push(format!("{}: ожидается таблица [{}]", section, section));
+            continue;
+        };
+
+        let mut entries = Vec::new();
+        flatten(table, "", &mut entries);
+
+        for (key, value) in entries {
+            let location = format!("[{}] {}", section, key);
+            if !known_keys.contains(key.as_str()) {
+                errors.push(format!("{}: неизвестный ключ", location));
+                continue;
+            }
+            let values = match value {
+                toml::Value::String(text) => vec![text.clone()],
+                toml::Value::Array(items) if key.starts_with("pool.") => {
+                    let texts: Vec<String> = items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(str::to_string))
+                        .collect();
+                    if texts.len() != items.len() || texts.is_empty() {
+                        errors.push(format!("{}: ожидается непустой список строк", location));
+                        continue;
+                    }
+                    texts
+                }
+                _ => {
+                    errors.push(format!("{}: ожидается строка", location));
+                    continue;
+                }
+            };
+
+            let allowed = i18n::placeholders(&key);
+            for text in &values {
+                for name in i18n::template_placeholders(text) {
+                    if !allowed.contains(&name) {
+                        errors.push(format!(
+                            "{}: неизвестная подстановка {{{}}} (допустимы: {})",
+                            location,
+                            name,
+                            describe(allowed)
+                        ));
+                    }
+                }
+            }
+            overrides.push((lang, key, values));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let count = overrides.len();
+    for (lang, key, values) in overrides {
+        i18n.set_override(lang, &key, values);
+    }
+    Ok(count)
+}
+
+/// Разворачивание вложенных таблиц в ключи через точку
+fn flatten<'a>(table: &'a toml::Table, prefix: &str, out: &mut Vec<(String, &'a toml::Value)>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            toml::Value::Table(nested) => flatten(nested, &key, out),
+            other => out.push((key, other)),
+        }
+    }
+}
+
+fn describe(allowed: &[&str]) -> String {
+    if allowed.is_empty() {
+        "нет".to_string()
+    } else {
+        allowed
+            .iter()
+            .map(|name| format!("{{{}}}", name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_overrides_are_applied() {
+        let mut i18n = I18n::new();
+        let content = r#"
+            [ru]
+            "start.welcome" = "Привет! У вас {balance} 🪙"
+
+            [en.pool]
+            win = ["Yes!", "Nailed it!"]
+
+            [en.exact]
+            lose = "Rolled {dice}, you said {choice}. Balance: {balance}"
+        "#;
+        assert_eq!(apply(&mut i18n, content), Ok(3));
+        assert_eq!(
+            i18n.format(Lang::Ru, "start.welcome", &[("balance", &90)]),
+            "Привет! У вас 90 🪙"
+        );
+        assert!(["Yes!", "Nailed it!"].contains(&i18n.random(Lang::En, "pool.win", &[]).as_str()));
+        // Не переопределенные строки остаются встроенными
+        assert_eq!(i18n.text(Lang::En, "menu.exact"), "🎯 Exact number");
+    }
+
+    #[test]
+    fn test_all_problems_are_reported() {
+        let mut i18n = I18n::new();
+        let content = r#"
+            [de]
+            "start.welcome" = "Hallo"
+
+            [ru]
+            "start.welcome" = "Баланс {balanse}"
+            "menu.unknown" = "?"
+            "help.body" = ["a", "b"]
+            "level.unlock_game" = "{game}: {rounds}"
+        "#;
+        let errors = apply(&mut i18n, content).unwrap_err();
+        assert_eq!(errors.len(), 5, "{:#?}", errors);
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("{balanse}") && e.contains("{balance}")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("level.unlock_game") && e.contains("{rounds}")));
+        // При ошибках ничего не применяется
+        assert!(i18n
+            .text(Lang::Ru, "start.welcome")
+            .starts_with("🎲 Привет!"));
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let errors = apply(&mut I18n::new(), "[ru\n").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("синтаксическая ошибка TOML"));
+    }
+}
@@ -0,0 +1,631 @@
+//! Турниры в чатах.
+//!
+//! Турнир создается командой `/tournament create`, после чего в чате открывается
+//! регистрация со взносом `tournament.entry_fee`. В назначенное время (или по
+//! `/tournament start`) участники распределяются по сетке: олимпийская система
+//! (проигравший выбывает) или круговая (каждый с каждым). Матч — дуэль на кубиках:
+//! у кого выпало больше, тот и победил, ничья переигрывается.
+//!
+//! Все сроки хранятся как время unix в самом турнире, а турнир — в хранилище, поэтому
+//! после перезапуска бот продолжает с того же места: просроченный раунд просто
+//! играется на ближайшей проверке. Взносы образуют призовой фонд, который делится
+//! между местами по долям `tournament.prize_shares`.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::TournamentConfig;
+use crate::i18n::Lang;
+use crate::ledger::Kind;
+use crate::limits::LimitError;
+use crate::storage::UserProfile;
+
+/// Система проведения
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// Олимпийская система: проигравший выбывает
+    #[default]
+    Knockout,
+    /// Круговая система: каждый играет с каждым
+    RoundRobin,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Option<Format> {
+        match value.trim().to_lowercase().as_str() {
+            "knockout" | "ko" => Some(Format::Knockout),
+            "round_robin" | "rr" => Some(Format::RoundRobin),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Knockout => "knockout",
+            Format::RoundRobin => "round_robin",
+        }
+    }
+}
+
+/// Стадия турнира
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Registration,
+    Running,
+    Finished,
+    /// Не набралось участников, взносы возвращены
+    Cancelled,
+}
+
+/// Участник турнира
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entrant {
+    pub user_id: u64,
+    pub name: String,
+}
+
+/// Матч сетки; `away == None` — участник проходит дальше без соперника
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Match {
+    pub round: u32,
+    pub home: u64,
+    pub away: Option<u64>,
+    /// Решающий бросок (хозяин, гость)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<(u8, u8)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winner: Option<u64>,
+}
+
+impl Match {
+    fn new(round: u32, home: u64, away: Option<u64>) -> Self {
+        Self {
+            round,
+            home,
+            away,
+            score: None,
+            // Без соперника победа засчитывается сразу
+            winner: away.is_none().then_some(home),
+        }
+    }
+
+    fn loser(&self) -> Option<u64> {
+        let away = self.away?;
+        match self.winner? {
+            winner if winner == self.home => Some(away),
+            _ => Some(self.home),
+        }
+    }
+}
+
+/// Почему действие с турниром не выполнено
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TournamentError {
+    /// В чате уже идет турнир
+    AlreadyExists,
+    /// В чате нет турнира
+    NotFound,
+    /// Регистрация уже закрыта
+    RegistrationClosed,
+    AlreadyJoined,
+    Full,
+    /// Не хватает фишек на взнос
+    NotEnoughChips {
+        fee: i64,
+    },
+    /// Баланс заморожен оператором
+    Frozen,
+    /// Взнос не пропускают ограничения игрока (`/limits`)
+    Limit(LimitError),
+    /// Для старта нужно хотя бы два участника
+    NotEnoughPlayers,
+    /// Действие доступно только создателю турнира или администраторам чата
+    NotAllowed,
+}
+
+impl TournamentError {
+    /// Суффикс ключа строки с текстом ошибки
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TournamentError::AlreadyExists => "exists",
+            TournamentError::NotFound => "not_found",
+            TournamentError::RegistrationClosed => "closed",
+            TournamentError::AlreadyJoined => "joined",
+            TournamentError::Full => "full",
+            TournamentError::NotEnoughChips { .. } => "no_chips",
+            TournamentError::Frozen => "frozen",
+            TournamentError::Limit(_) => "limit",
+            TournamentError::NotEnoughPlayers => "not_enough",
+            TournamentError::NotAllowed => "not_allowed",
+        }
+    }
+}
+
+/// Начисление фишек участнику: возврат взноса или приз
+pub type Payout = (u64, i64);
+
+/// Турнир одного чата
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tournament {
+    pub format: Format,
+    pub stage: Stage,
+    pub creator: u64,
+    /// Язык объявлений в чате (язык создателя)
+    pub lang: Lang,
+    pub entry_fee: i64,
+    /// Доли призового фонда по местам, в процентах (из конфигурации на момент создания)
+    pub shares: Vec<u32>,
+    /// Время (unix, секунды) автоматического старта
+    pub starts_at: u64,
+    /// Время следующего раунда, пока турнир идет
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_round_at: Option<u64>,
+    /// Текущий раунд (с 1; 0 — турнир еще не начался)
+    pub round: u32,
+    pub entrants: Vec<Entrant>,
+    pub matches: Vec<Match>,
+    /// Выплаченные призы, когда турнир завершен
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prizes: Vec<Payout>,
+}
+
+impl Tournament {
+    /// Новый турнир с регистрацией до `starts_at`
+    pub fn new(
+        creator: u64,
+        lang: Lang,
+        format: Format,
+        config: &TournamentConfig,
+        starts_at: u64,
+    ) -> Self {
+        Self {
+            format,
+            stage: Stage::Registration,
+            creator,
+            lang,
+            entry_fee: config.entry_fee,
+            shares: config.prize_shares.clone(),
+            starts_at,
+            next_round_at: None,
+            round: 0,
+            entrants: Vec::new(),
+            matches: Vec::new(),
+            prizes: Vec::new(),
+        }
+    }
+
+    /// Турнир завершен (или отменен), и на его месте можно создать новый
+    pub fn is_over(&self) -> bool {
+        matches!(self.stage, Stage::Finished | Stage::Cancelled)
+    }
+
+    /// Призовой фонд из взносов
+    pub fn pool(&self) -> i64 {
+        self.entry_fee * self.entrants.len() as i64
+    }
+
+    pub fn name(&self, user_id: u64) -> &str {
+        self.entrants
+            .iter()
+            .find(|e| e.user_id == user_id)
+            .map_or("?", |e| e.name.as_str())
+    }
+
+    /// Участвует ли пользователь (или создал ли турнир)
+    pub fn involves(&self, user_id: u64) -> bool {
+        self.creator == user_id || self.entrants.iter().any(|e| e.user_id == user_id)
+    }
+
+    /// Замена удаленного пользователя безымянным участником. Новый id не пересекается с
+    /// id Telegram (они укладываются в 52 бита) и различается у разных удаленных
+    pub fn anonymize(&mut self, user_id: u64, name: &str) {
+        let position = self.entrants.iter().position(|e| e.user_id == user_id);
+        let alias = u64::MAX - position.unwrap_or(self.entrants.len()) as u64;
+        let replace = |id: &mut u64| {
+            if *id == user_id {
+                *id = alias;
+            }
+        };
+        replace(&mut self.creator);
+        for entrant in &mut self.entrants {
+            if entrant.user_id == user_id {
+                entrant.user_id = alias;
+                entrant.name = name.to_string();
+            }
+        }
+        for m in &mut self.matches {
+            replace(&mut m.home);
+            m.away.iter_mut().for_each(replace);
+            m.winner.iter_mut().for_each(replace);
+        }
+        for (id, _) in &mut self.prizes {
+            replace(id);
+        }
+    }
+
+    /// Регистрация участника со списанием взноса
+    pub fn join(
+        &mut self,
+        entrant: Entrant,
+        profile: &mut UserProfile,
+        max_players: usize,
+    ) -> Result<(), TournamentError> {
+        if self.stage != Stage::Registration {
+            return Err(TournamentError::RegistrationClosed);
+        }
+        if self.entrants.iter().any(|e| e.user_id == entrant.user_id) {
+            return Err(TournamentError::AlreadyJoined);
+        }
+        if self.entrants.len() >= max_players {
+            return Err(TournamentError::Full);
+        }
+        if profile.frozen {
+            return Err(TournamentError::Frozen);
+        }
+        if profile.balance < self.entry_fee {
+            return Err(TournamentError::NotEnoughChips {
+                fee: self.entry_fee,
+            });
+        }
+        profile.post(Kind::TournamentFee, -self.entry_fee);
+        self.entrants.push(entrant);
+        Ok(())
+    }
+
+    /// Пора ли выполнить следующий шаг: старт или очередной раунд
+    pub fn is_due(&self, now: u64) -> bool {
+        match self.stage {
+            Stage::Registration => now >= self.starts_at,
+            Stage::Running => self.next_round_at.is_some_and(|at| now >= at),
+            Stage::Finished | Stage::Cancelled => false,
+        }
+    }
+
+    /// Старт: жеребьевка и сетка первого раунда (для круговой системы — все раунды)
+    pub fn start(
+        &mut self,
+        now: u64,
+        round_delay: Duration,
+        rng: &mut impl Rng,
+    ) -> Result<(), TournamentError> {
+        if self.stage != Stage::Registration {
+            return Err(TournamentError::RegistrationClosed);
+        }
+        if self.entrants.len() < 2 {
+            return Err(TournamentError::NotEnoughPlayers);
+        }
+        let mut seeds: Vec<u64> = self.entrants.iter().map(|e| e.user_id).collect();
+        seeds.shuffle(rng);
+        self.matches = match self.format {
+            Format::Knockout => pair_up(1, &seeds),
+            Format::RoundRobin => round_robin(&seeds),
+        };
+        self.stage = Stage::Running;
+        self.round = 1;
+        self.next_round_at = Some(now + round_delay.as_secs());
+        Ok(())
+    }
+
+    /// Отмена, если к старту не набралось участников; возвращает взносы к возврату
+    pub fn cancel(&mut self) -> Vec<Payout> {
+        self.stage = Stage::Cancelled;
+        self.next_round_at = None;
+        if self.entry_fee == 0 {
+            return Vec::new();
+        }
+        self.entrants
+            .iter()
+            .map(|e| (e.user_id, self.entry_fee))
+            .collect()
+    }
+
+    /// Матчи раунда
+    pub fn round_matches(&self, round: u32) -> impl Iterator<Item = &Match> {
+        self.matches.iter().filter(move |m| m.round == round)
+    }
+
+    /// Число раундов, известное на данный момент
+    pub fn rounds(&self) -> u32 {
+        self.matches.iter().map(|m| m.round).max().unwrap_or(0)
+    }
+
+    /// Сколько всего будет раундов
+    pub fn total_rounds(&self) -> u32 {
+        match self.format {
+            // Каждый раунд оставляет половину участников (с округлением вверх)
+            Format::Knockout => {
+                let mut left = self.entrants.len();
+                let mut rounds = 0;
+                while left > 1 {
+                    left = left.div_ceil(2);
+                    rounds += 1;
+                }
+                rounds
+            }
+            Format::RoundRobin => self.rounds(),
+        }
+    }
+
+    /// Игра текущего раунда: дуэли, переход к следующему раунду или подведение итогов.
+    /// `roll` бросает кубик; возвращает призы, если турнир завершился
+    pub fn play_round(
+        &mut self,
+        now: u64,
+        round_delay: Duration,
+        mut roll: impl FnMut() -> u8,
+    ) -> Vec<Payout> {
+        let round = self.round;
+        for game in self.matches.iter_mut().filter(|m| m.round == round) {
+            let Some(away) = game.away.filter(|_| game.winner.is_none()) else {
+                continue;
+            };
+            let score = loop {
+                let score = (roll(), roll());
+                if score.0 != score.1 {
+                    break score;
+                }
+            };
+            game.score = Some(score);
+            game.winner = Some(if score.0 > score.1 { game.home } else { away });
+        }
+
+        if self.format == Format::Knockout {
+            let winners: Vec<u64> = self.round_matches(round).filter_map(|m| m.winner).collect();
+            if winners.len() > 1 {
+                self.matches.extend(pair_up(round + 1, &winners));
+            }
+        }
+        if round < self.rounds() {
+            self.round += 1;
+            self.next_round_at = Some(now + round_delay.as_secs());
+            return Vec::new();
+        }
+
+        self.stage = Stage::Finished;
+        self.next_round_at = None;
+        self.prizes = self.prizes_for(&self.placings(), self.pool());
+        self.prizes.clone()
+    }
+
+    /// Победы участника (без учета проходов без соперника)
+    pub fn wins(&self, user_id: u64) -> u32 {
+        self.matches
+            .iter()
+            .filter(|m| m.away.is_some() && m.winner == Some(user_id))
+            .count() as u32
+    }
+
+    /// Сумма решающих бросков участника: дополнительный показатель в круговой системе
+    fn points(&self, user_id: u64) -> u32 {
+        self.matches
+            .iter()
+            .filter_map(|m| {
+                let (home, away) = m.score?;
+                if m.home == user_id {
+                    Some(u32::from(home))
+                } else if m.away == Some(user_id) {
+                    Some(u32::from(away))
+                } else {
+                    None
+                }
+            })
+            .sum()
+    }
+
+    /// Места по итогам; участники одного места (проигравшие в одном раунде олимпийской
+    /// сетки) идут одной группой
+    pub fn placings(&self) -> Vec<Vec<u64>> {
+        match self.format {
+            Format::Knockout => {
+                let mut places = Vec::new();
+                if let Some(champion) = self.round_matches(self.rounds()).find_map(|m| m.winner) {
+                    places.push(vec![champion]);
+                }
+                for round in (1..=self.rounds()).rev() {
+                    let losers: Vec<u64> =
+                        self.round_matches(round).filter_map(Match::loser).collect();
+                    if !losers.is_empty() {
+                        places.push(losers);
+                    }
+                }
+                places
+            }
+            Format::RoundRobin => self.standings().into_iter().map(|id| vec![id]).collect(),
+        }
+    }
+
+    /// Таблица круговой системы: по победам, затем по сумме бросков
+    pub fn standings(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.entrants.iter().map(|e| e.user_id).collect();
+        ids.sort_by_key(|&id| std::cmp::Reverse((self.wins(id), self.points(id))));
+        ids
+    }
+
+    /// Деление фонда по долям мест; доля места делится поровну между его участниками,
+    /// остаток от округления и доли незанятых мест достаются победителю
+    fn prizes_for(&self, placings: &[Vec<u64>], pool: i64) -> Vec<Payout> {
+        let mut prizes: Vec<Payout> = Vec::new();
+        for (group, share) in placings.iter().zip(&self.shares) {
+            let amount = pool * i64::from(*share) / 100 / group.len() as i64;
+            prizes.extend(group.iter().map(|&id| (id, amount)));
+        }
+        let rest = pool - prizes.iter().map(|(_, amount)| amount).sum::<i64>();
+        match prizes.first_mut() {
+            Some((_, amount)) => *amount += rest,
+            None => return Vec::new(),
+        }
+        prizes.retain(|(_, amount)| *amount > 0);
+        prizes
+    }
+}
+
+/// Пары раунда по порядку; нечетный последний проходит без соперника
+fn pair_up(round: u32, players: &[u64]) -> Vec<Match> {
+    players
+        .chunks(2)
+        .map(|pair| Match::new(round, pair[0], pair.get(1).copied()))
+        .collect()
+}
+
+/// Расписание круговой системы методом «карусели»: за раунд каждый играет один матч
+fn round_robin(players: &[u64]) -> Vec<Match> {
+    let mut slots: Vec<Option<u64>> = players.iter().copied().map(Some).collect();
+    if slots.len() % 2 == 1 {
+        slots.push(None);
+    }
+    let n = slots.len();
+    let mut matches = Vec::new();
+    for round in 1..n as u32 {
+        for i in 0..n / 2 {
+            // Свободный от игры в этом раунде участник в сетку не попадает
+            if let (Some(home), Some(away)) = (slots[i], slots[n - 1 - i]) {
+                matches.push(Match::new(round, home, Some(away)));
+            }
+        }
+        // Первый остается на месте, остальные сдвигаются по кругу
+        slots[1..].rotate_right(1);
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const NOW: u64 = 1_700_000_000;
+    const ROUND: Duration = Duration::from_secs(300);
+
+    fn tournament(format: Format, players: u64) -> Tournament {
+        let config = TournamentConfig::default();
+        let mut tournament = Tournament::new(1, Lang::Ru, format, &config, NOW);
+        for user_id in 1..=players {
+            let entrant = Entrant {
+                user_id,
+                name: format!("player{}", user_id),
+            };
+            let mut profile = UserProfile::new(100);
+            tournament.join(entrant, &mut profile, 32).unwrap();
+            assert_eq!(profile.balance, 90);
+        }
+        tournament
+    }
+
+    /// Играет все раунды; у игрока с большим id бросок всегда больше
+    fn play_out(tournament: &mut Tournament) -> Vec<Payout> {
+        let mut rng = StdRng::seed_from_u64(7);
+        tournament.start(NOW, ROUND, &mut rng).unwrap();
+        let mut now = NOW;
+        loop {
+            now += ROUND.as_secs();
+            assert!(tournament.is_due(now));
+            let matches = tournament.matches.clone();
+            let current: Vec<&Match> = matches
+                .iter()
+                .filter(|m| m.round == tournament.round)
+                .collect();
+            let mut rolls = current
+                .iter()
+                .filter(|m| m.winner.is_none())
+                .flat_map(|m| {
+                    let away = m.away.unwrap();
+                    [m.home as u8, away as u8]
+                })
+                .collect::<Vec<_>>()
+                .into_iter();
+            let prizes = tournament.play_round(now, ROUND, || rolls.next().unwrap());
+            if tournament.stage == Stage::Finished {
+                return prizes;
+            }
+        }
+    }
+
+    #[test]
+    fn test_join_rules() {
+        let mut tournament = tournament(Format::Knockout, 2);
+        let mut poor = UserProfile::new(5);
+        let entrant = |user_id| Entrant {
+            user_id,
+            name: String::new(),
+        };
+        assert_eq!(
+            tournament.join(entrant(1), &mut UserProfile::new(100), 32),
+            Err(TournamentError::AlreadyJoined)
+        );
+        assert_eq!(
+            tournament.join(entrant(3), &mut poor, 32),
+            Err(TournamentError::NotEnoughChips { fee: 10 })
+        );
+        assert_eq!(poor.balance, 5);
+        assert_eq!(
+            tournament.join(entrant(3), &mut UserProfile::new(100), 2),
+            Err(TournamentError::Full)
+        );
+    }
+
+    #[test]
+    fn test_knockout_with_bye_and_prizes() {
+        let mut tournament = tournament(Format::Knockout, 5);
+        let prizes = play_out(&mut tournament);
+
+        // 5 участников: 3 раунда, сильнейший (id 5) выигрывает
+        assert_eq!(tournament.rounds(), 3);
+        assert_eq!(tournament.total_rounds(), 3);
+        assert_eq!(tournament.placings()[0], vec![5]);
+        // Фонд 50: 60% победителю, 30% финалисту, 10% проигравшему во втором раунде
+        let amounts: Vec<i64> = prizes.iter().map(|(_, amount)| *amount).collect();
+        assert_eq!(amounts, vec![30, 15, 5]);
+        assert_eq!(prizes[0].0, 5);
+        assert!(!tournament.is_due(u64::MAX));
+    }
+
+    #[test]
+    fn test_round_robin_everyone_meets_everyone() {
+        let mut tournament = tournament(Format::RoundRobin, 5);
+        play_out(&mut tournament);
+
+        assert_eq!(tournament.rounds(), 5);
+        assert_eq!(tournament.matches.len(), 10);
+        for a in 1..=5 {
+            for b in a + 1..=5 {
+                let meetings = tournament
+                    .matches
+                    .iter()
+                    .filter(|m| {
+                        (m.home, m.away) == (a, Some(b)) || (m.home, m.away) == (b, Some(a))
+                    })
+                    .count();
+                assert_eq!(meetings, 1, "{} и {}", a, b);
+            }
+        }
+        assert_eq!(tournament.standings(), vec![5, 4, 3, 2, 1]);
+        assert_eq!(tournament.wins(5), 4);
+    }
+
+    #[test]
+    fn test_cancel_refunds_and_survives_restart() {
+        let mut tournament = tournament(Format::Knockout, 1);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            tournament.start(NOW, ROUND, &mut rng),
+            Err(TournamentError::NotEnoughPlayers)
+        );
+        assert!(tournament.is_due(NOW));
+        assert_eq!(tournament.cancel(), vec![(1, 10)]);
+        assert!(tournament.is_over());
+
+        // Состояние целиком переживает сохранение в хранилище
+        let mut running = self::tournament(Format::Knockout, 4);
+        running.start(NOW, ROUND, &mut rng).unwrap();
+        let json = serde_json::to_string(&running).unwrap();
+        let restored: Tournament = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, running);
+        assert_eq!(restored.next_round_at, Some(NOW + 300));
+    }
+}
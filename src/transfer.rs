@@ -0,0 +1,162 @@
+//! Переводы фишек между игроками (`/give`).
+//!
+//! Перевод подтверждается кнопкой отправителя, ограничен суммой одного перевода и суточным
+//! лимитом отправленного (по UTC) и проводится в журнал двумя записями: `transfer_out` у
+//! отправителя и `transfer_in` у получателя, каждая со ссылкой на второго участника.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::TransferConfig;
+use crate::ledger::Kind;
+use crate::storage::UserProfile;
+
+const DAY: u64 = 86_400;
+
+/// Отправленное за сутки
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferState {
+    /// День (номер суток UTC) последнего перевода
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<u64>,
+    /// Сумма, отправленная в этот день
+    pub sent: i64,
+}
+
+/// Почему перевод не выполнен
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferError {
+    /// Сумма вне допустимого диапазона
+    Amount {
+        min: i64,
+        max: i64,
+    },
+    /// Перевод самому себе
+    SelfTransfer,
+    NotEnoughChips {
+        balance: i64,
+    },
+    /// Суточный лимит исчерпан; `left` — сколько еще можно отправить сегодня
+    DailyLimit {
+        left: i64,
+    },
+    /// Баланс отправителя или получателя заморожен оператором
+    Frozen,
+}
+
+impl TransferError {
+    /// Суффикс ключа строки с текстом ошибки
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TransferError::Amount { .. } => "amount",
+            TransferError::SelfTransfer => "self",
+            TransferError::NotEnoughChips { .. } => "no_chips",
+            TransferError::DailyLimit { .. } => "limit",
+            TransferError::Frozen => "frozen",
+        }
+    }
+}
+
+/// Проверка суммы до подтверждения
+pub fn check_amount(amount: i64, config: &TransferConfig) -> Result<(), TransferError> {
+    if (config.min_amount..=config.max_amount).contains(&amount) {
+        Ok(())
+    } else {
+        Err(TransferError::Amount {
+            min: config.min_amount,
+            max: config.max_amount,
+        })
+    }
+}
+
+/// Перевод `amount` фишек от `sender` (id `from`) к `recipient` (id `to`)
+pub fn transfer(
+    (from, sender): (u64, &mut UserProfile),
+    (to, recipient): (u64, &mut UserProfile),
+    amount: i64,
+    config: &TransferConfig,
+    now: u64,
+) -> Result<(), TransferError> {
+    check_amount(amount, config)?;
+    if from == to {
+        return Err(TransferError::SelfTransfer);
+    }
+    if sender.frozen || recipient.frozen {
+        return Err(TransferError::Frozen);
+    }
+    if sender.balance < amount {
+        return Err(TransferError::NotEnoughChips {
+            balance: sender.balance,
+        });
+    }
+    let today = now / DAY;
+    let sent = match sender.transfers.day {
+        Some(day) if day == today => sender.transfers.sent,
+        _ => 0,
+    };
+    if sent + amount > config.daily_limit {
+        return Err(TransferError::DailyLimit {
+            left: (config.daily_limit - sent).max(0),
+        });
+    }
+    sender.transfers = TransferState {
+        day: Some(today),
+        sent: sent + amount,
+    };
+    sender.post_with_peer(Kind::TransferOut, -amount, to);
+    recipient.post_with_peer(Kind::TransferIn, amount, from);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const NOON: u64 = 20_000 * DAY + 12 * 3600;
+
+    #[test]
+    fn test_transfer_moves_chips_within_limits() {
+        let config = TransferConfig::default();
+        let mut alice = UserProfile::new(3000);
+        let mut bob = UserProfile::new(0);
+
+        transfer((1, &mut alice), (2, &mut bob), 500, &config, NOON).unwrap();
+        assert_eq!((alice.balance, bob.balance), (2500, 500));
+
+        assert_eq!(
+            transfer((1, &mut alice), (1, &mut bob), 10, &config, NOON),
+            Err(TransferError::SelfTransfer)
+        );
+        assert_eq!(
+            transfer((1, &mut alice), (2, &mut bob), 0, &config, NOON),
+            Err(TransferError::Amount { min: 1, max: 1000 })
+        );
+        assert_eq!(
+            transfer((2, &mut bob), (1, &mut alice), 600, &config, NOON),
+            Err(TransferError::NotEnoughChips { balance: 500 })
+        );
+
+        bob.frozen = true;
+        assert_eq!(
+            transfer((1, &mut alice), (2, &mut bob), 10, &config, NOON),
+            Err(TransferError::Frozen)
+        );
+    }
+
+    #[test]
+    fn test_daily_limit_resets_next_day() {
+        let config = TransferConfig::default();
+        let mut alice = UserProfile::new(10_000);
+        let mut bob = UserProfile::new(0);
+        for _ in 0..2 {
+            transfer((1, &mut alice), (2, &mut bob), 1000, &config, NOON).unwrap();
+        }
+        assert_eq!(
+            transfer((1, &mut alice), (2, &mut bob), 1, &config, NOON + 60),
+            Err(TransferError::DailyLimit { left: 0 })
+        );
+        transfer((1, &mut alice), (2, &mut bob), 1000, &config, NOON + DAY).unwrap();
+        assert_eq!(bob.balance, 3000);
+    }
+}